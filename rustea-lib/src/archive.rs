@@ -0,0 +1,227 @@
+use std::{
+    cmp::Ordering,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    distance::{ExactEquality, GenotypeEquality},
+    fitness::{Fitness, OptimizationGoal},
+    gene::Allele,
+    genotype::Genotype,
+    individual::Individual,
+};
+
+// A hall of fame of the best distinct individuals seen so far, sorted from best to worst
+// Genotypes that are equal under `E` are only archived once
+#[derive(Debug, Clone)]
+pub struct Archive<Gnt, A, F, E = ExactEquality>
+where
+    A: Allele,
+    F: Fitness,
+    E: GenotypeEquality<Gnt, A>,
+    Gnt: Genotype<A>,
+{
+    capacity: usize,
+    goal: OptimizationGoal,
+    equality: E,
+    elites: Vec<Individual<Gnt, A, F>>,
+}
+
+// An archive that several concurrently running populations contribute to. Contributions lock the
+// archive, so populations should only contribute occasionally (e.g. once every few generations).
+pub type SharedArchive<Gnt, A, F, E = ExactEquality> = Arc<Mutex<Archive<Gnt, A, F, E>>>;
+
+impl<Gnt, A, F> Archive<Gnt, A, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    pub fn with_capacity(capacity: usize, goal: OptimizationGoal) -> Self {
+        Self::with_equality(capacity, goal, ExactEquality)
+    }
+}
+
+impl<Gnt, A, F, E> Archive<Gnt, A, F, E>
+where
+    A: Allele,
+    F: Fitness,
+    E: GenotypeEquality<Gnt, A>,
+    Gnt: Genotype<A>,
+{
+    pub fn with_equality(capacity: usize, goal: OptimizationGoal, equality: E) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+
+        Self {
+            capacity,
+            goal,
+            equality,
+            elites: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn shared(self) -> SharedArchive<Gnt, A, F, E> {
+        Arc::new(Mutex::new(self))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.elites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elites.is_empty()
+    }
+
+    pub fn best(&self) -> Option<&Individual<Gnt, A, F>> {
+        self.elites.first()
+    }
+
+    pub fn elites(&self) -> &[Individual<Gnt, A, F>] {
+        &self.elites
+    }
+
+    fn cmp(&self, a: &F, b: &F) -> Ordering {
+        self.goal.cmp(a, b)
+    }
+
+    // Adds an evaluated individual if its genotype is not archived yet and it is better than the
+    // worst elite of a full archive. Returns whether the individual was added.
+    pub fn insert(&mut self, individual: &Individual<Gnt, A, F>) -> bool {
+        if self
+            .elites
+            .iter()
+            .any(|elite| self.equality.equal(elite.genotype(), individual.genotype()))
+        {
+            return false;
+        }
+
+        if self.elites.len() == self.capacity {
+            let worst = self.elites.last().unwrap();
+            if self.cmp(&individual.fitness(), &worst.fitness()).is_ge() {
+                return false;
+            }
+            self.elites.pop();
+        }
+
+        let idx = self
+            .elites
+            .partition_point(|elite| self.cmp(&elite.fitness(), &individual.fitness()).is_le());
+        self.elites.insert(idx, individual.clone());
+
+        true
+    }
+
+    pub fn extend<'b, I>(&mut self, individuals: I)
+    where
+        I: IntoIterator<Item = &'b Individual<Gnt, A, F>>,
+        Individual<Gnt, A, F>: 'b,
+    {
+        for individual in individuals {
+            self.insert(individual);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{
+        gene::BoolDomain, genome::Genome, selection::TruncationSelection,
+        simplega::SimpleGABuilder, variation::UniformCrossover,
+    };
+
+    const N: usize = 64;
+
+    type Gnt = [bool; N];
+
+    fn one_max(genotype: &Gnt) -> usize {
+        genotype.iter().filter(|bit| *bit).count()
+    }
+
+    fn evaluated(genotype: Gnt) -> Individual<Gnt, bool, usize> {
+        let mut individual = Individual::from_genotype(genotype);
+        individual.set_fitness(one_max(&genotype));
+        individual
+    }
+
+    #[test]
+    fn keeps_best_distinct_individuals() {
+        let mut archive = Archive::with_capacity(2, OptimizationGoal::Maximize);
+
+        let mut genotype = [false; N];
+        assert!(archive.insert(&evaluated(genotype)));
+
+        genotype[0] = true;
+        assert!(archive.insert(&evaluated(genotype)));
+        assert!(!archive.insert(&evaluated(genotype)));
+
+        genotype[1] = true;
+        assert!(archive.insert(&evaluated(genotype)));
+        assert!(!archive.insert(&evaluated([false; N])));
+
+        let fitnesses: Vec<_> = archive.elites().iter().map(|idv| idv.fitness()).collect();
+        assert_eq!(fitnesses, vec![2, 1]);
+    }
+
+    #[test]
+    fn shared_archive_holds_best_of_all_islands() {
+        const ISLANDS: u64 = 4;
+        const GENERATIONS: usize = 20;
+        const MIGRATION_INTERVAL: usize = 5;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let archive = Archive::with_capacity(10, OptimizationGoal::Maximize).shared();
+
+        let island_bests: Vec<usize> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..ISLANDS)
+                .map(|island| {
+                    let genome = &genome;
+                    let archive = &archive;
+
+                    scope.spawn(move || {
+                        let mut ga = SimpleGABuilder::new()
+                            .genome(genome)
+                            .random_population(20)
+                            .evaluation_function(&one_max)
+                            .goal(OptimizationGoal::Maximize)
+                            .selection(TruncationSelection::default())
+                            .variation(UniformCrossover::default())
+                            .seed(island)
+                            .build();
+                        ga.initialize();
+
+                        let mut best = ga.best_individual().unwrap().fitness();
+                        for generation in 1..=GENERATIONS {
+                            ga.step();
+                            best = best.max(ga.best_individual().unwrap().fitness());
+
+                            if generation % MIGRATION_INTERVAL == 0 {
+                                ga.contribute_to(archive, 3);
+                            }
+                        }
+
+                        best
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let archive = archive.lock().unwrap();
+        assert_eq!(
+            archive.best().unwrap().fitness(),
+            *island_bests.iter().max().unwrap()
+        );
+        assert_eq!(archive.len(), archive.capacity());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    fitness::Fitness,
+    gene::{Allele, Gene},
+    genotype::Genotype,
+    selection::SelectionOperator,
+    simplega::{SimpleGABuilder, Status},
+    statistics::StatsCollector,
+    variation::VariationOperator,
+};
+
+// The outcomes of repeated, independent runs of the same configuration
+#[derive(Debug, Default)]
+pub struct BenchmarkResult {
+    statuses: Vec<Status>,
+}
+
+impl BenchmarkResult {
+    pub fn from_statuses(statuses: Vec<Status>) -> Self {
+        Self { statuses }
+    }
+
+    pub fn statuses(&self) -> &[Status] {
+        &self.statuses
+    }
+
+    pub fn runs(&self) -> usize {
+        self.statuses.len()
+    }
+
+    pub fn successes(&self) -> usize {
+        self.evaluations_to_target().len()
+    }
+
+    // Runs that stopped before reaching the target; their evaluations to target are censored, as
+    // it is only known to exceed the evaluations they used
+    pub fn failures(&self) -> usize {
+        self.runs() - self.successes()
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        self.successes() as f64 / self.runs() as f64
+    }
+
+    // The number of evaluations at which each successful run reached the target (or met its stop
+    // condition), in run order
+    pub fn evaluations_to_target(&self) -> Vec<usize> {
+        self.statuses
+            .iter()
+            .filter_map(|status| match status {
+                Status::TargetReached(evaluations) | Status::ConditionMet(evaluations) => {
+                    Some(*evaluations)
+                }
+                Status::BudgetReached(_) | Status::Converged(_) | Status::StoppedByCallback(_) => {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    // Statistics over the successful runs only, or None if there are none
+    pub fn mean_evaluations_to_target(&self) -> Option<f64> {
+        let evaluations = self.evaluations_to_target();
+        if evaluations.is_empty() {
+            return None;
+        }
+
+        Some(evaluations.iter().sum::<usize>() as f64 / evaluations.len() as f64)
+    }
+
+    pub fn median_evaluations_to_target(&self) -> Option<f64> {
+        let mut evaluations = self.evaluations_to_target();
+        if evaluations.is_empty() {
+            return None;
+        }
+
+        evaluations.sort_unstable();
+        let mid = evaluations.len() / 2;
+
+        if evaluations.len().is_multiple_of(2) {
+            Some((evaluations[mid - 1] + evaluations[mid]) as f64 / 2.0)
+        } else {
+            Some(evaluations[mid] as f64)
+        }
+    }
+
+    // Sample standard deviation, which needs at least two successful runs
+    pub fn std_dev_evaluations_to_target(&self) -> Option<f64> {
+        let evaluations = self.evaluations_to_target();
+        if evaluations.len() < 2 {
+            return None;
+        }
+
+        let mean = self.mean_evaluations_to_target()?;
+        let sum_of_squares: f64 = evaluations
+            .iter()
+            .map(|&evaluations| (evaluations as f64 - mean).powi(2))
+            .sum();
+
+        Some((sum_of_squares / (evaluations.len() - 1) as f64).sqrt())
+    }
+
+    // The expected running time: the evaluations of all runs, including the censored ones, per
+    // successful run. Unlike the mean over successful runs, this penalizes configurations that
+    // often fail
+    pub fn expected_running_time(&self) -> Option<f64> {
+        if self.successes() == 0 {
+            return None;
+        }
+
+        let total: usize = self
+            .statuses
+            .iter()
+            .map(|status| match status {
+                Status::TargetReached(evaluations)
+                | Status::BudgetReached(evaluations)
+                | Status::Converged(evaluations)
+                | Status::StoppedByCallback(evaluations)
+                | Status::ConditionMet(evaluations) => *evaluations,
+            })
+            .sum();
+
+        Some(total as f64 / self.successes() as f64)
+    }
+}
+
+// Runs a fresh GA from the builder `runs` times, each with the given evaluation budget. Run `i`
+// derives its seed from the seed of the builder (see `SimpleGABuilder::derive_seed`), so that a
+// seeded benchmark is reproducible while its runs differ.
+pub fn benchmark<Gnt, A, G, F, S, V, C>(
+    builder: &SimpleGABuilder<'_, Gnt, A, G, F, S, V, C>,
+    runs: usize,
+    evaluation_budget: usize,
+) -> BenchmarkResult
+where
+    A: Allele,
+    G: Gene<A>,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    BenchmarkResult::from_statuses(
+        (0..runs)
+            .map(|run| {
+                builder
+                    .clone()
+                    .derive_seed(run as u64)
+                    .build()
+                    .run(evaluation_budget)
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::{
+        fitness::OptimizationGoal, gene::BoolDomain, genome::Genome,
+        selection::TruncationSelection, variation::UniformCrossover,
+    };
+
+    const N: usize = 16;
+
+    fn one_max(genotype: &[bool; N]) -> usize {
+        genotype.iter().filter(|bit| *bit).count()
+    }
+
+    #[test]
+    fn statistics_of_evaluations_to_target() {
+        let result = BenchmarkResult::from_statuses(vec![
+            Status::TargetReached(100),
+            Status::BudgetReached(1000),
+            Status::TargetReached(300),
+            Status::TargetReached(200),
+            Status::Converged(400),
+        ]);
+
+        assert_eq!(result.runs(), 5);
+        assert_eq!(result.successes(), 3);
+        assert_eq!(result.failures(), 2);
+        assert_eq!(result.evaluations_to_target(), vec![100, 300, 200]);
+        assert_abs_diff_eq!(result.success_rate(), 0.6);
+        assert_abs_diff_eq!(result.mean_evaluations_to_target().unwrap(), 200.0);
+        assert_abs_diff_eq!(result.median_evaluations_to_target().unwrap(), 200.0);
+        assert_abs_diff_eq!(result.std_dev_evaluations_to_target().unwrap(), 100.0);
+        assert_abs_diff_eq!(result.expected_running_time().unwrap(), 2000.0 / 3.0);
+    }
+
+    #[test]
+    fn statistics_without_successful_runs() {
+        let result = BenchmarkResult::from_statuses(vec![Status::BudgetReached(1000)]);
+
+        assert_eq!(result.successes(), 0);
+        assert_eq!(result.mean_evaluations_to_target(), None);
+        assert_eq!(result.median_evaluations_to_target(), None);
+        assert_eq!(result.std_dev_evaluations_to_target(), None);
+        assert_eq!(result.expected_running_time(), None);
+    }
+
+    #[test]
+    fn benchmark_one_max() {
+        const RUNS: usize = 5;
+        const BUDGET: usize = 20_000;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(100)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .target(N);
+
+        let result = benchmark(&builder, RUNS, BUDGET);
+
+        assert_eq!(result.runs(), RUNS);
+        assert_eq!(result.successes(), RUNS);
+
+        let mean = result.mean_evaluations_to_target().unwrap();
+        assert!(mean >= 100.0 && mean <= BUDGET as f64);
+        assert_abs_diff_eq!(result.expected_running_time().unwrap(), mean);
+        assert!(result.std_dev_evaluations_to_target().unwrap() >= 0.0);
+    }
+
+    #[test]
+    fn seeded_benchmark_is_reproducible() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .target(N)
+            .seed(5);
+
+        let result = benchmark(&builder, 5, 5_000);
+        let again = benchmark(&builder, 5, 5_000);
+        assert_eq!(
+            format!("{:?}", result.statuses()),
+            format!("{:?}", again.statuses())
+        );
+
+        // Every run has its own seed
+        let evaluations = result.evaluations_to_target();
+        assert!(evaluations.iter().any(|&e| e != evaluations[0]));
+    }
+}
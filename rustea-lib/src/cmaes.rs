@@ -0,0 +1,483 @@
+use ndarray::{Array, Ix1, Ix2};
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+
+use crate::{
+    fitness::{Fitness, FitnessFunc, OptimizationGoal},
+    gene::{Allele, Real, RealDomain, RealGene},
+    genome::Genome,
+    genotype::Genotype,
+    individual::Individual,
+    parallel::*,
+    rng::{item_rngs, SeedStream, SeededRng},
+    simplega::Status,
+    statistics::regularized_cholesky,
+    types::CollectUnsafe,
+};
+
+// A sampled offspring, along with the standard normal vector it was generated from and its step
+// from the mean, before scaling by the step size
+struct Sample<Gnt, A, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    individual: Individual<Gnt, A, F>,
+    z: Array<f64, Ix1>,
+    y: Array<f64, Ix1>,
+}
+
+// The default strategy parameters for a given dimension and number of offspring
+#[derive(Debug, Clone)]
+struct Parameters {
+    lambda: usize,
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    chi_n: f64,
+}
+
+impl Parameters {
+    fn new(n: usize, lambda: usize) -> Self {
+        let n = n as f64;
+        let mu = lambda / 2;
+
+        let raw: Vec<f64> = (1..=mu)
+            .map(|i| ((lambda as f64 + 1.0) / 2.0).ln() - (i as f64).ln())
+            .collect();
+        let sum: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / sum).collect();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let c_1 = 2.0 / ((n + 1.3).powi(2) + mu_eff);
+
+        Self {
+            lambda,
+            weights,
+            mu_eff,
+            c_sigma,
+            d_sigma: 1.0 + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma,
+            c_c: (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n),
+            c_1,
+            c_mu: (1.0 - c_1)
+                .min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0).powi(2) + mu_eff)),
+            chi_n: n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n)),
+        }
+    }
+}
+
+// Covariance matrix adaptation evolution strategy, (mu/mu_w, lambda)-CMA-ES with the default
+// strategy parameters of Hansen's tutorial. The covariance is factorized with a Cholesky
+// decomposition instead of an eigendecomposition, and samples are clamped to the domain of each gene.
+pub struct CmaEs<'a, Gnt, A, D, F>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    genome: &'a Genome<Gnt, A, RealGene<A, D>>,
+    fitness_func: FitnessFunc<'a, Gnt, A, F>,
+    target_fitness: Option<F>,
+    params: Parameters,
+    seed: u64,
+    rng: SeededRng,
+    mean: Array<f64, Ix1>,
+    step_size: f64,
+    covariance: Array<f64, Ix2>,
+    p_sigma: Array<f64, Ix1>,
+    p_c: Array<f64, Ix1>,
+    generation: usize,
+    best: Option<Individual<Gnt, A, F>>,
+}
+
+impl<'a, Gnt, A, D, F> CmaEs<'a, Gnt, A, D, F>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    pub fn best_individual(&self) -> Option<&Individual<Gnt, A, F>> {
+        self.best.as_ref()
+    }
+
+    pub fn mean(&self) -> &Array<f64, Ix1> {
+        &self.mean
+    }
+
+    pub fn step_size(&self) -> f64 {
+        self.step_size
+    }
+
+    pub fn covariance(&self) -> &Array<f64, Ix2> {
+        &self.covariance
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.fitness_func.evaluations()
+    }
+
+    pub fn fitness_func(&self) -> &FitnessFunc<'a, Gnt, A, F> {
+        &self.fitness_func
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn target_reached(&self) -> bool {
+        let Some(target) = self.target_fitness else {
+            return false;
+        };
+
+        self.best_individual()
+            .is_some_and(|idv| self.fitness_func.cmp(&idv.fitness(), &target).is_le())
+    }
+
+    // Samples and evaluates a generation of offspring and updates the search distribution
+    pub fn step(&mut self) {
+        self.step_within(None)
+    }
+
+    // As `step`, but samples at most `max_new_evaluations` offspring. A generation smaller than
+    // lambda is only used to track the best individual, not to update the distribution.
+    pub fn step_within(&mut self, max_new_evaluations: Option<usize>) {
+        self.rng = SeedStream::new(self.seed).generation(self.generation).rng();
+
+        let n = Gnt::LEN;
+        let genome = self.genome;
+
+        let mean = self.mean.clone();
+
+        // A degenerate covariance cannot be recovered from; start over from the unit matrix
+        let lower = regularized_cholesky(&self.covariance).unwrap_or_else(|_| {
+            self.covariance = Array::eye(n);
+            self.p_c = Array::zeros(n);
+            Array::eye(n)
+        });
+
+        let count =
+            max_new_evaluations.map_or(self.params.lambda, |cap| cap.min(self.params.lambda));
+        let step_size = self.step_size;
+        let fitness_func = &self.fitness_func;
+
+        let mut samples: Vec<Sample<Gnt, A, F>> = item_rngs(&mut self.rng, count)
+            .into_par_iter()
+            .map(|mut rng| {
+                let z: Array<f64, Ix1> = (0..n).map(|_| rng.sample(StandardNormal)).collect();
+                let y = lower.dot(&z);
+
+                let genotype = genome
+                    .iter()
+                    .zip(&mean + &(&y * step_size))
+                    .map(|(gene, x)| gene.domain().clamp(A::from(x).unwrap()))
+                    .collect_unsafe();
+
+                let mut individual = Individual::from_genotype(genotype);
+                fitness_func.evaluate(&mut individual);
+
+                Sample { individual, z, y }
+            })
+            .collect();
+
+        samples.sort_by(|a, b| fitness_func.cmp(&a.individual.fitness(), &b.individual.fitness()));
+
+        if let Some(best) = samples.first() {
+            let improved = self.best.as_ref().is_none_or(|current| {
+                fitness_func
+                    .cmp(&best.individual.fitness(), &current.fitness())
+                    .is_lt()
+            });
+            if improved {
+                self.best = Some(best.individual.clone());
+            }
+        }
+
+        if count < self.params.lambda {
+            return;
+        }
+
+        self.update(mean, &samples);
+    }
+
+    fn update(&mut self, mean: Array<f64, Ix1>, samples: &[Sample<Gnt, A, F>]) {
+        let n = Gnt::LEN as f64;
+        let selected = &samples[..self.params.weights.len()];
+
+        // Weighted recombination of the best mu steps
+        let mut y_w: Array<f64, Ix1> = Array::zeros(Gnt::LEN);
+        let mut z_w: Array<f64, Ix1> = Array::zeros(Gnt::LEN);
+        for (sample, w) in selected.iter().zip(&self.params.weights) {
+            y_w.scaled_add(*w, &sample.y);
+            z_w.scaled_add(*w, &sample.z);
+        }
+
+        self.mean = mean + &(&y_w * self.step_size);
+        self.generation += 1;
+
+        // Evolution paths
+        self.p_sigma = &self.p_sigma * (1.0 - self.params.c_sigma)
+            + &z_w
+                * (self.params.c_sigma * (2.0 - self.params.c_sigma) * self.params.mu_eff).sqrt();
+
+        let p_sigma_norm = self.p_sigma.dot(&self.p_sigma).sqrt();
+        let correction =
+            (1.0 - (1.0 - self.params.c_sigma).powi(2 * self.generation as i32)).sqrt();
+        let h_sigma = p_sigma_norm / correction < (1.4 + 2.0 / (n + 1.0)) * self.params.chi_n;
+        let h_sigma = if h_sigma { 1.0 } else { 0.0 };
+
+        self.p_c = &self.p_c * (1.0 - self.params.c_c)
+            + &y_w
+                * (h_sigma
+                    * (self.params.c_c * (2.0 - self.params.c_c) * self.params.mu_eff).sqrt());
+
+        // Rank-one and rank-mu updates of the covariance
+        let mut rank_mu: Array<f64, Ix2> = Array::zeros((Gnt::LEN, Gnt::LEN));
+        for (sample, w) in selected.iter().zip(&self.params.weights) {
+            rank_mu += &(outer(&sample.y, &sample.y) * *w);
+        }
+
+        let rank_one = outer(&self.p_c, &self.p_c)
+            + &self.covariance * ((1.0 - h_sigma) * self.params.c_c * (2.0 - self.params.c_c));
+
+        let covariance = &self.covariance * (1.0 - self.params.c_1 - self.params.c_mu)
+            + rank_one * self.params.c_1
+            + rank_mu * self.params.c_mu;
+
+        // Keep the matrix exactly symmetric despite rounding errors
+        self.covariance = (&covariance + &covariance.t()) / 2.0;
+
+        // Cumulative step size adaptation
+        self.step_size *= ((self.params.c_sigma / self.params.d_sigma)
+            * (p_sigma_norm / self.params.chi_n - 1.0))
+            .exp();
+    }
+
+    pub fn run(&mut self, evaluation_budget: usize) -> Status {
+        while self.fitness_func.evaluations() < evaluation_budget {
+            if self.target_reached() {
+                return Status::TargetReached(self.fitness_func.evaluations());
+            }
+
+            // Never exceed the budget in the final generation
+            self.step_within(Some(evaluation_budget - self.fitness_func.evaluations()));
+        }
+
+        if self.target_reached() {
+            return Status::TargetReached(self.fitness_func.evaluations());
+        }
+
+        Status::BudgetReached(self.fitness_func.evaluations())
+    }
+}
+
+pub struct CmaEsBuilder<'a, Gnt, A, D, F>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    genome: Option<&'a Genome<Gnt, A, RealGene<A, D>>>,
+    evaluation_func: Option<&'a (dyn Fn(&Gnt) -> F + Send + Sync)>,
+    goal: OptimizationGoal,
+    target_fitness: Option<F>,
+    lambda: Option<usize>,
+    step_size: f64,
+    mean: Option<Gnt>,
+    seed: Option<u64>,
+}
+
+impl<'a, Gnt, A, D, F> Default for CmaEsBuilder<'a, Gnt, A, D, F>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, Gnt, A, D, F> CmaEsBuilder<'a, Gnt, A, D, F>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    pub fn new() -> Self {
+        Self {
+            genome: None,
+            evaluation_func: None,
+            goal: OptimizationGoal::Minimize,
+            target_fitness: None,
+            lambda: None,
+            step_size: 1.0,
+            mean: None,
+            seed: None,
+        }
+    }
+
+    pub fn genome(mut self, genome: &'a Genome<Gnt, A, RealGene<A, D>>) -> Self {
+        self.genome = Some(genome);
+        self
+    }
+
+    pub fn goal(mut self, goal: OptimizationGoal) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    pub fn evaluation_function(mut self, func: &'a (dyn Fn(&Gnt) -> F + Send + Sync)) -> Self {
+        self.evaluation_func = Some(func);
+        self
+    }
+
+    pub fn target(mut self, fitness: F) -> Self {
+        self.target_fitness = Some(fitness);
+        self
+    }
+
+    // The number of offspring sampled per generation, of which the best half are recombined.
+    // Defaults to 4 + 3 ln(n).
+    pub fn lambda(mut self, lambda: usize) -> Self {
+        self.lambda = Some(lambda);
+        self
+    }
+
+    // The initial step size, relative to the initial (unit) covariance
+    pub fn step_size(mut self, step_size: f64) -> Self {
+        self.step_size = step_size;
+        self
+    }
+
+    // The initial mean of the search distribution; sampled uniformly from the genome by default
+    pub fn mean(mut self, genotype: Gnt) -> Self {
+        self.mean = Some(genotype);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> CmaEs<'a, Gnt, A, D, F> {
+        let Some(genome) = self.genome else {
+            panic!("Failed to build: genome not specified");
+        };
+
+        let Some(evaluation_func) = self.evaluation_func else {
+            panic!("Failed to build: evaluation function not specified");
+        };
+
+        let n = Gnt::LEN;
+        let lambda = self
+            .lambda
+            .unwrap_or(4 + (3.0 * (n as f64).ln()).floor() as usize);
+        assert!(lambda >= 2, "Failed to build: lambda must be at least 2");
+        assert!(
+            self.step_size > 0.0,
+            "Failed to build: the step size must be positive"
+        );
+
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let mut rng = SeededRng::seed_from_u64(seed);
+
+        let mean = match self.mean {
+            Some(genotype) => to_vector(&genotype),
+            None => to_vector(&genome.sample_uniform(&mut rng)),
+        };
+
+        CmaEs {
+            genome,
+            fitness_func: FitnessFunc::new(evaluation_func, self.goal),
+            target_fitness: self.target_fitness,
+            params: Parameters::new(n, lambda),
+            seed,
+            rng,
+            mean,
+            step_size: self.step_size,
+            covariance: Array::eye(n),
+            p_sigma: Array::zeros(n),
+            p_c: Array::zeros(n),
+            generation: 0,
+            best: None,
+        }
+    }
+}
+
+fn to_vector<Gnt, A>(genotype: &Gnt) -> Array<f64, Ix1>
+where
+    A: Allele + Real,
+    Gnt: Genotype<A>,
+{
+    genotype.iter().map(|a| a.to_f64().unwrap()).collect()
+}
+
+fn outer(a: &Array<f64, Ix1>, b: &Array<f64, Ix1>) -> Array<f64, Ix2> {
+    Array::from_shape_fn((a.len(), b.len()), |(i, j)| a[i] * b[j])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gene::ExclusiveRangeRealDomain, rdom};
+
+    const N: usize = 4;
+    type Gnt = [f64; N];
+
+    fn rosenbrock(genotype: &Gnt) -> f64 {
+        genotype
+            .windows(2)
+            .map(|x| 100.0 * (x[1] - x[0] * x[0]).powi(2) + (1.0 - x[0]).powi(2))
+            .sum()
+    }
+
+    #[test]
+    fn minimizes_rosenbrock() {
+        let genome = Genome::with_real_domain(&rdom!(-5.0..5.0));
+
+        let mut cmaes = CmaEsBuilder::new()
+            .genome(&genome)
+            .evaluation_function(&rosenbrock)
+            .goal(OptimizationGoal::Minimize)
+            .step_size(1.0)
+            .target(1e-8)
+            .seed(42)
+            .build();
+
+        let status = cmaes.run(20000);
+
+        assert!(matches!(status, Status::TargetReached(_)));
+        assert!(cmaes.best_individual().unwrap().fitness() < 1e-8);
+        assert!(cmaes.evaluations() <= 20000);
+    }
+
+    #[test]
+    fn final_generation_respects_budget() {
+        let genome = Genome::with_real_domain(&rdom!(-5.0..5.0));
+
+        let mut cmaes = CmaEsBuilder::new()
+            .genome(&genome)
+            .evaluation_function(&rosenbrock)
+            .lambda(10)
+            .build();
+
+        assert!(matches!(cmaes.run(25), Status::BudgetReached(25)));
+        assert_eq!(cmaes.generation(), 2);
+    }
+}
@@ -0,0 +1,149 @@
+use approx::AbsDiffEq;
+
+use crate::{
+    gene::{Allele, Real},
+    genotype::Genotype,
+};
+
+// A distance between two genotypes, used to measure how similar individuals are
+pub trait DistanceMetric<Gnt, A>: Clone + Send + Sync
+where
+    A: Allele,
+    Gnt: Genotype<A>,
+{
+    fn distance(&self, genotype_a: &Gnt, genotype_b: &Gnt) -> f64;
+}
+
+// The number of loci at which two genotypes differ
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hamming;
+
+impl<Gnt, A> DistanceMetric<Gnt, A> for Hamming
+where
+    A: Allele,
+    Gnt: Genotype<A>,
+{
+    fn distance(&self, genotype_a: &Gnt, genotype_b: &Gnt) -> f64 {
+        genotype_a
+            .iter()
+            .zip(genotype_b.iter())
+            .filter(|(a, b)| a != b)
+            .count() as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Euclidean;
+
+impl<Gnt, A> DistanceMetric<Gnt, A> for Euclidean
+where
+    A: Allele + Real,
+    Gnt: Genotype<A>,
+{
+    fn distance(&self, genotype_a: &Gnt, genotype_b: &Gnt) -> f64 {
+        genotype_a
+            .iter()
+            .zip(genotype_b.iter())
+            .map(|(a, b)| {
+                let diff = (a - b).to_f64().unwrap();
+                diff * diff
+            })
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+// Decides whether two genotypes are the same, e.g. when removing duplicates from a population
+pub trait GenotypeEquality<Gnt, A>: Clone + Send + Sync
+where
+    A: Allele,
+    Gnt: Genotype<A>,
+{
+    fn equal(&self, genotype_a: &Gnt, genotype_b: &Gnt) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExactEquality;
+
+impl<Gnt, A> GenotypeEquality<Gnt, A> for ExactEquality
+where
+    A: Allele,
+    Gnt: Genotype<A>,
+{
+    fn equal(&self, genotype_a: &Gnt, genotype_b: &Gnt) -> bool {
+        genotype_a.iter().eq(genotype_b.iter())
+    }
+}
+
+// Real-valued genotypes are equal if every pair of alleles differs by at most `epsilon`, as
+// exact equality of floats rarely holds after variation
+#[derive(Debug, Clone, Copy)]
+pub struct ApproximateEquality<A> {
+    epsilon: A,
+}
+
+impl<A> ApproximateEquality<A>
+where
+    A: Allele + Real,
+{
+    pub fn with_epsilon(epsilon: A) -> Self {
+        assert!(epsilon >= A::zero(), "epsilon must be non-negative");
+        Self { epsilon }
+    }
+
+    pub fn epsilon(&self) -> A {
+        self.epsilon
+    }
+}
+
+impl<Gnt, A> GenotypeEquality<Gnt, A> for ApproximateEquality<A>
+where
+    A: Allele + Real + AbsDiffEq<Epsilon = A>,
+    Gnt: Genotype<A>,
+{
+    fn equal(&self, genotype_a: &Gnt, genotype_b: &Gnt) -> bool {
+        genotype_a
+            .iter()
+            .zip(genotype_b.iter())
+            .all(|(a, b)| a.abs_diff_eq(&b, self.epsilon))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+
+    #[test]
+    fn hamming_on_bool_genotype() {
+        let a = [true, false, true, true, false];
+        let b = [true, true, false, true, false];
+
+        assert_eq!(Hamming.distance(&a, &b), 2.0);
+        assert_eq!(Hamming.distance(&a, &a), 0.0);
+    }
+
+    #[test]
+    fn euclidean_on_real_genotype() {
+        let a = [0.0, 3.0, 1.0];
+        let b = [4.0, 0.0, 1.0];
+
+        assert_abs_diff_eq!(Euclidean.distance(&a, &b), 5.0);
+        assert_abs_diff_eq!(
+            Euclidean.distance(&[1.5f32, -2.0], &[0.5, 0.0]),
+            5f64.sqrt()
+        );
+    }
+
+    #[test]
+    fn approximate_equality_within_epsilon() {
+        let equality = ApproximateEquality::with_epsilon(1e-6);
+        let a = [0.5, -1.0, 2.0];
+
+        assert!(equality.equal(&a, &[0.5 + 5e-7, -1.0, 2.0 - 5e-7]));
+        assert!(!equality.equal(&a, &[0.5 + 2e-6, -1.0, 2.0]));
+        assert!(!ExactEquality.equal(&a, &[0.5 + 5e-7, -1.0, 2.0]));
+        assert!(ExactEquality.equal(&a, &a));
+    }
+}
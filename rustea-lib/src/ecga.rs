@@ -1,7 +1,5 @@
 use std::marker::PhantomData;
 
-use rayon::prelude::{IntoParallelIterator, ParallelIterator};
-
 use crate::{
     fitness::{Fitness, FitnessFunc},
     gene::{Allele, Discrete, DiscreteDomain, DiscreteGene},
@@ -9,9 +7,20 @@ use crate::{
     genotype::Genotype,
     individual::Individual,
     model::{Factorization, MultivariateModel},
-    variation::VariationOperator,
+    parallel::*,
+    rng::{item_rngs, SeededRng},
+    variation::{gap_count, offspring_count, OperatorStatistics, VariationOperator},
 };
 
+// A merge accepted by the greedy model search: the two factors that were joined, and the combined
+// complexity of the model before and after joining them
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeDecision {
+    pub joined_factors: (Vec<usize>, Vec<usize>),
+    pub old_complexity: f64,
+    pub new_complexity: f64,
+}
+
 #[derive(Debug, Clone)]
 pub struct Ecga<'a, Gnt, A, D, F>
 where
@@ -22,6 +31,12 @@ where
 {
     genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
     p_best: f64,
+    warm_start: bool,
+    factorization: Option<Factorization>,
+    greedy_steps: usize,
+    merge_decisions: Vec<MergeDecision>,
+    generation_gap: f64,
+    statistics: OperatorStatistics,
     _genotype: PhantomData<Gnt>,
     _fitness: PhantomData<F>,
 }
@@ -34,19 +49,68 @@ where
     Gnt: Genotype<A>,
 {
     pub fn with_genome(genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>, p_best: f64) -> Self {
+        genome.assert_matches_genotype();
+
         Self {
             genome,
             p_best,
+            warm_start: false,
+            factorization: None,
+            greedy_steps: 0,
+            merge_decisions: Vec::new(),
+            generation_gap: 1.0,
+            statistics: OperatorStatistics::default(),
             _genotype: PhantomData,
             _fitness: PhantomData,
         }
     }
 
+    // Start the greedy model search of each generation from the factorization learned
+    // in the previous generation, rather than from the univariate factorization
+    pub fn warm_start(mut self, warm_start: bool) -> Self {
+        self.warm_start = warm_start;
+        self
+    }
+
+    // The number of offspring sampled per generation, relative to the population size; must be
+    // 1 under copying selection
+    pub fn generation_gap(mut self, generation_gap: f64) -> Self {
+        assert!(generation_gap > 0.0, "the generation gap must be positive");
+        self.generation_gap = generation_gap;
+        self
+    }
+
+    // The factorization learned in the most recent generation
+    pub fn factorization(&self) -> Option<&Factorization> {
+        self.factorization.as_ref()
+    }
+
+    pub fn statistics(&self) -> &OperatorStatistics {
+        &self.statistics
+    }
+
+    // The merges made by the model search of the most recent generation, in the order they were made
+    pub fn merge_decisions(&self) -> &[MergeDecision] {
+        &self.merge_decisions
+    }
+
+    fn initial_factorization(&self) -> Factorization {
+        match &self.factorization {
+            // Only reuse the previous factorization if it still covers the entire genome
+            Some(factorization)
+                if self.warm_start && factorization.num_loci() == self.genome.len() =>
+            {
+                factorization.clone()
+            }
+            _ => Factorization::univariate(self.genome.len()),
+        }
+    }
+
     fn select_model(
-        &self,
+        &mut self,
         initial_factorization: Factorization,
         population: &[&Individual<Gnt, A, F>],
-    ) -> MultivariateModel<'_, Gnt, A, D, F>
+    ) -> MultivariateModel<'a, Gnt, A, D, F>
     where
         Self: Sized,
     {
@@ -56,7 +120,12 @@ where
             initial_factorization,
         );
 
+        self.greedy_steps = 0;
+        self.merge_decisions.clear();
+
         loop {
+            self.greedy_steps += 1;
+
             let candidates = model.factorization().par_join_all();
 
             let Some(best_model) = candidates
@@ -73,6 +142,8 @@ where
             };
 
             if best_model.combined_complexity() <= model.combined_complexity() {
+                self.merge_decisions
+                    .push(merge_decision(&model, &best_model));
                 model = best_model;
             } else {
                 break;
@@ -96,6 +167,31 @@ where
     }
 }
 
+// Joining puts the joined factor last, so the joined factors are the old factors it contains
+fn merge_decision<Gnt, A, D, F>(
+    old: &MultivariateModel<'_, Gnt, A, D, F>,
+    new: &MultivariateModel<'_, Gnt, A, D, F>,
+) -> MergeDecision
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    let joined = new.factorization().iter().last().unwrap();
+    let mut parts = old
+        .factorization()
+        .iter()
+        .filter(|factor| factor.iter().all(|locus| joined.contains(locus)))
+        .cloned();
+
+    MergeDecision {
+        joined_factors: (parts.next().unwrap(), parts.next().unwrap()),
+        old_complexity: old.combined_complexity(),
+        new_complexity: new.combined_complexity(),
+    }
+}
+
 impl<'a, Gnt, A, D, F> VariationOperator<Gnt, A, F> for Ecga<'a, Gnt, A, D, F>
 where
     A: Allele + Discrete,
@@ -104,34 +200,141 @@ where
     Gnt: Genotype<A>,
 {
     fn create_offspring(
-        &self,
+        &mut self,
         population: &[Individual<Gnt, A, F>],
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
     ) -> Vec<Individual<Gnt, A, F>>
     where
         Self: Sized,
     {
         let selection = self.select_individuals(population, fitness_func);
-        let model = self.select_model(Factorization::univariate(self.genome.len()), &selection);
+        let model = self.select_model(self.initial_factorization(), &selection);
 
-        println!("Factorization: {:?}", model.factorization());
+        self.factorization = Some(model.factorization().clone());
 
-        (0..population.len())
+        let n = gap_count(population.len(), self.generation_gap);
+
+        let offspring: Vec<_> = item_rngs(rng, offspring_count(n, max_new_evaluations))
             .into_par_iter()
-            .map_init(
-                || rand::thread_rng(), // each thread has its own rng
-                |rng, _| {
-                    let mut child = model.sample(rng);
+            .map(|mut rng| {
+                let mut child = model.sample(&mut rng);
 
-                    fitness_func.evaluate(&mut child);
+                fitness_func.evaluate(&mut child);
 
-                    child
-                },
-            )
-            .collect()
+                child
+            })
+            .collect();
+
+        self.statistics.record_samples(population, &offspring);
+        offspring
     }
 
     fn mutates(&self) -> bool {
         false
     }
+
+    fn offspring_ratio(&self) -> f64 {
+        self.generation_gap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{fitness::OptimizationGoal, gene::BoolDomain};
+
+    const N: usize = 6;
+    type Gnt = [bool; N];
+
+    // Each block of three loci is either all zeros or all ones
+    fn linked_population() -> Vec<Individual<Gnt, bool, usize>> {
+        let mut rng = rand::thread_rng();
+
+        (0..200)
+            .map(|_| {
+                let (a, b): (bool, bool) = (rng.gen(), rng.gen());
+                let mut idv = Individual::from_genotype([a, a, a, b, b, b]);
+                idv.set_fitness(0);
+                idv
+            })
+            .collect()
+    }
+
+    #[test]
+    fn warm_start_reaches_same_factorization_in_fewer_steps() {
+        let genome: Genome<Gnt, bool, DiscreteGene<bool, BoolDomain>> = Genome::with_bool_domain();
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let population = linked_population();
+
+        let mut cold = Ecga::with_genome(&genome, 1.0);
+        cold.create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::from_entropy(),
+        );
+        let cold_steps = cold.greedy_steps;
+
+        let mut warm = Ecga::with_genome(&genome, 1.0).warm_start(true);
+        warm.create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::from_entropy(),
+        );
+        assert_eq!(warm.greedy_steps, cold_steps);
+
+        warm.create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::from_entropy(),
+        );
+        assert!(warm.greedy_steps < cold_steps);
+        assert_eq!(warm.factorization(), cold.factorization());
+    }
+
+    #[test]
+    fn merge_decisions_trace_the_model_search() {
+        let genome: Genome<Gnt, bool, DiscreteGene<bool, BoolDomain>> = Genome::with_bool_domain();
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let population = linked_population();
+
+        let mut ecga = Ecga::with_genome(&genome, 1.0);
+        ecga.create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::from_entropy(),
+        );
+
+        let decisions = ecga.merge_decisions();
+        assert_eq!(decisions.len(), ecga.greedy_steps - 1);
+        assert_eq!(
+            decisions.len(),
+            N - ecga.factorization().unwrap().iter().count()
+        );
+
+        for decision in decisions {
+            assert!(decision.new_complexity <= decision.old_complexity);
+        }
+        for pair in decisions.windows(2) {
+            assert_eq!(pair[0].new_complexity, pair[1].old_complexity);
+        }
+
+        // Loci are only ever joined within their block
+        for MergeDecision {
+            joined_factors: (a, b),
+            ..
+        } in decisions
+        {
+            assert_eq!(a[0] / 3, b[0] / 3);
+        }
+    }
 }
@@ -1,30 +1,328 @@
 use std::{
+    cell::Cell,
     cmp::Ordering,
-    fmt::Debug,
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::{self, Debug, Display},
+    hash::Hash,
     marker::PhantomData,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
-use crate::{gene::Allele, genotype::Genotype, individual::Individual};
+use crate::{
+    gene::{Allele, Discrete},
+    genotype::Genotype,
+    individual::Individual,
+    parallel::*,
+    rng::{SeedStream, SeededRng},
+};
 
 #[derive(Debug, Clone)]
 pub enum OptimizationGoal {
     Minimize,
     Maximize,
+    // Compares tuple fitnesses objective by objective, in order of priority, each with its own
+    // goal (e.g. minimize the cost, then maximize the robustness)
+    Lexicographic(Vec<OptimizationGoal>),
 }
 
-pub trait Fitness: Default + Copy + Debug + Send + Sync + PartialOrd {}
+impl OptimizationGoal {
+    // The goal of the first (or only) objective, which applies to `Fitness::as_f64`
+    pub fn primary(&self) -> &OptimizationGoal {
+        match self {
+            OptimizationGoal::Lexicographic(goals) => goals
+                .first()
+                .expect("a lexicographic goal needs at least one objective")
+                .primary(),
+            goal => goal,
+        }
+    }
+
+    // Orders fitnesses from best to worst. A NaN fitness (e.g. from a buggy float evaluation
+    // function) is not comparable, so it is ordered as worse than any other fitness rather than
+    // panicking; NaNs are equal to each other.
+    pub fn cmp<F: Fitness>(&self, a: &F, b: &F) -> Ordering {
+        #[allow(clippy::eq_op)]
+        let is_nan = |fitness: &F| fitness != fitness;
+
+        match (is_nan(a), is_nan(b)) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => match self {
+                OptimizationGoal::Minimize => a.partial_cmp(b).unwrap(),
+                OptimizationGoal::Maximize => b.partial_cmp(a).unwrap(),
+                OptimizationGoal::Lexicographic(goals) => a.cmp_objectives(b, goals),
+            },
+        }
+    }
+
+    // Whether `new` is better than `old` by more than `epsilon`, so that e.g. float noise does
+    // not count as progress. The margin applies to the primary objective of tuple fitnesses.
+    pub fn is_improvement<F: Fitness>(&self, new: &F, old: &F, epsilon: f64) -> bool {
+        self.cmp(new, old).is_lt()
+            && (epsilon == 0.0 || (new.as_f64() - old.as_f64()).abs() > epsilon)
+    }
+}
+
+pub trait Fitness: Default + Copy + Debug + Send + Sync + PartialOrd {
+    // The default margin by which a fitness has to improve to count as progress (e.g. when
+    // detecting stagnation), which absorbs rounding errors of float fitnesses
+    const IMPROVEMENT_EPSILON: f64 = 0.0;
+
+    fn as_f64(&self) -> f64;
+
+    // The mean of several fitnesses (e.g. of perturbed samples), rounded towards zero for
+    // integers. Fitness types that cannot be averaged return `None`.
+    fn mean(_fitnesses: &[Self]) -> Option<Self> {
+        None
+    }
+
+    // Rejects goals that do not fit the fitness type, e.g. a lexicographic goal with the wrong
+    // number of objectives, so that comparisons need not check them
+    fn check_goal(goal: &OptimizationGoal) {
+        if let OptimizationGoal::Lexicographic(goals) = goal {
+            assert!(
+                !goals.is_empty(),
+                "a lexicographic goal needs at least one objective"
+            );
+        }
+    }
+
+    // Compares the objectives of two fitnesses, each under its own goal. A single objective is
+    // compared under the first goal.
+    fn cmp_objectives(&self, other: &Self, goals: &[OptimizationGoal]) -> Ordering {
+        goals
+            .first()
+            .expect("a lexicographic goal needs at least one objective")
+            .cmp(self, other)
+    }
+}
+
+// A fitness type with a smallest and largest representable fitness, from which the worst
+// fitness assigned to failed evaluations is derived (see `FailurePolicy::WorstFitness`)
+pub trait BoundedFitness: Fitness {
+    const MIN: Self;
+    const MAX: Self;
+
+    // The worst possible fitness under the given goal
+    fn worst(goal: &OptimizationGoal) -> Self {
+        match goal.primary() {
+            OptimizationGoal::Maximize => Self::MIN,
+            _ => Self::MAX,
+        }
+    }
+}
 
 macro_rules! impl_fitness {
-    (for $($ty:ty),+) => {
+    ($epsilon:expr, for $($ty:ty),+) => {
         $(
-            impl Fitness for $ty {}
+            impl Fitness for $ty {
+                const IMPROVEMENT_EPSILON: f64 = $epsilon;
+
+                fn as_f64(&self) -> f64 {
+                    *self as f64
+                }
+
+                fn mean(fitnesses: &[Self]) -> Option<Self> {
+                    if fitnesses.is_empty() {
+                        return None;
+                    }
+
+                    let total: f64 = fitnesses.iter().map(|fitness| *fitness as f64).sum();
+                    Some((total / fitnesses.len() as f64) as $ty)
+                }
+            }
+
+            impl BoundedFitness for $ty {
+                const MIN: Self = <$ty>::MIN;
+                const MAX: Self = <$ty>::MAX;
+            }
 
         )*
     };
 }
 
-impl_fitness!(for u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, usize, isize, f32, f64);
+impl_fitness!(0.0, for u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, usize, isize);
+impl_fitness!(1e-6, for f32);
+impl_fitness!(1e-12, for f64);
+
+// Tuples of fitnesses are compared lexicographically. Under a single goal, all objectives share
+// it; `OptimizationGoal::Lexicographic` gives each objective its own goal. The first objective
+// is the primary one, which `as_f64` returns.
+macro_rules! impl_tuple_fitness {
+    ($(($($ty:ident: $idx:tt),+)),+) => {
+        $(
+            impl<$($ty: Fitness),+> Fitness for ($($ty,)+) {
+                fn as_f64(&self) -> f64 {
+                    self.0.as_f64()
+                }
+
+                // Every objective is averaged separately
+                fn mean(fitnesses: &[Self]) -> Option<Self> {
+                    Some(($({
+                        let objective: Vec<$ty> = fitnesses.iter().map(|f| f.$idx).collect();
+                        $ty::mean(&objective)?
+                    },)+))
+                }
+
+                fn check_goal(goal: &OptimizationGoal) {
+                    if let OptimizationGoal::Lexicographic(goals) = goal {
+                        let objectives = [$($idx),+].len();
+                        assert_eq!(
+                            goals.len(),
+                            objectives,
+                            "expected a goal for each of the {} objectives",
+                            objectives
+                        );
+                    }
+                }
+
+                fn cmp_objectives(&self, other: &Self, goals: &[OptimizationGoal]) -> Ordering {
+                    let objectives = [$($idx),+].len();
+                    assert_eq!(
+                        goals.len(),
+                        objectives,
+                        "expected a goal for each of the {} objectives",
+                        objectives
+                    );
+
+                    Ordering::Equal
+                        $(.then_with(|| goals[$idx].cmp(&self.$idx, &other.$idx)))+
+                }
+            }
+
+            impl<$($ty: BoundedFitness),+> BoundedFitness for ($($ty,)+) {
+                const MIN: Self = ($($ty::MIN,)+);
+                const MAX: Self = ($($ty::MAX,)+);
+
+                fn worst(goal: &OptimizationGoal) -> Self {
+                    match goal {
+                        OptimizationGoal::Lexicographic(goals) => {
+                            ($($ty::worst(&goals[$idx]),)+)
+                        }
+                        goal => ($($ty::worst(goal),)+),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_tuple_fitness!((A: 0, B: 1), (A: 0, B: 1, C: 2));
+
+pub type EvaluationFunc<'a, Gnt, F> = dyn Fn(&Gnt) -> F + Send + Sync + 'a;
+
+pub type FallibleEvaluationFunc<'a, Gnt, F> =
+    dyn Fn(&Gnt) -> Result<F, EvalError> + Send + Sync + 'a;
+
+// Perturbs a genotype (e.g. by flipping a few bits) to evaluate how robust it is
+pub type Perturbation<'a, Gnt> = dyn Fn(&Gnt, &mut SeededRng) -> Gnt + Send + Sync + 'a;
+
+// An evaluation that could not be completed, e.g. because a simulator rejected the genotype
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalError {
+    message: String,
+}
+
+impl EvalError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "evaluation failed: {}", self.message)
+    }
+}
+
+impl Error for EvalError {}
+
+// What to do with an individual whose evaluation failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailurePolicy {
+    // Assign the worst possible fitness, so that the individual is selected against
+    WorstFitness,
+    // Leave the individual out of the offspring, so that it does not compete in selection. An
+    // individual of the initial population cannot be left out, so it gets the worst fitness.
+    Skip,
+    // Panic with the evaluation error
+    Abort,
+}
+
+thread_local! {
+    // Set when an evaluation on this thread failed under `FailurePolicy::Skip`, which the
+    // fitness function reads right after calling the evaluation function
+    static SKIPPED: Cell<bool> = const { Cell::new(false) };
+}
+
+// Remembers the fitnesses of evaluated genotypes, so that duplicates need not be evaluated again
+pub trait FitnessCache<Gnt, F>: Send + Sync {
+    fn get(&self, genotype: &Gnt) -> Option<F>;
+    fn insert(&self, genotype: &Gnt, fitness: F);
+}
+
+// Caches fitnesses by the alleles of the genotype, which requires discrete (hashable) alleles.
+// By default, every distinct genotype is kept, so memory grows with the number of distinct
+// genotypes evaluated; a capacity stops caching new genotypes once it is reached.
+#[derive(Debug)]
+pub struct GenotypeCache<A, F> {
+    fitnesses: Mutex<HashMap<Vec<A>, F>>,
+    capacity: Option<usize>,
+}
+
+impl<A, F> Default for GenotypeCache<A, F> {
+    fn default() -> Self {
+        Self {
+            fitnesses: Mutex::new(HashMap::new()),
+            capacity: None,
+        }
+    }
+}
+
+impl<A, F> GenotypeCache<A, F> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            fitnesses: Mutex::new(HashMap::with_capacity(capacity)),
+            capacity: Some(capacity),
+        }
+    }
+}
+
+impl<Gnt, A, F> FitnessCache<Gnt, F> for GenotypeCache<A, F>
+where
+    A: Allele + Discrete,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn get(&self, genotype: &Gnt) -> Option<F> {
+        let key: Vec<A> = genotype.iter().collect();
+        self.fitnesses.lock().unwrap().get(&key).copied()
+    }
+
+    fn insert(&self, genotype: &Gnt, fitness: F) {
+        let mut fitnesses = self.fitnesses.lock().unwrap();
+        if self
+            .capacity
+            .is_some_and(|capacity| fitnesses.len() >= capacity)
+        {
+            return;
+        }
+
+        fitnesses.insert(genotype.iter().collect(), fitness);
+    }
+}
 
 pub struct FitnessFunc<'a, Gnt, A, F>
 where
@@ -33,7 +331,12 @@ where
     Gnt: Genotype<A>,
 {
     counter: Arc<Mutex<usize>>,
-    evaluation_func: &'a (dyn Fn(&Gnt) -> F + Send + Sync),
+    cost: usize,
+    calls: AtomicUsize,
+    cache_hits: AtomicUsize,
+    evaluation_nanos: AtomicU64,
+    evaluation_func: Arc<EvaluationFunc<'a, Gnt, F>>,
+    cache: Option<Box<dyn FitnessCache<Gnt, F> + 'a>>,
     goal: OptimizationGoal,
     _gene: PhantomData<A>,
 }
@@ -48,32 +351,542 @@ where
         evaluation_func: &'a (dyn Fn(&Gnt) -> F + Send + Sync),
         goal: OptimizationGoal,
     ) -> Self {
+        Self::from_shared(Arc::new(evaluation_func), goal)
+    }
+
+    // Evaluates genotypes using auxiliary context (e.g. a dataset) that is shared by all evaluations
+    pub fn with_context<Ctx>(
+        evaluation_func: &'a (dyn Fn(&Gnt, &Ctx) -> F + Send + Sync),
+        context: &'a Ctx,
+        goal: OptimizationGoal,
+    ) -> Self
+    where
+        Ctx: Sync,
+    {
+        Self::from_shared(bind_context(evaluation_func, context), goal)
+    }
+
+    pub fn from_shared(
+        evaluation_func: Arc<EvaluationFunc<'a, Gnt, F>>,
+        goal: OptimizationGoal,
+    ) -> Self {
+        F::check_goal(&goal);
+
         Self {
             counter: Arc::new(Mutex::new(0)),
+            cost: 1,
+            calls: AtomicUsize::new(0),
+            cache_hits: AtomicUsize::new(0),
+            evaluation_nanos: AtomicU64::new(0),
             evaluation_func,
+            cache: None,
             goal,
             _gene: PhantomData,
         }
     }
 
+    // Looks up the fitness of previously evaluated genotypes in the cache. Cache hits do not count
+    // as evaluations (and so do not use up the evaluation budget), but they do count as lookups.
+    pub fn with_cache(mut self, cache: Box<dyn FitnessCache<Gnt, F> + 'a>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    // The number of evaluations each call to the evaluation function counts as, e.g. when it
+    // averages the fitness of several samples
+    pub fn with_evaluation_cost(mut self, cost: usize) -> Self {
+        assert!(cost > 0, "the evaluation cost must be positive");
+        self.cost = cost;
+        self
+    }
+
+    pub fn evaluation_cost(&self) -> usize {
+        self.cost
+    }
+
+    // Evaluates the individual, and marks it if its evaluation failed under
+    // `FailurePolicy::Skip`
     pub fn evaluate(&self, individual: &mut Individual<Gnt, A, F>) -> F {
-        let fitness = (self.evaluation_func)(individual.genotype());
+        let (fitness, skipped) = self.evaluate_skippable(individual.genotype());
         individual.set_fitness(fitness);
+        if skipped {
+            individual.mark_skipped();
+        }
+        fitness
+    }
 
+    // Evaluates a bare genotype, which counts as an evaluation (or cache hit) like `evaluate`
+    pub fn evaluate_genotype(&self, genotype: &Gnt) -> F {
+        self.evaluate_skippable(genotype).0
+    }
+
+    fn evaluate_skippable(&self, genotype: &Gnt) -> (F, bool) {
+        if let Some(fitness) = self.cache.as_ref().and_then(|cache| cache.get(genotype)) {
+            self.cache_hits.fetch_add(1, AtomicOrdering::Relaxed);
+            return (fitness, false);
+        }
+
+        SKIPPED.set(false);
+        let start = Instant::now();
+        let fitness = (self.evaluation_func)(genotype);
+        self.evaluation_nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, AtomicOrdering::Relaxed);
+        let skipped = SKIPPED.replace(false);
+
+        // A skipped genotype is evaluated (and skipped) again when it reappears
+        if let Some(cache) = self.cache.as_ref().filter(|_| !skipped) {
+            cache.insert(genotype, fitness);
+        }
+
+        self.calls.fetch_add(1, AtomicOrdering::Relaxed);
         let mut counter = self.counter.lock().unwrap();
-        *counter += 1;
+        *counter += self.cost;
 
-        fitness
+        (fitness, skipped)
+    }
+
+    // Evaluates genotypes in parallel and returns their fitnesses in the same order, e.g. to
+    // generate training data for a surrogate model. Every genotype counts as an evaluation.
+    pub fn par_evaluate(&self, genotypes: &[Gnt]) -> Vec<F> {
+        genotypes
+            .par_iter()
+            .map(|genotype| self.evaluate_genotype(genotype))
+            .collect()
     }
 
+    // Evaluates, in parallel, every individual that has no fitness yet. Returns the number of new
+    // evaluations.
+    pub fn evaluate_all(&self, individuals: &mut [Individual<Gnt, A, F>]) -> usize {
+        individuals
+            .into_par_iter()
+            .filter(|idv| !idv.is_evaluated())
+            .map(|idv| self.evaluate(idv))
+            .count()
+    }
+
+    // The number of evaluations charged to the evaluation budget: every call to the evaluation
+    // function counts as the evaluation cost
     pub fn evaluations(&self) -> usize {
         *self.counter.lock().unwrap()
     }
 
+    // The number of calls to the evaluation function, regardless of their cost. Cache hits are not
+    // calls.
+    pub fn real_evaluations(&self) -> usize {
+        self.calls.load(AtomicOrdering::Relaxed)
+    }
+
+    // The number of fitnesses requested, whether evaluated or found in the cache
+    pub fn total_lookups(&self) -> usize {
+        self.real_evaluations() + self.cache_hits()
+    }
+
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits.load(AtomicOrdering::Relaxed)
+    }
+
+    // Total time spent in the evaluation function, summed over all (possibly parallel) evaluations
+    pub fn evaluation_time(&self) -> Duration {
+        Duration::from_nanos(self.evaluation_nanos.load(AtomicOrdering::Relaxed))
+    }
+
+    // A fitness function that transforms the fitness of this one, e.g. to log-scale it or to
+    // negate it (together with `with_goal`) to turn a minimization into a maximization problem.
+    // Both share the evaluation function, but each counts its own evaluations and has no cache.
+    pub fn map<G, M>(&self, f: M) -> FitnessFunc<'a, Gnt, A, G>
+    where
+        G: Fitness,
+        M: Fn(F) -> G + Send + Sync + 'a,
+        Gnt: 'a,
+        F: 'a,
+    {
+        let evaluation_func = Arc::clone(&self.evaluation_func);
+
+        FitnessFunc::from_shared(
+            Arc::new(move |genotype: &Gnt| f(evaluation_func(genotype))),
+            self.goal.clone(),
+        )
+    }
+
+    pub fn with_goal(mut self, goal: OptimizationGoal) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    pub fn goal(&self) -> &OptimizationGoal {
+        &self.goal
+    }
+
     pub fn cmp(&self, a: &F, b: &F) -> Ordering {
-        match self.goal {
-            OptimizationGoal::Minimize => a.partial_cmp(b).unwrap(),
-            OptimizationGoal::Maximize => b.partial_cmp(a).unwrap(),
+        self.goal.cmp(a, b)
+    }
+
+    pub fn is_improvement(&self, new: &F, old: &F, epsilon: f64) -> bool {
+        self.goal.is_improvement(new, old, epsilon)
+    }
+}
+
+// Wraps an evaluation function to measure how many distinct genotypes it was called with, as
+// reported in research next to the total number of evaluations. Unlike a cache, it does not change
+// which evaluations are performed.
+pub struct UniqueEvaluations<'a, Gnt, F> {
+    evaluation_func: &'a EvaluationFunc<'a, Gnt, F>,
+    evaluations: AtomicUsize,
+    evaluated: Mutex<HashSet<Gnt>>,
+}
+
+impl<'a, Gnt, F> UniqueEvaluations<'a, Gnt, F>
+where
+    F: Fitness,
+    Gnt: Hash + Eq + Clone,
+{
+    pub fn new(evaluation_func: &'a EvaluationFunc<'a, Gnt, F>) -> Self {
+        Self {
+            evaluation_func,
+            evaluations: AtomicUsize::new(0),
+            evaluated: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn evaluate(&self, genotype: &Gnt) -> F {
+        self.evaluations.fetch_add(1, AtomicOrdering::Relaxed);
+
+        let mut evaluated = self.evaluated.lock().unwrap();
+        if !evaluated.contains(genotype) {
+            evaluated.insert(genotype.clone());
+        }
+        drop(evaluated);
+
+        (self.evaluation_func)(genotype)
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations.load(AtomicOrdering::Relaxed)
+    }
+
+    pub fn unique_evaluations(&self) -> usize {
+        self.evaluated.lock().unwrap().len()
+    }
+}
+
+pub fn bind_context<'a, Gnt, Ctx, F>(
+    evaluation_func: &'a (dyn Fn(&Gnt, &Ctx) -> F + Send + Sync),
+    context: &'a Ctx,
+) -> Arc<EvaluationFunc<'a, Gnt, F>>
+where
+    Ctx: Sync,
+    F: Fitness,
+{
+    Arc::new(move |genotype: &Gnt| evaluation_func(genotype, context))
+}
+
+// Turns a fallible evaluation function into an infallible one by applying the failure policy,
+// where failed evaluations get the `worst` fitness (e.g. `BoundedFitness::worst` of the goal)
+pub fn bind_failure_policy<'a, Gnt, F>(
+    evaluation_func: &'a FallibleEvaluationFunc<'a, Gnt, F>,
+    policy: FailurePolicy,
+    worst: F,
+) -> Arc<EvaluationFunc<'a, Gnt, F>>
+where
+    F: Fitness,
+{
+    Arc::new(move |genotype: &Gnt| match evaluation_func(genotype) {
+        Ok(fitness) => fitness,
+        Err(error) => match policy {
+            FailurePolicy::WorstFitness => worst,
+            FailurePolicy::Skip => {
+                SKIPPED.set(true);
+                worst
+            }
+            FailurePolicy::Abort => panic!("{}", error),
+        },
+    })
+}
+
+// Evaluates the average fitness of `samples` perturbed copies of a genotype, which favours optima
+// that are robust to perturbation. Sample `i` of every genotype is perturbed with the same random
+// numbers (derived from the seed and `i`), so that evaluations are reproducible and genotypes are
+// compared under equal conditions.
+pub fn bind_perturbation<'a, Gnt, F>(
+    evaluation_func: Arc<EvaluationFunc<'a, Gnt, F>>,
+    perturbation: &'a Perturbation<'a, Gnt>,
+    samples: usize,
+    seed: u64,
+) -> Arc<EvaluationFunc<'a, Gnt, F>>
+where
+    Gnt: 'a,
+    F: Fitness + 'a,
+{
+    assert!(samples > 0, "at least one perturbed sample is required");
+    let stream = SeedStream::new(seed);
+
+    Arc::new(move |genotype: &Gnt| {
+        let fitnesses: Vec<F> = (0..samples as u64)
+            .map(|sample| {
+                let perturbed = perturbation(genotype, &mut stream.derive(sample).rng());
+                evaluation_func(&perturbed)
+            })
+            .collect();
+
+        F::mean(&fitnesses).expect("robust evaluation requires a fitness type that can be averaged")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        gene::BoolDomain,
+        genome::Genome,
+        selection::{SelectionOperator, TournamentSelection},
+    };
+
+    #[test]
+    fn evaluation_with_lookup_table_context() {
+        type Gnt = [usize; 3];
+
+        let lookup_table = vec![1.5, -2.0, 4.0, 0.25];
+        let evaluation_func =
+            |genotype: &Gnt, table: &Vec<f64>| genotype.iter().map(|idx| table[idx]).sum::<f64>();
+        let fitness_func =
+            FitnessFunc::with_context(&evaluation_func, &lookup_table, OptimizationGoal::Maximize);
+
+        let mut idv = Individual::from_genotype([0, 2, 3]);
+        assert_eq!(fitness_func.evaluate(&mut idv), 5.75);
+        assert_eq!(idv.fitness(), 5.75);
+        assert_eq!(fitness_func.evaluations(), 1);
+    }
+
+    #[test]
+    fn evaluation_time_includes_sleeping_evaluations() {
+        let evaluation_func = |_: &[bool; 1]| {
+            std::thread::sleep(Duration::from_millis(5));
+            0
+        };
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+
+        for _ in 0..4 {
+            fitness_func.evaluate(&mut Individual::from_genotype([true]));
+        }
+
+        assert!(fitness_func.evaluation_time() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn par_evaluate_matches_individual_evaluations() {
+        let evaluation_func = |genotype: &[bool; 32]| genotype.iter().filter(|bit| *bit).count();
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let genotypes = genome.random_genotype_batch(&mut SeededRng::seed_from_u64(0), 1000);
+
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let fitnesses = fitness_func.par_evaluate(&genotypes);
+        assert_eq!(fitness_func.evaluations(), genotypes.len());
+
+        let expected: Vec<_> = genotypes
+            .iter()
+            .map(|genotype| fitness_func.evaluate(&mut Individual::from_genotype(*genotype)))
+            .collect();
+        assert_eq!(fitnesses, expected);
+    }
+
+    #[test]
+    fn lexicographic_goal_per_objective() {
+        use OptimizationGoal::*;
+
+        // Minimize the first objective, then maximize the second
+        let goal = Lexicographic(vec![Minimize, Maximize]);
+        let mut fitnesses = vec![(1, 5.0), (0, -1.0), (1, 7.0), (2, 9.0), (0, 3.0)];
+        fitnesses.sort_by(|a, b| goal.cmp(a, b));
+        assert_eq!(
+            fitnesses,
+            vec![(0, 3.0), (0, -1.0), (1, 7.0), (1, 5.0), (2, 9.0)]
+        );
+
+        assert_eq!(<(i32, f64)>::worst(&goal), (i32::MAX, f64::MIN));
+        assert!(goal.cmp(&(i32::MAX, f64::MIN), &(i32::MAX, 0.0)).is_gt());
+        assert!(matches!(goal.primary(), Minimize));
+
+        // A single goal applies to all objectives
+        assert!(Maximize.cmp(&(1, 2.0), &(1, 3.0)).is_gt());
+
+        let evaluation_func = |genotype: &[i32; 2]| (genotype[0], genotype[1] as f64);
+        let fitness_func = FitnessFunc::new(&evaluation_func, goal);
+        let best = [[1, 5], [0, -1], [0, 3]]
+            .into_iter()
+            .map(|genotype| fitness_func.evaluate(&mut Individual::from_genotype(genotype)))
+            .min_by(|a, b| fitness_func.cmp(a, b));
+        assert_eq!(best, Some((0, 3.0)));
+
+        assert_eq!(<(i32, f64)>::mean(&[(1, 5.0), (2, 7.0)]), Some((1, 6.0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a goal for each of the 3 objectives")]
+    fn lexicographic_goal_needs_every_objective() {
+        use OptimizationGoal::*;
+
+        let evaluation_func = |genotype: &[i32; 1]| (genotype[0], 0, 0.0);
+        FitnessFunc::new(&evaluation_func, Lexicographic(vec![Minimize, Maximize]));
+    }
+
+    fn fails_on_negative(genotype: &[i32; 1]) -> Result<i32, EvalError> {
+        if genotype[0] < 0 {
+            Err(EvalError::new("negative input"))
+        } else {
+            Ok(genotype[0])
+        }
+    }
+
+    #[test]
+    fn failed_evaluation_gets_worst_fitness() {
+        let evaluation_func = bind_failure_policy(
+            &fails_on_negative,
+            FailurePolicy::WorstFitness,
+            i32::worst(&OptimizationGoal::Maximize),
+        );
+        let fitness_func = FitnessFunc::from_shared(evaluation_func, OptimizationGoal::Maximize);
+
+        assert_eq!(
+            fitness_func.evaluate(&mut Individual::from_genotype([3])),
+            3
+        );
+        assert_eq!(
+            fitness_func.evaluate(&mut Individual::from_genotype([-3])),
+            i32::MIN
+        );
+        assert_eq!(fitness_func.evaluations(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "evaluation failed: negative input")]
+    fn failed_evaluation_aborts() {
+        let evaluation_func = bind_failure_policy(
+            &fails_on_negative,
+            FailurePolicy::Abort,
+            i32::worst(&OptimizationGoal::Minimize),
+        );
+
+        evaluation_func(&[-1]);
+    }
+
+    #[test]
+    fn skipped_evaluation_is_marked_and_not_cached() {
+        let evaluation_func = bind_failure_policy(
+            &fails_on_negative,
+            FailurePolicy::Skip,
+            i32::worst(&OptimizationGoal::Maximize),
+        );
+        let fitness_func = FitnessFunc::from_shared(evaluation_func, OptimizationGoal::Maximize)
+            .with_cache(Box::new(GenotypeCache::default()));
+
+        let mut failed = Individual::from_genotype([-3]);
+        assert_eq!(fitness_func.evaluate(&mut failed), i32::MIN);
+        assert!(failed.is_skipped());
+
+        let mut evaluated = Individual::from_genotype([3]);
+        fitness_func.evaluate(&mut evaluated);
+        assert!(!evaluated.is_skipped());
+
+        let mut again = Individual::from_genotype([-3]);
+        fitness_func.evaluate(&mut again);
+        assert!(again.is_skipped());
+        assert_eq!(fitness_func.cache_hits(), 0);
+    }
+
+    #[test]
+    fn counts_unique_evaluations() {
+        let evaluation_func = |genotype: &[u8; 1]| genotype[0] as usize;
+        let unique = UniqueEvaluations::new(&evaluation_func);
+        let counted = |genotype: &[u8; 1]| unique.evaluate(genotype);
+        let fitness_func = FitnessFunc::new(&counted, OptimizationGoal::Maximize);
+
+        let mut rng = SeededRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let mut idv = Individual::from_genotype([rng.gen_range(0..10)]);
+            assert_eq!(fitness_func.evaluate(&mut idv), idv.genotype()[0] as usize);
         }
+
+        assert_eq!(fitness_func.evaluations(), 100);
+        assert_eq!(unique.evaluations(), 100);
+        assert_eq!(unique.unique_evaluations(), 10);
+    }
+
+    #[test]
+    fn negated_fitness_with_flipped_goal_selects_the_same() {
+        type Gnt = [bool; 16];
+
+        let one_max = |genotype: &Gnt| genotype.iter().filter(|bit| *bit).count();
+        let fitness_func = FitnessFunc::new(&one_max, OptimizationGoal::Maximize);
+        let negated = fitness_func
+            .map(|fitness| -(fitness as i64))
+            .with_goal(OptimizationGoal::Minimize);
+
+        let genome = Genome::with_bool_domain();
+        let mut rng = SeededRng::seed_from_u64(0);
+        let genotypes: Vec<Gnt> = (0..40).map(|_| genome.sample_uniform(&mut rng)).collect();
+
+        let mut pool: Vec<_> = genotypes
+            .iter()
+            .copied()
+            .map(Individual::from_genotype)
+            .collect();
+        let mut negated_pool: Vec<_> = genotypes
+            .into_iter()
+            .map(Individual::from_genotype)
+            .collect();
+        fitness_func.evaluate_all(&mut pool);
+        negated.evaluate_all(&mut negated_pool);
+
+        for (idv, negated_idv) in pool.iter().zip(negated_pool.iter()) {
+            assert_eq!(negated_idv.fitness(), -(idv.fitness() as i64));
+        }
+
+        let selected = TournamentSelection::new(4, true).select_indices(
+            &pool,
+            20,
+            &fitness_func,
+            &mut SeededRng::seed_from_u64(1),
+        );
+        let negated_selected = TournamentSelection::new(4, true).select_indices(
+            &negated_pool,
+            20,
+            &negated,
+            &mut SeededRng::seed_from_u64(1),
+        );
+
+        assert_eq!(selected, negated_selected);
+        assert_eq!(negated.evaluations(), 40);
+    }
+
+    #[test]
+    fn bounded_cache_stops_caching_when_full() {
+        let evaluation_func = |genotype: &[i32; 1]| genotype[0];
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize)
+            .with_cache(Box::new(GenotypeCache::with_capacity(1)));
+
+        for genotype in [[1], [2], [1], [2]] {
+            fitness_func.evaluate(&mut Individual::from_genotype(genotype));
+        }
+
+        assert_eq!(fitness_func.cache_hits(), 1);
+        assert_eq!(fitness_func.evaluations(), 3);
+    }
+
+    #[test]
+    fn lookups_count_calls_regardless_of_cost() {
+        let evaluation_func = |genotype: &[i32; 1]| genotype[0];
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize)
+            .with_cache(Box::new(GenotypeCache::default()))
+            .with_evaluation_cost(5);
+
+        for genotype in [[1], [2], [1]] {
+            fitness_func.evaluate(&mut Individual::from_genotype(genotype));
+        }
+
+        assert_eq!(fitness_func.evaluations(), 10);
+        assert_eq!(fitness_func.real_evaluations(), 2);
+        assert_eq!(fitness_func.total_lookups(), 3);
     }
 }
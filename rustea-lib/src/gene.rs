@@ -8,7 +8,7 @@ use std::{
 use core::{fmt::Debug, panic};
 
 use approx::AbsDiffEq;
-use num_traits::{One, Zero};
+use num_traits::{Float, One, Zero};
 use rand::{distributions::uniform::SampleUniform, Rng};
 use rand_distr::{Distribution, WeightedIndex};
 
@@ -17,9 +17,17 @@ pub trait Gene<A>: Send + Sync + Clone {
     fn sample_uniform<R>(&self, rng: &mut R) -> A
     where
         R: Rng + ?Sized;
+
+    // The number of alleles this gene can take, if it is finite
+    fn cardinality(&self) -> Option<u128> {
+        None
+    }
+
+    // Whether the gene can take the given allele
+    fn contains(&self, allele: A) -> bool;
 }
 
-pub trait Allele: Sized + Send + Sync + Copy + Debug + Default {}
+pub trait Allele: Sized + Send + Sync + Copy + Debug + Default + PartialEq {}
 
 // Marker for discrete-valued genes and alleles
 pub trait Discrete: Eq + PartialEq + Hash {}
@@ -27,7 +35,7 @@ pub trait Discrete: Eq + PartialEq + Hash {}
 pub trait Integer: Discrete + PartialOrd + Ord + SampleUniform {}
 
 // Marker for real-valued genes and alleles
-pub trait Real: PartialOrd + AbsDiffEq + SampleUniform {}
+pub trait Real: PartialOrd + AbsDiffEq + SampleUniform + Float {}
 
 #[derive(Debug, Clone)]
 pub struct DiscreteGene<A, D>
@@ -50,6 +58,14 @@ where
     {
         self.domain.sample_uniform(rng)
     }
+
+    fn cardinality(&self) -> Option<u128> {
+        Some(self.domain.len() as u128)
+    }
+
+    fn contains(&self, allele: A) -> bool {
+        self.domain.contains(allele)
+    }
 }
 
 impl<A, D> DiscreteGene<A, D>
@@ -79,8 +95,49 @@ where
     }
 }
 
-#[derive(Clone)]
-pub struct RealGene {}
+#[derive(Debug, Clone)]
+pub struct RealGene<A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+{
+    domain: D,
+    _allele: PhantomData<A>,
+}
+
+impl<A, D> Gene<A> for RealGene<A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+{
+    fn sample_uniform<R>(&self, rng: &mut R) -> A
+    where
+        R: Rng + ?Sized,
+    {
+        self.domain.sample_uniform(rng)
+    }
+
+    fn contains(&self, allele: A) -> bool {
+        self.domain.contains(allele)
+    }
+}
+
+impl<A, D> RealGene<A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+{
+    pub fn with_domain(domain: &D) -> Self {
+        Self {
+            domain: domain.clone(),
+            _allele: PhantomData,
+        }
+    }
+
+    pub fn domain(&self) -> &D {
+        &self.domain
+    }
+}
 
 pub struct DiscreteDomainIter<'a, A, D>
 where
@@ -117,6 +174,12 @@ where
     fn get(&self, idx: usize) -> A;
     fn index_of(&self, allele: A) -> usize;
     fn len(&self) -> usize;
+    fn contains(&self, allele: A) -> bool
+    where
+        Self: Sized,
+    {
+        self.iter().any(|other| other == allele)
+    }
     fn iter(&self) -> DiscreteDomainIter<A, Self>
     where
         Self: Sized,
@@ -155,10 +218,7 @@ where
     where
         R: Rng + ?Sized,
     {
-        let r: f64 = rng.gen();
-        let n: usize = (r * self.len() as f64) as usize;
-
-        self.iter().nth(n).unwrap()
+        self.get(rng.gen_range(0..self.len()))
     }
 }
 
@@ -292,10 +352,14 @@ impl<A> FromIterator<A> for DisjointIntegralDomain<A>
 where
     A: Allele + Discrete + Ord + PartialOrd + Add + Zero + One,
 {
+    // The alleles are kept sorted and distinct, so that the index of an allele does not depend on
+    // the order in which they are given (e.g. when collected from a hash set)
     fn from_iter<T: IntoIterator<Item = A>>(iter: T) -> Self {
-        Self {
-            alleles: iter.into_iter().collect(),
-        }
+        let mut alleles: Vec<_> = iter.into_iter().collect();
+        alleles.sort_unstable();
+        alleles.dedup();
+
+        Self { alleles }
     }
 }
 
@@ -311,6 +375,10 @@ where
         self.alleles.binary_search(&allele).unwrap()
     }
 
+    fn contains(&self, allele: A) -> bool {
+        self.alleles.binary_search(&allele).is_ok()
+    }
+
     fn len(&self) -> usize {
         self.alleles.len()
     }
@@ -404,6 +472,8 @@ macro_rules! idom {
 //     };
 // }
 
+// The domain of bool alleles, where false has index 0 and true has index 1, so that models index
+// their frequencies by the value of the allele
 #[derive(Default, Clone)]
 pub struct BoolDomain;
 
@@ -426,6 +496,10 @@ impl DiscreteDomain<bool> for BoolDomain {
         2
     }
 
+    fn contains(&self, _: bool) -> bool {
+        true
+    }
+
     fn add(self, _: bool) -> Self {
         self
     }
@@ -452,9 +526,15 @@ where
     fn sample_uniform<R>(&self, rng: &mut R) -> A
     where
         R: Rng + ?Sized;
+
+    // Moves the value to the nearest value inside the domain
+    fn clamp(&self, value: A) -> A;
+
+    // Whether the value lies inside the domain; a clamped value always does
+    fn contains(&self, value: A) -> bool;
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct ExclusiveRangeRealDomain<A>
 where
     A: Allele + Real,
@@ -485,9 +565,25 @@ where
     {
         rng.gen_range(self.range.clone())
     }
+
+    fn clamp(&self, value: A) -> A {
+        let (low, high) = (self.range.start, self.range.end);
+
+        if value < high {
+            return value.max(low);
+        }
+
+        // The upper bound is excluded, so step just below it (but never below the lower bound)
+        let below = high - high.abs().max(high - low) * A::epsilon();
+        below.max(low)
+    }
+
+    fn contains(&self, value: A) -> bool {
+        self.range.contains(&value)
+    }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct InclusiveRangeRealDomain<A>
 where
     A: Allele + Real,
@@ -518,6 +614,14 @@ where
     {
         rng.gen_range(self.range.clone())
     }
+
+    fn clamp(&self, value: A) -> A {
+        value.max(*self.range.start()).min(*self.range.end())
+    }
+
+    fn contains(&self, value: A) -> bool {
+        self.range.contains(&value)
+    }
 }
 
 #[macro_export]
@@ -555,7 +659,10 @@ impl_real_allele!(for f32, f64);
 
 #[cfg(test)]
 mod tests {
+    use rand::SeedableRng;
+
     use super::*;
+    use crate::rng::SeededRng;
 
     #[test]
     fn test_integral_domain_union() {
@@ -609,6 +716,36 @@ mod tests {
         assert_eq!(domain.iter().collect::<Vec<_>>(), vec![false, true])
     }
 
+    #[test]
+    fn test_bdom_add_existing_value_is_noop() {
+        let domain = bdom!().add(true).add(false);
+
+        assert_eq!(domain.iter().collect::<Vec<_>>(), vec![false, true])
+    }
+
+    #[test]
+    fn test_bdom_index_mapping_is_consistent() {
+        for domain in [bdom!(), BoolDomain, bdom!().add(true).add(false)] {
+            assert!(!domain.get(0));
+            assert!(domain.get(1));
+
+            for allele in [false, true] {
+                assert_eq!(domain.get(domain.index_of(allele)), allele);
+            }
+        }
+    }
+
+    #[test]
+    fn test_idom_from_unordered_alleles_is_sorted() {
+        let alleles: HashSet<_> = [5, 1, 3, 9, 7].into_iter().collect();
+        let domain: DisjointIntegralDomain<_> = alleles.into_iter().chain([3, 1]).collect();
+
+        assert_eq!(domain.iter().collect::<Vec<_>>(), vec![1, 3, 5, 7, 9]);
+        for (idx, allele) in domain.iter().enumerate() {
+            assert_eq!(domain.index_of(allele), idx);
+        }
+    }
+
     #[test]
     fn test_rdom_exclusive() {
         let range = -1.0..1.0;
@@ -624,4 +761,52 @@ mod tests {
 
         assert_eq!(*domain.range(), range)
     }
+
+    #[test]
+    fn test_rdom_inclusive_clamps_to_nearest_bound() {
+        let domain = rdom!(-1.0..=1.0);
+
+        assert_eq!(domain.clamp(-3.0), -1.0);
+        assert_eq!(domain.clamp(0.25), 0.25);
+        assert_eq!(domain.clamp(1.0), 1.0);
+        assert_eq!(domain.clamp(7.5), 1.0);
+        assert!(domain.contains(domain.clamp(7.5)));
+    }
+
+    #[test]
+    fn test_rdom_exclusive_clamps_below_upper_bound() {
+        for (low, high) in [(-1.0, 1.0), (0.0, 1e-300), (1e6, 1e6 + 1.0), (-2.0, -1.0)] {
+            let domain = ExclusiveRangeRealDomain::with_range(low..high);
+
+            assert_eq!(domain.clamp(low - 1.0), low);
+            assert_eq!(domain.clamp(low), low);
+
+            for value in [high, high + 1.0, f64::MAX] {
+                let clamped = domain.clamp(value);
+                assert!(clamped < high, "{} was clamped to {}", value, clamped);
+                assert!(domain.contains(clamped));
+            }
+        }
+
+        assert!(!rdom!(-1.0..1.0).contains(1.0));
+        assert!(rdom!(-1.0..1.0).contains(-1.0));
+    }
+
+    #[test]
+    fn test_sample_uniform_stays_in_domain_and_is_uniform() {
+        const SAMPLES: usize = 60_000;
+
+        let domain = idom!(1..3, 10, 20..=22);
+        let mut rng = SeededRng::seed_from_u64(42);
+        let mut counts = vec![0usize; domain.len()];
+
+        for _ in 0..SAMPLES {
+            counts[domain.index_of(domain.sample_uniform(&mut rng))] += 1;
+        }
+
+        let expected = SAMPLES / domain.len();
+        for count in counts {
+            assert!(count.abs_diff(expected) < expected / 20, "{}", count);
+        }
+    }
 }
@@ -1,16 +1,34 @@
 use crate::{
-    gene::{Allele, BoolDomain, Discrete, DiscreteDomain, DiscreteGene, Gene},
+    gene::{
+        Allele, BoolDomain, Discrete, DiscreteDomain, DiscreteGene, Gene, Real, RealDomain,
+        RealGene,
+    },
     genotype::Genotype,
+    parallel::*,
+    rng::{RngGenerator, XoshiroRngGenerator},
     types::CollectUnsafe,
 };
 
-use std::{fmt::Debug, marker::PhantomData, slice::Iter};
+use std::{collections::HashSet, fmt::Debug, marker::PhantomData, ops::Range, slice::Iter};
 
 use rand::Rng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+const BATCH_CHUNK_SIZE: usize = 256;
 
 pub trait Permutation: Send + Sync {}
 pub trait Cartesian<Gene>: Send + Sync {
     fn set(&mut self, index: usize, gene: Gene);
+
+    // Copies the gene at `index` from `donor`, along with anything else the genotype keeps per
+    // locus (e.g. the step sizes of a self-adaptive genotype)
+    fn inherit(&mut self, index: usize, donor: &Self)
+    where
+        Self: Genotype<Gene>,
+        Gene: Allele,
+    {
+        self.set(index, donor.get(index));
+    }
 }
 
 // A genome represents the domain of all possible genotypes
@@ -43,6 +61,50 @@ where
             .collect_unsafe()
     }
 
+    // Samples `count` genotypes in parallel, where each chunk of the batch uses its own rng
+    pub fn random_genotype_batch<R>(&self, rng: &mut R, count: usize) -> Vec<Gnt>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut generator = XoshiroRngGenerator::<Xoshiro256PlusPlus>::from_seed(rng.gen());
+        let rngs: Vec<_> = (0..count.div_ceil(BATCH_CHUNK_SIZE))
+            .map(|_| generator.next())
+            .collect();
+
+        rngs.into_par_iter()
+            .enumerate()
+            .flat_map_iter(|(chunk, mut rng)| {
+                let n = BATCH_CHUNK_SIZE.min(count - chunk * BATCH_CHUNK_SIZE);
+                (0..n).map(move |_| self.sample_uniform(&mut rng))
+            })
+            .collect()
+    }
+
+    // Samples `n` distinct genotypes. If the genome has fewer than `n` genotypes, every genotype is
+    // sampled once and the remainder is sampled uniformly, allowing duplicates.
+    pub fn sample_n_distinct<R>(&self, rng: &mut R, n: usize) -> Vec<Gnt>
+    where
+        R: Rng + ?Sized,
+        A: Discrete,
+    {
+        let distinct = match self.cardinality() {
+            Some(cardinality) if cardinality < n as u128 => cardinality as usize,
+            _ => n,
+        };
+
+        let mut seen = HashSet::with_capacity(distinct);
+        let mut genotypes = Vec::with_capacity(n);
+        while genotypes.len() < distinct {
+            let genotype = self.sample_uniform(rng);
+            if seen.insert(genotype.iter().collect::<Vec<_>>()) {
+                genotypes.push(genotype);
+            }
+        }
+
+        genotypes.extend((distinct..n).map(|_| self.sample_uniform(rng)));
+        genotypes
+    }
+
     pub fn get(&self, index: usize) -> &G {
         &self.genes[index]
     }
@@ -51,9 +113,208 @@ where
         self.genes.len()
     }
 
+    // The size of the search space, or None if it is infinite or does not fit in a u128
+    pub fn cardinality(&self) -> Option<u128> {
+        self.genes
+            .iter()
+            .try_fold(1u128, |acc, gene| acc.checked_mul(gene.cardinality()?))
+    }
+
     pub fn iter(&self) -> Iter<'_, G> {
         self.genes.iter()
     }
+
+    // A read-only view of a contiguous range of loci, e.g. one block of a block-structured
+    // problem such as the concatenated trap
+    pub fn slice(&self, range: Range<usize>) -> GenomeView<'_, Gnt, A, G> {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "the range {:?} does not lie within the {} loci of the genome",
+            range,
+            self.len()
+        );
+
+        GenomeView {
+            genes: &self.genes[range.clone()],
+            range,
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
+
+    // Whether every allele of the genotype lies in the domain of its gene, stopping at the first
+    // allele that does not
+    pub fn contains_genotype(&self, genotype: &Gnt) -> bool {
+        self.iter()
+            .enumerate()
+            .all(|(idx, gene)| gene.contains(genotype.get(idx)))
+    }
+
+    // Operators index genotypes through the genome, so a genome that does not match the genotype
+    // length would only surface as an out-of-bounds panic during sampling
+    pub(crate) fn assert_matches_genotype(&self) {
+        assert_eq!(
+            self.len(),
+            Gnt::LEN,
+            "the genome has {} genes, but the genotype has length {}",
+            self.len(),
+            Gnt::LEN
+        );
+    }
+}
+
+// A contiguous range of loci of a genome, created with `Genome::slice`. Loci are indexed relative
+// to the start of the range.
+#[derive(Debug, Clone)]
+pub struct GenomeView<'a, Gnt, A, G>
+where
+    A: Allele,
+    G: Gene<A>,
+    Gnt: Genotype<A>,
+{
+    genes: &'a [G],
+    range: Range<usize>,
+    _allele: PhantomData<A>,
+    _genotype: PhantomData<Gnt>,
+}
+
+impl<'a, Gnt, A, G> GenomeView<'a, Gnt, A, G>
+where
+    A: Allele,
+    G: Gene<A>,
+    Gnt: Genotype<A>,
+{
+    // The loci of the genome that the view covers
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    pub fn get(&self, index: usize) -> &G {
+        &self.genes[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'a, G> {
+        self.genes.iter()
+    }
+
+    pub fn cardinality(&self) -> Option<u128> {
+        self.genes
+            .iter()
+            .try_fold(1u128, |acc, gene| acc.checked_mul(gene.cardinality()?))
+    }
+
+    // The alleles of a genotype of the full genome at the loci of the view
+    pub fn alleles<'g>(&self, genotype: &'g Gnt) -> impl Iterator<Item = A> + 'g {
+        self.range().map(|idx| genotype.get(idx))
+    }
+
+    pub fn contains_genotype(&self, genotype: &Gnt) -> bool {
+        self.iter()
+            .zip(self.alleles(genotype))
+            .all(|(gene, allele)| gene.contains(allele))
+    }
+
+    // A standalone genome of the loci of the view, for genotypes of the length of the view, so
+    // that models and operators can be applied to the region (see `project`)
+    pub fn to_genome<Sub>(&self) -> Genome<Sub, A, G>
+    where
+        Sub: Genotype<A>,
+    {
+        let genome = Genome {
+            genes: self.genes.to_vec(),
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        };
+        genome.assert_matches_genotype();
+        genome
+    }
+
+    // Restricts a genotype of the full genome to the loci of the view
+    pub fn project<Sub>(&self, genotype: &Gnt) -> Sub
+    where
+        Sub: Genotype<A>,
+    {
+        assert_eq!(
+            Sub::LEN,
+            self.len(),
+            "the view has {} loci, but the genotype has length {}",
+            self.len(),
+            Sub::LEN
+        );
+
+        self.alleles(genotype).collect_unsafe()
+    }
+}
+
+// Builds a genome locus by locus, so every locus can have its own gene
+// The length of the genome is the number of genes that were added
+#[derive(Debug, Clone)]
+pub struct GenomeBuilder<Gnt, A, G>
+where
+    A: Allele,
+    G: Gene<A>,
+    Gnt: Genotype<A>,
+{
+    genes: Vec<G>,
+    _allele: PhantomData<A>,
+    _genotype: PhantomData<Gnt>,
+}
+
+impl<Gnt, A, G> Default for GenomeBuilder<Gnt, A, G>
+where
+    A: Allele,
+    G: Gene<A>,
+    Gnt: Genotype<A>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Gnt, A, G> GenomeBuilder<Gnt, A, G>
+where
+    A: Allele,
+    G: Gene<A>,
+    Gnt: Genotype<A>,
+{
+    pub fn new() -> Self {
+        Self {
+            genes: Vec::new(),
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
+
+    pub fn push_gene(mut self, gene: G) -> Self {
+        self.genes.push(gene);
+        self
+    }
+
+    pub fn repeat(mut self, gene: G, n: usize) -> Self {
+        self.genes.extend(std::iter::repeat_n(gene, n));
+        self
+    }
+
+    pub fn build(self) -> Genome<Gnt, A, G> {
+        assert!(
+            !self.genes.is_empty(),
+            "Failed to build: no genes were added"
+        );
+
+        Genome {
+            genes: self.genes,
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
 }
 
 impl<Gnt, A, D> Genome<Gnt, A, DiscreteGene<A, D>>
@@ -71,6 +332,69 @@ where
             _genotype: PhantomData,
         }
     }
+
+    // Tiles a block with a domain per locus `times` times, e.g. for the `M` blocks of size `K`
+    // of a concatenated trap, so that the block structure is encoded in the genome itself
+    pub fn repeat_block(block_domains: Vec<D>, times: usize) -> Self {
+        assert_eq!(
+            block_domains.len() * times,
+            Gnt::LEN,
+            "length of the genotype must equal the block size times the number of blocks"
+        );
+
+        let block: Vec<_> = block_domains
+            .iter()
+            .map(DiscreteGene::with_domain)
+            .collect();
+
+        Self {
+            genes: block.iter().cycle().take(Gnt::LEN).cloned().collect(),
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
+
+    // Creates a new genome by transforming the domain of each locus, preserving the order of the genes
+    pub fn map_domains<Gnt2, A2, D2, M>(&self, f: M) -> Genome<Gnt2, A2, DiscreteGene<A2, D2>>
+    where
+        A2: Allele + Discrete,
+        D2: DiscreteDomain<A2>,
+        Gnt2: Genotype<A2>,
+        M: Fn(usize, &D) -> D2,
+    {
+        assert_eq!(
+            Gnt2::LEN,
+            self.len(),
+            "length of the new genotype must equal the length of the genome"
+        );
+
+        Genome {
+            genes: self
+                .iter()
+                .enumerate()
+                .map(|(idx, gene)| DiscreteGene::with_domain(&f(idx, gene.domain())))
+                .collect(),
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
+}
+
+impl<Gnt, A, D> Genome<Gnt, A, RealGene<A, D>>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    Gnt: Genotype<A>,
+{
+    pub fn with_real_domain(domain: &D) -> Self {
+        Self {
+            genes: (0..Gnt::LEN)
+                .map(|_| RealGene::with_domain(domain))
+                .collect(),
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
 }
 
 impl<Gnt> Genome<Gnt, bool, DiscreteGene<bool, BoolDomain>>
@@ -87,3 +411,218 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        gene::{DisjointIntegralDomain, ExclusiveRangeRealDomain, InclusiveRangeRealDomain},
+        idom,
+        individual::Individual,
+        model::UnivariateModel,
+        rdom,
+        rng::SeededRng,
+        variation::Umda,
+    };
+
+    #[test]
+    fn map_bool_domains_to_integer_domains() {
+        let genome: Genome<[bool; 10], _, _> = Genome::with_bool_domain();
+
+        let mapped: Genome<[u8; 10], _, _> = genome.map_domains(|idx, domain| {
+            DisjointIntegralDomain::from_range(0..(domain.len() + idx) as u8)
+        });
+
+        assert_eq!(mapped.len(), genome.len());
+        for (idx, gene) in mapped.iter().enumerate() {
+            assert_eq!(gene.domain().len(), 2 + idx);
+        }
+    }
+
+    #[test]
+    fn univariate_model_of_one_trap_block() {
+        const BLOCKS: usize = 4;
+        const K: usize = 4;
+
+        let mut rng = SeededRng::seed_from_u64(0);
+        let genome: Genome<[bool; BLOCKS * K], _, _> = Genome::with_bool_domain();
+
+        // Every block except the second is all ones, so only the second block varies
+        let population: Vec<Individual<_, _, usize>> = genome
+            .random_genotype_batch(&mut rng, 200)
+            .into_iter()
+            .map(|mut genotype| {
+                genotype[..K].fill(true);
+                genotype[2 * K..].fill(true);
+                Individual::from_genotype(genotype)
+            })
+            .collect();
+
+        let view = genome.slice(K..2 * K);
+        assert_eq!(view.range(), K..2 * K);
+        assert_eq!(view.cardinality(), Some(16));
+        assert!(population
+            .iter()
+            .all(|idv| view.contains_genotype(idv.genotype())));
+
+        let block_genome: Genome<[bool; K], _, _> = view.to_genome();
+        let block_population: Vec<Individual<_, _, usize>> = population
+            .iter()
+            .map(|idv| Individual::from_genotype(view.project(idv.genotype())))
+            .collect();
+
+        let model = UnivariateModel::estimate_from_population(&block_genome, &block_population);
+        let distribution = model.to_distribution();
+        assert_eq!(distribution.len(), K);
+
+        for (locus, probabilities) in distribution.iter().enumerate() {
+            let ones = population
+                .iter()
+                .filter(|idv| idv.genotype()[K + locus])
+                .count();
+            let expected = ones as f64 / population.len() as f64;
+            assert!((probabilities[BoolDomain.index_of(true)] - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn repeat_block_tiles_domains() {
+        const K: usize = 5;
+        const M: usize = 12;
+
+        let block = vec![
+            idom!(0..2),
+            idom!(0..3),
+            idom!(0..2),
+            idom!(1, 4),
+            idom!(0..5),
+        ];
+        let genome: Genome<[u8; K * M], _, _> = Genome::repeat_block(block.clone(), M);
+
+        assert_eq!(genome.len(), 60);
+        for (locus, gene) in genome.iter().enumerate() {
+            let alleles: Vec<u8> = gene.domain().iter().collect();
+            let expected: Vec<u8> = block[locus % K].iter().collect();
+            assert_eq!(alleles, expected);
+        }
+    }
+
+    #[test]
+    fn cardinality_of_bool_genome() {
+        let genome: Genome<[bool; 10], _, _> = Genome::with_bool_domain();
+        assert_eq!(genome.cardinality(), Some(1024));
+
+        let genome: Genome<[bool; 200], _, _> = Genome::with_bool_domain();
+        assert_eq!(genome.cardinality(), None);
+    }
+
+    #[test]
+    fn cardinality_of_real_genome() {
+        let genome: Genome<[f64; 2], _, _> = Genome::with_real_domain(&rdom!(0.0..1.0));
+        assert_eq!(genome.cardinality(), None);
+    }
+
+    #[test]
+    fn random_genotype_batch_is_valid() {
+        let genome: Genome<[u8; 12], _, _> = Genome::with_discrete_domain(&idom!(1, 3, 5));
+
+        let batch = genome.random_genotype_batch(&mut rand::thread_rng(), 1000);
+
+        assert_eq!(batch.len(), 1000);
+        for genotype in batch {
+            assert!(genotype.iter().all(|allele| [1, 3, 5].contains(&allele)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the genome has 3 genes, but the genotype has length 4")]
+    fn operator_rejects_genome_of_wrong_length() {
+        let genome: Genome<[bool; 4], _, _> = Genome {
+            genes: vec![DiscreteGene::with_domain(&BoolDomain); 3],
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        };
+
+        Umda::with_genome(&genome);
+    }
+
+    #[test]
+    fn builder_derives_length_from_genes() {
+        let genome: Genome<[u8; 3], _, _> = GenomeBuilder::new()
+            .push_gene(DiscreteGene::with_domain(&idom!(0..2)))
+            .push_gene(DiscreteGene::with_domain(&idom!(5..=9)))
+            .push_gene(DiscreteGene::with_domain(&idom!(1, 3, 7)))
+            .build();
+
+        assert_eq!(genome.len(), 3);
+        assert_eq!(genome.cardinality(), Some(2 * 5 * 3));
+        assert_eq!(genome.get(1).domain().len(), 5);
+    }
+
+    #[test]
+    fn builder_repeats_genes() {
+        let genome: Genome<[u8; 5], _, _> = GenomeBuilder::new()
+            .repeat(DiscreteGene::with_domain(&idom!(0..4)), 4)
+            .push_gene(DiscreteGene::with_domain(&idom!(0..2)))
+            .build();
+
+        assert_eq!(genome.len(), 5);
+        assert_eq!(genome.cardinality(), Some(4u128.pow(4) * 2));
+    }
+
+    #[test]
+    fn contains_genotype_checks_every_locus() {
+        let genome: Genome<[u8; 3], _, _> = GenomeBuilder::new()
+            .push_gene(DiscreteGene::with_domain(&idom!(0..2)))
+            .push_gene(DiscreteGene::with_domain(&idom!(5..=9)))
+            .push_gene(DiscreteGene::with_domain(&idom!(1, 3, 7)))
+            .build();
+
+        assert!(genome.contains_genotype(&[0, 5, 1]));
+        assert!(genome.contains_genotype(&[1, 9, 7]));
+        assert!(!genome.contains_genotype(&[2, 5, 1]));
+        assert!(!genome.contains_genotype(&[0, 4, 1]));
+        assert!(!genome.contains_genotype(&[0, 5, 2]));
+
+        let batch = genome.random_genotype_batch(&mut rand::thread_rng(), 100);
+        assert!(batch
+            .iter()
+            .all(|genotype| genome.contains_genotype(genotype)));
+    }
+
+    #[test]
+    fn contains_genotype_respects_real_bounds() {
+        let genome: Genome<[f64; 2], _, _> = Genome::with_real_domain(&rdom!(0.0..1.0));
+        assert!(genome.contains_genotype(&[0.0, 0.5]));
+        assert!(!genome.contains_genotype(&[0.5, 1.0]));
+        assert!(!genome.contains_genotype(&[f64::NAN, 0.5]));
+
+        let genome: Genome<[f64; 2], _, _> = Genome::with_real_domain(&rdom!(0.0..=1.0));
+        assert!(genome.contains_genotype(&[0.5, 1.0]));
+        assert!(!genome.contains_genotype(&[-0.1, 1.0]));
+    }
+
+    #[test]
+    fn sample_n_distinct_genotypes() {
+        let genome: Genome<[bool; 6], _, _> = Genome::with_bool_domain();
+        let mut rng = SeededRng::seed_from_u64(0);
+
+        let genotypes = genome.sample_n_distinct(&mut rng, 40);
+        let distinct: HashSet<_> = genotypes.iter().collect();
+        assert_eq!(genotypes.len(), 40);
+        assert_eq!(distinct.len(), 40);
+    }
+
+    #[test]
+    fn sample_n_distinct_covers_small_space() {
+        let genome: Genome<[bool; 3], _, _> = Genome::with_bool_domain();
+        let mut rng = SeededRng::seed_from_u64(0);
+
+        let genotypes = genome.sample_n_distinct(&mut rng, 20);
+        let distinct: HashSet<_> = genotypes.iter().collect();
+        assert_eq!(genotypes.len(), 20);
+        assert_eq!(distinct.len(), 8);
+    }
+}
@@ -2,6 +2,8 @@ use crate::gene::Allele;
 use crate::genome::Cartesian;
 use crate::types::FromIteratorUnsafe;
 use arrayvec::ArrayVec;
+use ndarray::{Array, Ix1};
+use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
@@ -49,6 +51,15 @@ where
             _allele: PhantomData,
         }
     }
+
+    // Orders genotypes lexicographically by their alleles, which gives dedup, archives and
+    // tie-breaking a total order that does not depend on the genotype representation
+    fn cmp_genotype(&self, other: &Self) -> Ordering
+    where
+        A: Ord,
+    {
+        self.iter().cmp(other.iter())
+    }
 }
 
 pub struct GenotypeIter<'a, G, A>
@@ -129,6 +140,126 @@ where
     }
 }
 
+// A fixed-length genotype backed by an ndarray vector, for use with the ndarray-based statistics code
+#[derive(Debug, Clone)]
+pub struct SizedArray<T, const N: usize> {
+    array: Array<T, Ix1>,
+}
+
+impl<T, const N: usize> SizedArray<T, N> {
+    pub fn as_array(&self) -> &Array<T, Ix1> {
+        &self.array
+    }
+
+    pub fn into_array(self) -> Array<T, Ix1> {
+        self.array
+    }
+}
+
+impl<T, const N: usize> From<Array<T, Ix1>> for SizedArray<T, N> {
+    fn from(array: Array<T, Ix1>) -> Self {
+        assert_eq!(array.len(), N, "length of the array must equal N");
+        Self { array }
+    }
+}
+
+impl<T, const N: usize> FromIteratorUnsafe<T> for SizedArray<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            array: Array::from_iter(iter),
+        }
+    }
+}
+
+impl<T, const N: usize> Genotype<T> for SizedArray<T, N>
+where
+    T: Allele,
+{
+    const LEN: usize = N;
+
+    fn get(&self, index: usize) -> T {
+        self.array[index]
+    }
+}
+
+impl<T, const N: usize> Cartesian<T> for SizedArray<T, N>
+where
+    T: Allele,
+{
+    fn set(&mut self, index: usize, gene: T) {
+        self.array[index] = gene;
+    }
+}
+
+pub const DEFAULT_STEP_SIZE: f64 = 1.0;
+
+// Pairs each object variable with a mutation step size (strategy parameter) that evolves along with it
+#[derive(Debug, Clone)]
+pub struct SelfAdaptiveGenotype<A, const N: usize>
+where
+    A: Allele,
+{
+    values: [A; N],
+    step_sizes: [f64; N],
+}
+
+impl<A, const N: usize> SelfAdaptiveGenotype<A, N>
+where
+    A: Allele,
+{
+    pub fn step_size(&self, index: usize) -> f64 {
+        self.step_sizes[index]
+    }
+
+    pub fn set_step_size(&mut self, index: usize, step_size: f64) {
+        self.step_sizes[index] = step_size;
+    }
+
+    pub fn step_sizes(&self) -> &[f64; N] {
+        &self.step_sizes
+    }
+}
+
+impl<A, const N: usize> FromIteratorUnsafe<A> for SelfAdaptiveGenotype<A, N>
+where
+    A: Allele,
+{
+    // Bare values carry no strategy parameters, so a genotype built from them (e.g. sampled from
+    // the genome) starts with the default step sizes
+    fn from_iter<I: IntoIterator<Item = A>>(iter: I) -> Self {
+        Self {
+            values: <[A; N] as FromIteratorUnsafe<A>>::from_iter(iter),
+            step_sizes: [DEFAULT_STEP_SIZE; N],
+        }
+    }
+}
+
+impl<A, const N: usize> Genotype<A> for SelfAdaptiveGenotype<A, N>
+where
+    A: Allele,
+{
+    const LEN: usize = N;
+
+    fn get(&self, index: usize) -> A {
+        self.values[index]
+    }
+}
+
+impl<A, const N: usize> Cartesian<A> for SelfAdaptiveGenotype<A, N>
+where
+    A: Allele,
+{
+    fn set(&mut self, index: usize, gene: A) {
+        self.values[index] = gene;
+    }
+
+    // Crossover passes on the step size of a value together with the value itself
+    fn inherit(&mut self, index: usize, donor: &Self) {
+        self.values[index] = donor.values[index];
+        self.step_sizes[index] = donor.step_sizes[index];
+    }
+}
+
 macro_rules! impl_cartesian_genotype_for_vec_types {
     (for $($g:ty),+) => {
         $(
@@ -140,3 +271,165 @@ macro_rules! impl_cartesian_genotype_for_vec_types {
 }
 
 impl_cartesian_genotype_for_vec_types!(for bool, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, usize, isize, f32, f64);
+
+// Bit strings of at most 64 or 128 bits packed into a single word, which are much smaller and
+// faster to copy, compare and count than `[bool; N]`. Bit `i` is locus `i`.
+macro_rules! impl_bit_genotype {
+    ($($name:ident: $word:ty),+) => {
+        $(
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+            pub struct $name<const N: usize> {
+                bits: $word,
+            }
+
+            impl<const N: usize> $name<N> {
+                // Rejects lengths that do not fit in the word at compile time
+                const FITS: () = assert!(N <= <$word>::BITS as usize, "the genotype does not fit");
+
+                // The bits beyond the first `N` must be zero
+                pub fn from_bits(bits: $word) -> Self {
+                    let () = Self::FITS;
+                    assert!(
+                        N == <$word>::BITS as usize || bits >> N == 0,
+                        "bits beyond the length of the genotype are set"
+                    );
+                    Self { bits }
+                }
+
+                pub fn bits(&self) -> $word {
+                    self.bits
+                }
+
+                pub fn flip(&mut self, index: usize) {
+                    assert!(index < N, "index {} out of bounds for length {}", index, N);
+                    self.bits ^= 1 << index;
+                }
+
+                pub fn count_ones(&self) -> usize {
+                    self.bits.count_ones() as usize
+                }
+
+                pub fn hamming_distance(&self, other: &Self) -> usize {
+                    (self.bits ^ other.bits).count_ones() as usize
+                }
+            }
+
+            impl<const N: usize> FromIteratorUnsafe<bool> for $name<N> {
+                fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+                    let () = Self::FITS;
+                    let bits = iter
+                        .into_iter()
+                        .take(N)
+                        .enumerate()
+                        .fold(0, |bits, (index, bit)| bits | (<$word>::from(bit) << index));
+                    Self { bits }
+                }
+            }
+
+            impl<const N: usize> Genotype<bool> for $name<N> {
+                const LEN: usize = N;
+
+                fn get(&self, index: usize) -> bool {
+                    assert!(index < N, "index {} out of bounds for length {}", index, N);
+                    (self.bits >> index) & 1 == 1
+                }
+            }
+
+            impl<const N: usize> Cartesian<bool> for $name<N> {
+                fn set(&mut self, index: usize, bit: bool) {
+                    assert!(index < N, "index {} out of bounds for length {}", index, N);
+                    self.bits = (self.bits & !(1 << index)) | (<$word>::from(bit) << index);
+                }
+            }
+        )+
+    };
+}
+
+impl_bit_genotype!(BitGenotype64: u64, BitGenotype128: u128);
+
+#[cfg(test)]
+mod tests {
+    use rand::{seq::SliceRandom, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        distance::{DistanceMetric, Hamming},
+        rng::SeededRng,
+    };
+
+    #[test]
+    fn genotypes_sort_lexicographically() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let genotypes: Vec<[u8; 3]> = (0..100)
+            .map(|_| {
+                [
+                    rng.gen_range(0..3),
+                    rng.gen_range(0..3),
+                    rng.gen_range(0..3),
+                ]
+            })
+            .collect();
+
+        let mut expected = genotypes.clone();
+        expected.sort();
+
+        for _ in 0..5 {
+            let mut shuffled = genotypes.clone();
+            shuffled.shuffle(&mut rng);
+            shuffled.sort_by(|a, b| a.cmp_genotype(b));
+            assert_eq!(shuffled, expected);
+        }
+
+        let sized: Vec<SizedArray<u8, 3>> = expected
+            .iter()
+            .map(|genotype| Array::from_iter(genotype.iter()).into())
+            .collect();
+        assert!(sized
+            .windows(2)
+            .all(|pair| pair[0].cmp_genotype(&pair[1]).is_le()));
+    }
+
+    #[test]
+    fn bit_genotype_behaves_like_bool_array() {
+        const N: usize = 40;
+
+        let mut rng = SeededRng::seed_from_u64(0);
+        let arrays: Vec<[bool; N]> = (0..50)
+            .map(|_| std::array::from_fn(|_| rng.gen()))
+            .collect();
+        let bits: Vec<BitGenotype64<N>> = arrays
+            .iter()
+            .map(|array| FromIteratorUnsafe::from_iter(array.iter()))
+            .collect();
+
+        for (array, genotype) in arrays.iter().zip(&bits) {
+            assert_eq!(genotype.len(), N);
+            assert!(genotype.iter().eq(array.iter()));
+            assert_eq!(
+                genotype.count_ones(),
+                array.iter().filter(|bit| *bit).count()
+            );
+
+            let mut array = *array;
+            let mut genotype = *genotype;
+            let index = rng.gen_range(0..N);
+            array.set(index, !array.get(index));
+            genotype.flip(index);
+            assert!(genotype.iter().eq(array.iter()));
+            array.set(index, true);
+            genotype.set(index, true);
+            assert!(genotype.iter().eq(array.iter()));
+        }
+
+        for i in 0..arrays.len() - 1 {
+            let (a, b) = (&bits[i], &bits[i + 1]);
+            let expected = Hamming.distance(&arrays[i], &arrays[i + 1]);
+            assert_eq!(a.hamming_distance(b) as f64, expected);
+            assert_eq!(a.cmp_genotype(b), arrays[i].cmp_genotype(&arrays[i + 1]));
+        }
+
+        let ones = BitGenotype128::<100>::from_iter(std::iter::repeat(true));
+        assert_eq!(ones.count_ones(), 100);
+        assert_eq!(ones, BitGenotype128::from_bits((1 << 100) - 1));
+    }
+}
@@ -0,0 +1,257 @@
+use std::marker::PhantomData;
+
+use rand::seq::SliceRandom;
+
+use crate::{
+    fitness::{Fitness, FitnessFunc},
+    gene::Allele,
+    genome::Cartesian,
+    genotype::Genotype,
+    individual::Individual,
+    model::Factorization,
+    parallel::*,
+    rng::{item_rngs, SeededRng},
+    variation::VariationOperator,
+};
+
+// Gene-pool optimal mixing (as in GOMEA): every individual is improved in place by copying the
+// genes of each factor from a random donor, keeping a change only if it does not worsen the
+// fitness. Selection thus happens inside the operator, so it must be paired with
+// `CopyOffspringSelection`, e.g. via `SimpleGABuilder::with_optimal_mixing`.
+#[derive(Debug, Clone)]
+pub struct Gom<Gnt, A>
+where
+    A: Allele,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    factorization: Option<Factorization>,
+    _allele: PhantomData<A>,
+    _genotype: PhantomData<Gnt>,
+}
+
+impl<Gnt, A> Default for Gom<Gnt, A>
+where
+    A: Allele,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    // Mixes every gene separately
+    fn default() -> Self {
+        Self {
+            factorization: None,
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
+}
+
+impl<Gnt, A> Gom<Gnt, A>
+where
+    A: Allele,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    pub fn with_factorization(factorization: Factorization) -> Self {
+        Self {
+            factorization: Some(factorization),
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
+
+    fn mix<F>(
+        &self,
+        parent: &Individual<Gnt, A, F>,
+        population: &[Individual<Gnt, A, F>],
+        factorization: &Factorization,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        rng: &mut SeededRng,
+    ) -> Individual<Gnt, A, F>
+    where
+        F: Fitness,
+    {
+        let mut child = parent.clone();
+        child.reset_age();
+
+        let mut factors: Vec<_> = factorization.iter().collect();
+        factors.shuffle(rng);
+
+        for factor in factors {
+            let donor = population.choose(rng).unwrap();
+
+            // Copying identical genes cannot change the fitness
+            if factor
+                .iter()
+                .all(|idx| donor.genotype().get(*idx) == child.genotype().get(*idx))
+            {
+                continue;
+            }
+
+            let mut genotype = child.genotype().clone();
+            for idx in factor {
+                genotype.inherit(*idx, donor.genotype());
+            }
+
+            let mut candidate = Individual::from_genotype(genotype);
+            fitness_func.evaluate(&mut candidate);
+
+            if fitness_func
+                .cmp(&candidate.fitness(), &child.fitness())
+                .is_le()
+            {
+                child = candidate;
+            }
+        }
+
+        child
+    }
+}
+
+impl<Gnt, A, F> VariationOperator<Gnt, A, F> for Gom<Gnt, A>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>> {
+        let factorization = self
+            .factorization
+            .clone()
+            .unwrap_or_else(|| Factorization::univariate(Gnt::LEN));
+
+        // Mixing an individual takes at most one evaluation per factor; individuals beyond the
+        // cap are copied unchanged
+        let evaluations_per_individual = factorization.iter().count().max(1);
+        let n = max_new_evaluations.map_or(population.len(), |cap| {
+            (cap / evaluations_per_individual).min(population.len())
+        });
+
+        item_rngs(rng, population.len())
+            .into_par_iter()
+            .zip(population.par_iter())
+            .enumerate()
+            .map(|(idx, (mut rng, parent))| {
+                if idx < n {
+                    self.mix(parent, population, &factorization, fitness_func, &mut rng)
+                } else {
+                    parent.clone()
+                }
+            })
+            .collect()
+    }
+
+    fn mutates(&self) -> bool {
+        false
+    }
+
+    fn selects_offspring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fitness::OptimizationGoal,
+        gene::BoolDomain,
+        genome::Genome,
+        selection::{CopyOffspringSelection, NoSelection},
+        simplega::SimpleGABuilder,
+    };
+
+    const K: usize = 4;
+    const M: usize = 4;
+    type Gnt = [bool; K * M];
+
+    fn trap(genotype: &Gnt) -> usize {
+        genotype
+            .chunks(K)
+            .map(|block| match block.iter().filter(|bit| **bit).count() {
+                K => K,
+                ones => K - 1 - ones,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn optimal_mixing_never_decreases_best_fitness() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let factorization =
+            Factorization::from_groups((0..M).map(|b| (b * K..(b + 1) * K).collect()).collect());
+
+        let mut ga = SimpleGABuilder::with_optimal_mixing(Gom::with_factorization(factorization))
+            .genome(&genome)
+            .random_population(50)
+            .evaluation_function(&trap)
+            .goal(OptimizationGoal::Maximize)
+            .build();
+
+        ga.initialize();
+        let mut best = ga.best_individual().unwrap().fitness();
+        let mut previous: Vec<_> = ga.iter().map(|idv| idv.fitness()).collect();
+
+        for _ in 0..10 {
+            ga.step();
+
+            let new_best = ga.best_individual().unwrap().fitness();
+            assert!(new_best >= best);
+            best = new_best;
+
+            // Every individual is only ever replaced by an equal or better one
+            for (idv, old) in ga.iter().zip(previous.iter()) {
+                assert!(idv.fitness() >= *old);
+            }
+            previous = ga.iter().map(|idv| idv.fitness()).collect();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "selects its own offspring")]
+    fn optimal_mixing_requires_copy_offspring_selection() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(10)
+            .evaluation_function(&trap)
+            .selection(NoSelection)
+            .variation(Gom::default())
+            .build();
+    }
+
+    #[test]
+    fn copy_offspring_selection_is_accepted() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(10)
+            .evaluation_function(&trap)
+            .selection(CopyOffspringSelection)
+            .variation(Gom::default())
+            .build();
+    }
+
+    #[test]
+    fn mixed_offspring_are_new() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::with_optimal_mixing(Gom::default())
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&trap)
+            .goal(OptimizationGoal::Maximize)
+            .build();
+
+        ga.initialize();
+        for _ in 0..5 {
+            ga.step();
+            assert!(ga.iter().all(|idv| idv.age() == 0));
+        }
+    }
+}
@@ -18,6 +18,8 @@ where
 {
     genotype: Gnt,
     fitness: Option<F>,
+    age: usize,
+    skipped: bool,
     _gene: PhantomData<A>,
 }
 
@@ -31,6 +33,8 @@ where
         Individual {
             genotype,
             fitness: None,
+            age: 0,
+            skipped: false,
             _gene: PhantomData,
         }
     }
@@ -45,6 +49,8 @@ where
         Individual {
             genotype,
             fitness: None,
+            age: 0,
+            skipped: false,
             _gene: PhantomData,
         }
     }
@@ -61,7 +67,36 @@ where
     }
 
     pub fn set_fitness(&mut self, fitness: F) {
-        self.fitness = Some(fitness)
+        self.fitness = Some(fitness);
+        self.skipped = false;
+    }
+
+    pub fn is_evaluated(&self) -> bool {
+        self.fitness.is_some()
+    }
+
+    // Whether the evaluation failed under `FailurePolicy::Skip`, so that the individual is left
+    // out of the offspring (it still has the worst fitness)
+    pub fn is_skipped(&self) -> bool {
+        self.skipped
+    }
+
+    pub(crate) fn mark_skipped(&mut self) {
+        self.skipped = true;
+    }
+
+    // The number of generations the individual has survived; new offspring have age 0
+    pub fn age(&self) -> usize {
+        self.age
+    }
+
+    pub fn grow_older(&mut self) {
+        self.age += 1;
+    }
+
+    // Makes a copy of a parent a new offspring, e.g. when it is created by copying the parent
+    pub fn reset_age(&mut self) {
+        self.age = 0;
     }
 }
 
@@ -75,6 +110,8 @@ where
         Self {
             genotype: self.genotype.clone(),
             fitness: self.fitness,
+            age: self.age,
+            skipped: self.skipped,
             _gene: PhantomData,
         }
     }
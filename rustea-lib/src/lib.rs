@@ -1,15 +1,31 @@
 #![feature(array_chunks)]
 
+pub mod archive;
+pub mod benchmark;
+pub mod cmaes;
+pub mod distance;
 pub mod ecga;
 pub mod fitness;
 pub mod gene;
 pub mod genome;
 pub mod genotype;
+pub mod gom;
 pub mod individual;
 pub mod model;
+pub mod mutation;
+pub mod parallel;
+pub mod parameterless;
+pub mod parse;
+pub mod population;
+pub mod prelude;
+pub mod progress;
+pub mod restart;
 pub mod rng;
 pub mod selection;
 pub mod simplega;
 pub mod statistics;
+pub mod surrogate;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod types;
 pub mod variation;
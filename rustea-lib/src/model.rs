@@ -4,13 +4,94 @@ use crate::{
     genome::Genome,
     genotype::Genotype,
     individual::Individual,
+    parallel::*,
     types::CollectUnsafe,
 };
 use approx::abs_diff_ne;
 use rand::Rng;
-use rand_distr::WeightedIndex;
-use rayon::prelude::{IntoParallelIterator, ParallelIterator};
-use std::{marker::PhantomData, ops::Index};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    marker::PhantomData,
+    ops::Index,
+};
+
+// Samples indices proportionally to a set of weights by binary searching over their prefix sums.
+// Unlike `WeightedIndex`, the mapping from random numbers to indices is fixed here, so seeded runs
+// stay reproducible across versions of rand.
+#[derive(Debug, Clone)]
+pub(crate) struct CumulativeDistribution {
+    cumulative: Vec<f64>,
+    last: usize,
+}
+
+impl CumulativeDistribution {
+    pub(crate) fn from_weights<I>(weights: I) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        let cumulative: Vec<f64> = weights
+            .into_iter()
+            .scan(0.0, |acc, weight| {
+                assert!(weight >= 0.0, "Weights must be non-negative");
+                *acc += weight;
+                Some(*acc)
+            })
+            .collect();
+
+        let total = *cumulative.last().expect("At least one weight is required");
+        assert!(total > 0.0, "At least one weight must be positive");
+
+        // Index of the last non-zero weight, in case rounding puts a sample at the very end
+        let last = cumulative.partition_point(|c| *c < total);
+
+        Self { cumulative, last }
+    }
+
+    // Normalizes the weights and lifts every non-zero probability to at least `min_probability`,
+    // so that rare but present states remain sampleable
+    pub(crate) fn with_min_probability<I>(weights: I, min_probability: f64) -> Self
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        let weights: Vec<f64> = weights.into_iter().collect();
+        let total: f64 = weights.iter().sum();
+
+        Self::from_weights(weights.into_iter().map(|weight| {
+            if weight > 0.0 {
+                (weight / total).max(min_probability)
+            } else {
+                0.0
+            }
+        }))
+    }
+
+    // The normalized probability of each index
+    pub(crate) fn probabilities(&self) -> Vec<f64> {
+        let total = self.cumulative[self.cumulative.len() - 1];
+
+        self.cumulative
+            .iter()
+            .scan(0.0, |previous, c| {
+                let p = (c - *previous) / total;
+                *previous = *c;
+                Some(p)
+            })
+            .collect()
+    }
+
+    pub(crate) fn sample<R>(&self, rng: &mut R) -> usize
+    where
+        R: Rng + ?Sized,
+    {
+        let total = self.cumulative[self.cumulative.len() - 1];
+        let u = rng.gen::<f64>() * total;
+
+        self.cumulative.partition_point(|c| *c <= u).min(self.last)
+    }
+}
 
 #[derive(Debug)]
 pub struct UnivariateModel<'a, Gnt, A, D, F>
@@ -20,7 +101,7 @@ where
     F: Fitness,
     Gnt: Genotype<A>,
 {
-    distributions: Vec<WeightedIndex<usize>>,
+    distributions: Vec<CumulativeDistribution>,
     genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
     _genotype: PhantomData<Gnt>,
     _fitness: PhantomData<F>,
@@ -54,7 +135,9 @@ where
 
         let distributions = counts
             .into_iter()
-            .map(|counts| WeightedIndex::new(counts).unwrap())
+            .map(|counts| {
+                CumulativeDistribution::from_weights(counts.into_iter().map(|c| c as f64))
+            })
             .collect();
 
         Self {
@@ -73,14 +156,63 @@ where
             .genome
             .iter()
             .enumerate()
-            .map(|(idx, gene)| gene.sample_with_weights(rng, &self.distributions[idx]))
+            .map(|(idx, gene)| gene.domain().get(self.distributions[idx].sample(rng)))
             .collect_unsafe();
 
         Individual::from_genotype(genotype)
     }
+
+    // The marginal probability of each allele (in domain order) at each locus, e.g. to store a
+    // learned model and reload it with `from_distribution`
+    pub fn to_distribution(&self) -> Vec<Vec<f64>> {
+        self.distributions
+            .iter()
+            .map(|distribution| distribution.probabilities())
+            .collect()
+    }
+
+    // Builds a model from marginal probabilities, as returned by `to_distribution`. The
+    // probabilities of each locus are normalized.
+    pub fn from_distribution(
+        genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+        distribution: Vec<Vec<f64>>,
+    ) -> Self {
+        assert_eq!(
+            distribution.len(),
+            genome.len(),
+            "there must be one marginal distribution per locus of the genome"
+        );
+
+        let distributions = distribution
+            .into_iter()
+            .zip(genome.iter())
+            .enumerate()
+            .map(|(idx, (probabilities, gene))| {
+                assert_eq!(
+                    probabilities.len(),
+                    gene.domain().len(),
+                    "the distribution of locus {} does not match the size of its domain",
+                    idx
+                );
+                CumulativeDistribution::from_weights(probabilities)
+            })
+            .collect();
+
+        Self {
+            distributions,
+            genome,
+            _genotype: PhantomData,
+            _fitness: PhantomData,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "Vec<Vec<usize>>", into = "Vec<Vec<usize>>")
+)]
 pub struct Factorization {
     factors: Vec<Vec<usize>>,
 }
@@ -100,6 +232,15 @@ impl Factorization {
         }
     }
 
+    // Builds a factorization from known groups of linked loci; every locus must appear in exactly
+    // one group
+    pub fn from_groups(groups: Vec<Vec<usize>>) -> Self {
+        match Self::try_from(groups) {
+            Ok(factorization) => factorization,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
     pub fn join(&self, idx_a: usize, idx_b: usize) -> Self {
         let mut joined = self.factors[idx_a].clone();
         joined.extend(self.factors[idx_b].iter());
@@ -138,6 +279,11 @@ impl Factorization {
         self.factors.iter().filter(|f| !f.is_empty())
     }
 
+    // The number of loci covered by all factors combined
+    pub fn num_loci(&self) -> usize {
+        self.factors.iter().map(|f| f.len()).sum()
+    }
+
     pub fn iter_genotype<'a, Gnt, A>(
         &'a self,
         genotype: &'a Gnt,
@@ -157,6 +303,58 @@ impl PartialEq for Factorization {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FactorizationError {
+    // A locus is not smaller than the total number of loci in the groups
+    OutOfRange { locus: usize, len: usize },
+    // A locus appears in more than one group
+    Duplicate { locus: usize },
+}
+
+impl Display for FactorizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FactorizationError::OutOfRange { locus, len } => {
+                write!(f, "locus {} is out of range for {} loci", locus, len)
+            }
+            FactorizationError::Duplicate { locus } => {
+                write!(f, "locus {} appears in more than one group", locus)
+            }
+        }
+    }
+}
+
+impl Error for FactorizationError {}
+
+// Like `from_groups`, but returns an error for invalid groups instead of panicking; deserialization
+// goes through here as well
+impl TryFrom<Vec<Vec<usize>>> for Factorization {
+    type Error = FactorizationError;
+
+    fn try_from(groups: Vec<Vec<usize>>) -> Result<Self, Self::Error> {
+        let len: usize = groups.iter().map(|group| group.len()).sum();
+        let mut seen = vec![false; len];
+
+        for &locus in groups.iter().flatten() {
+            if locus >= len {
+                return Err(FactorizationError::OutOfRange { locus, len });
+            }
+            if seen[locus] {
+                return Err(FactorizationError::Duplicate { locus });
+            }
+            seen[locus] = true;
+        }
+
+        Ok(Self { factors: groups })
+    }
+}
+
+impl From<Factorization> for Vec<Vec<usize>> {
+    fn from(factorization: Factorization) -> Self {
+        factorization.factors
+    }
+}
+
 impl FromIterator<Vec<usize>> for Factorization {
     fn from_iter<T: IntoIterator<Item = Vec<usize>>>(iter: T) -> Self {
         Self {
@@ -175,6 +373,9 @@ impl IntoIterator for Factorization {
     }
 }
 
+// The smallest probability with which a state that occurs in the population is sampled
+pub const DEFAULT_MIN_PROBABILITY: f64 = 1e-9;
+
 #[derive(Debug)]
 pub struct MultivariateModel<'a, Gnt, A, D, F>
 where
@@ -185,6 +386,7 @@ where
 {
     factorization: Factorization,
     probabilities: Vec<Vec<f64>>,
+    distributions: Vec<CumulativeDistribution>,
     genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
     sample_size: usize,
     _fitness: PhantomData<F>,
@@ -247,11 +449,22 @@ where
                     .map(|count| *count as f64 / population.len() as f64)
                     .collect()
             })
+            .collect::<Vec<Vec<f64>>>();
+
+        let distributions = probabilities
+            .iter()
+            .map(|probs| {
+                CumulativeDistribution::with_min_probability(
+                    probs.iter().copied(),
+                    DEFAULT_MIN_PROBABILITY,
+                )
+            })
             .collect();
 
         Self {
             factorization,
             probabilities,
+            distributions,
             genome,
             sample_size: population.len(),
             _fitness: PhantomData,
@@ -269,8 +482,7 @@ where
             .iter()
             .enumerate()
             .for_each(|(factor_idx, factor)| {
-                let distr = WeightedIndex::new(&self.probabilities[factor_idx]).unwrap();
-                let mut raw_idx = rng.sample(distr);
+                let mut raw_idx = self.distributions[factor_idx].sample(rng);
 
                 // Extract allele indices from raw_idx
                 let n = factor.len();
@@ -334,6 +546,19 @@ where
         self.compressed_population_complexity() + 0.2 * self.model_complexity()
     }
 
+    // Rebuilds the sampling distributions such that every state present in the population is
+    // sampled with at least the given probability
+    pub fn min_probability(mut self, min_probability: f64) -> Self {
+        self.distributions = self
+            .probabilities
+            .iter()
+            .map(|probs| {
+                CumulativeDistribution::with_min_probability(probs.iter().copied(), min_probability)
+            })
+            .collect();
+        self
+    }
+
     pub fn factorization(&self) -> &Factorization {
         &self.factorization
     }
@@ -344,6 +569,116 @@ mod tests {
     use approx::assert_abs_diff_eq;
 
     use super::*;
+    use crate::{gene::DisjointIntegralDomain, idom};
+
+    #[test]
+    fn cumulative_distribution_matches_probabilities() {
+        const SAMPLE_SIZE: usize = 100000;
+        let probabilities = [0.1, 0.0, 0.3, 0.6];
+
+        let distribution = CumulativeDistribution::from_weights(probabilities);
+        let mut rng = rand::thread_rng();
+
+        let mut counts = [0usize; 4];
+        for _ in 0..SAMPLE_SIZE {
+            counts[distribution.sample(&mut rng)] += 1;
+        }
+
+        assert_eq!(counts[1], 0);
+        for (count, p) in counts.into_iter().zip(probabilities) {
+            assert_abs_diff_eq!(count as f64 / SAMPLE_SIZE as f64, p, epsilon = 0.01);
+        }
+    }
+
+    #[test]
+    fn min_probability_keeps_vanishing_weights_sampleable() {
+        const SAMPLE_SIZE: usize = 100000;
+
+        // Without a floor, the rare weight vanishes in the prefix sum
+        let plain = CumulativeDistribution::from_weights([1.0, 1e-20]);
+        let floored = CumulativeDistribution::with_min_probability([1.0, 1e-20], 1e-3);
+        let mut rng = rand::thread_rng();
+
+        let plain_rare = (0..SAMPLE_SIZE)
+            .filter(|_| plain.sample(&mut rng) == 1)
+            .count();
+        let floored_rare = (0..SAMPLE_SIZE)
+            .filter(|_| floored.sample(&mut rng) == 1)
+            .count();
+
+        assert_eq!(plain_rare, 0);
+        assert!(floored_rare > 0);
+    }
+
+    #[test]
+    fn univariate_model_round_trips_through_distribution() {
+        const N: usize = 3;
+        const SAMPLE_SIZE: usize = 100000;
+        type Ftnss = f64;
+
+        let genome = Genome::<[u8; N], _, _>::with_discrete_domain(&idom!(0..3));
+        let population: Vec<Individual<_, _, Ftnss>> = [[0, 1, 2], [0, 2, 2], [1, 1, 2], [0, 0, 2]]
+            .into_iter()
+            .map(Individual::from_genotype)
+            .collect();
+
+        let model = UnivariateModel::estimate_from_population(&genome, &population);
+        let distribution = model.to_distribution();
+        assert_eq!(distribution[0], vec![0.75, 0.25, 0.0]);
+
+        let reloaded: UnivariateModel<'_, _, _, _, Ftnss> =
+            UnivariateModel::from_distribution(&genome, distribution.clone());
+        assert_eq!(reloaded.to_distribution(), distribution);
+
+        // Sampling from either model yields the same marginals
+        let mut rng = rand::thread_rng();
+        let mut frequencies = |model: &UnivariateModel<'_, [u8; N], u8, _, Ftnss>| {
+            let mut counts = [[0usize; 3]; N];
+            for _ in 0..SAMPLE_SIZE {
+                for (idx, allele) in model.sample(&mut rng).genotype().iter().enumerate() {
+                    counts[idx][allele as usize] += 1;
+                }
+            }
+            counts.map(|locus| locus.map(|count| count as f64 / SAMPLE_SIZE as f64))
+        };
+        let original = frequencies(&model);
+        let sampled = frequencies(&reloaded);
+
+        for (locus_a, locus_b) in original.iter().zip(&sampled) {
+            for (p_a, p_b) in locus_a.iter().zip(locus_b) {
+                assert_abs_diff_eq!(p_a, p_b, epsilon = 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn multivariate_model_samples_rare_state() {
+        const N: usize = 4;
+        const SAMPLE_SIZE: usize = 100000;
+        type Ftnss = f64;
+
+        let genome = Genome::<[bool; N], _, _>::with_bool_domain();
+
+        // A single individual out of a thousand has its first two bits set
+        let mut population = vec![Individual::<_, _, Ftnss>::from_genotype([false; N]); 999];
+        population.push(Individual::from_genotype([true, true, false, false]));
+
+        let model = MultivariateModel::estimate_from_population(
+            &genome,
+            &population.iter().collect::<Vec<_>>(),
+            Factorization::univariate(N).join(0, 1),
+        )
+        .min_probability(0.01);
+
+        let mut rng = rand::thread_rng();
+        let rare = (0..SAMPLE_SIZE)
+            .filter(|_| model.sample(&mut rng).genotype()[0])
+            .count();
+
+        // The rare state is lifted to a probability of about 1%
+        assert!(rare > 0);
+        assert_abs_diff_eq!(rare as f64 / SAMPLE_SIZE as f64, 0.01, epsilon = 0.003);
+    }
 
     #[test]
     fn join_univariate_factors_large() {
@@ -476,6 +811,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn model_from_trap_aligned_factorization() {
+        const N: usize = 12;
+        const K: usize = 4;
+        type Ftnss = usize;
+
+        let genome = Genome::<[bool; N], _, _>::with_bool_domain();
+        let factorization = Factorization::from_groups(
+            (0..N / K).map(|b| (b * K..(b + 1) * K).collect()).collect(),
+        );
+
+        assert_eq!(factorization.num_loci(), N);
+
+        // Every trap block is either all zeros or all ones
+        let mut rng = rand::thread_rng();
+        let population: Vec<_> = (0..1000)
+            .map(|_| {
+                let blocks: [bool; N / K] = std::array::from_fn(|_| rng.gen());
+                let genotype: [bool; N] = std::array::from_fn(|idx| blocks[idx / K]);
+                Individual::<_, _, Ftnss>::from_genotype(genotype)
+            })
+            .collect();
+
+        let model = MultivariateModel::estimate_from_population(
+            &genome,
+            &population.iter().collect::<Vec<_>>(),
+            factorization,
+        );
+
+        for _ in 0..1000 {
+            let sample = model.sample(&mut rng);
+            for block in sample.genotype().chunks(K) {
+                assert!(block.iter().all(|bit| *bit == block[0]));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn factorization_rejects_duplicate_loci() {
+        Factorization::from_groups(vec![vec![0, 1], vec![1, 2]]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn factorization_serde_round_trip() {
+        let factorization = Factorization::from_groups(vec![vec![2, 0], vec![1], vec![3]]);
+
+        let json = serde_json::to_string(&factorization).unwrap();
+        assert_eq!(json, "[[2,0],[1],[3]]");
+        assert_eq!(
+            serde_json::from_str::<Factorization>(&json).unwrap(),
+            factorization
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialization_rejects_invalid_factorizations() {
+        for json in ["[[0,1],[1,2]]", "[[0,3],[1]]"] {
+            assert!(serde_json::from_str::<Factorization>(json).is_err());
+        }
+        assert_eq!(
+            Factorization::try_from(vec![vec![0, 1], vec![1, 2]]),
+            Err(FactorizationError::Duplicate { locus: 1 })
+        );
+    }
+
     #[test]
     fn compressed_population_complexity() {
         type Ftnss = f64;
@@ -0,0 +1,840 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+use rand_distr::{Cauchy, Distribution, Normal, StandardNormal};
+
+use crate::{
+    fitness::{Fitness, FitnessFunc},
+    gene::{Allele, Discrete, DiscreteDomain, DiscreteGene, Gene, Real, RealDomain, RealGene},
+    genome::{Cartesian, Genome},
+    genotype::{Genotype, SelfAdaptiveGenotype},
+    individual::Individual,
+    parallel::*,
+    rng::{item_rngs, SeededRng},
+    types::CollectUnsafe,
+    variation::{offspring_count, OperatorStatistics, VariationOperator},
+};
+
+// Creates one mutated and evaluated child per parent, recording each in the statistics
+fn mutate_population<Gnt, A, F, M>(
+    population: &[Individual<Gnt, A, F>],
+    fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    max_new_evaluations: Option<usize>,
+    rng: &mut SeededRng,
+    statistics: &OperatorStatistics,
+    mutate: M,
+) -> Vec<Individual<Gnt, A, F>>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+    M: Fn(&mut SeededRng, &Gnt) -> Gnt + Send + Sync,
+{
+    let n = offspring_count(population.len(), max_new_evaluations);
+
+    population[..n]
+        .par_iter()
+        .zip(item_rngs(rng, n))
+        .map(|(parent, mut rng)| {
+            let mut child = Individual::from_genotype(mutate(&mut rng, parent.genotype()));
+            statistics.record_mutation(parent.genotype(), child.genotype());
+
+            fitness_func.evaluate(&mut child);
+
+            child
+        })
+        .collect()
+}
+
+// Adds a random step to every gene, clamping the result to the domain of the gene
+fn perturb<Gnt, A, D, R, S>(
+    genome: &Genome<Gnt, A, RealGene<A, D>>,
+    genotype: &Gnt,
+    rng: &mut R,
+    mut sample_step: S,
+) -> Gnt
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    Gnt: Genotype<A>,
+    R: Rng + ?Sized,
+    S: FnMut(&mut R) -> f64,
+{
+    genotype
+        .iter()
+        .zip(genome.iter())
+        .map(|(allele, gene)| {
+            let step = A::from(sample_step(rng)).unwrap();
+            gene.domain().clamp(allele + step)
+        })
+        .collect_unsafe()
+}
+
+#[derive(Debug, Clone)]
+pub struct CauchyMutation<'a, Gnt, A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    Gnt: Genotype<A>,
+{
+    genome: &'a Genome<Gnt, A, RealGene<A, D>>,
+    scale: f64,
+    statistics: OperatorStatistics,
+}
+
+impl<'a, Gnt, A, D> CauchyMutation<'a, Gnt, A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    Gnt: Genotype<A>,
+{
+    pub fn with_genome(genome: &'a Genome<Gnt, A, RealGene<A, D>>, scale: f64) -> Self {
+        assert!(scale > 0.0, "scale must be positive");
+        Self {
+            genome,
+            scale,
+            statistics: OperatorStatistics::default(),
+        }
+    }
+
+    pub fn statistics(&self) -> &OperatorStatistics {
+        &self.statistics
+    }
+}
+
+impl<'a, Gnt, A, D, F> VariationOperator<Gnt, A, F> for CauchyMutation<'a, Gnt, A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>> {
+        let distr = Cauchy::new(0.0, self.scale).unwrap();
+
+        mutate_population(
+            population,
+            fitness_func,
+            max_new_evaluations,
+            rng,
+            &self.statistics,
+            |rng, genotype| perturb(self.genome, genotype, rng, |rng| distr.sample(rng)),
+        )
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LevyMutation<'a, Gnt, A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    Gnt: Genotype<A>,
+{
+    genome: &'a Genome<Gnt, A, RealGene<A, D>>,
+    alpha: f64,
+    scale: f64,
+    statistics: OperatorStatistics,
+}
+
+impl<'a, Gnt, A, D> LevyMutation<'a, Gnt, A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    Gnt: Genotype<A>,
+{
+    pub fn with_genome(genome: &'a Genome<Gnt, A, RealGene<A, D>>, alpha: f64) -> Self {
+        assert!(
+            alpha > 0.0 && alpha <= 2.0,
+            "alpha must be in the range (0, 2]"
+        );
+        Self {
+            genome,
+            alpha,
+            scale: 1.0,
+            statistics: OperatorStatistics::default(),
+        }
+    }
+
+    pub fn scale(mut self, scale: f64) -> Self {
+        assert!(scale > 0.0, "scale must be positive");
+        self.scale = scale;
+        self
+    }
+
+    pub fn statistics(&self) -> &OperatorStatistics {
+        &self.statistics
+    }
+
+    // Standard deviation of the numerator in Mantegna's algorithm
+    fn sigma(&self) -> f64 {
+        let alpha = self.alpha;
+        let numerator = gamma(1.0 + alpha) * (PI * alpha / 2.0).sin();
+        let denominator = gamma((1.0 + alpha) / 2.0) * alpha * 2f64.powf((alpha - 1.0) / 2.0);
+
+        (numerator / denominator).powf(1.0 / alpha)
+    }
+}
+
+impl<'a, Gnt, A, D, F> VariationOperator<Gnt, A, F> for LevyMutation<'a, Gnt, A, D>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>> {
+        let numerator = Normal::new(0.0, self.sigma()).unwrap();
+        let exponent = 1.0 / self.alpha;
+
+        mutate_population(
+            population,
+            fitness_func,
+            max_new_evaluations,
+            rng,
+            &self.statistics,
+            |rng, genotype| {
+                perturb(self.genome, genotype, rng, |rng| {
+                    let u: f64 = numerator.sample(rng);
+                    let v: f64 = rng.sample(StandardNormal);
+
+                    self.scale * u / v.abs().powf(exponent)
+                })
+            },
+        )
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+}
+
+// Self-adaptive mutation as used in evolution strategies: the step sizes of each genotype are
+// mutated log-normally, after which each gene is perturbed with a Gaussian of its new step size
+#[derive(Debug, Clone)]
+pub struct SelfAdaptiveMutation<'a, A, D, const N: usize>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+{
+    genome: &'a Genome<SelfAdaptiveGenotype<A, N>, A, RealGene<A, D>>,
+    tau: f64,
+    tau_prime: f64,
+    min_step_size: f64,
+    statistics: OperatorStatistics,
+}
+
+impl<'a, A, D, const N: usize> SelfAdaptiveMutation<'a, A, D, N>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+{
+    pub fn with_genome(genome: &'a Genome<SelfAdaptiveGenotype<A, N>, A, RealGene<A, D>>) -> Self {
+        let n = N as f64;
+
+        Self {
+            genome,
+            tau: 1.0 / (2.0 * n.sqrt()).sqrt(),
+            tau_prime: 1.0 / (2.0 * n).sqrt(),
+            min_step_size: 1e-12,
+            statistics: OperatorStatistics::default(),
+        }
+    }
+
+    pub fn learning_rates(mut self, tau: f64, tau_prime: f64) -> Self {
+        self.tau = tau;
+        self.tau_prime = tau_prime;
+        self
+    }
+
+    pub fn min_step_size(mut self, min_step_size: f64) -> Self {
+        self.min_step_size = min_step_size;
+        self
+    }
+
+    pub fn statistics(&self) -> &OperatorStatistics {
+        &self.statistics
+    }
+}
+
+impl<'a, A, D, F, const N: usize> VariationOperator<SelfAdaptiveGenotype<A, N>, A, F>
+    for SelfAdaptiveMutation<'a, A, D, N>
+where
+    A: Allele + Real,
+    D: RealDomain<A>,
+    F: Fitness,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<SelfAdaptiveGenotype<A, N>, A, F>],
+        fitness_func: &FitnessFunc<'_, SelfAdaptiveGenotype<A, N>, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<Individual<SelfAdaptiveGenotype<A, N>, A, F>> {
+        mutate_population(
+            population,
+            fitness_func,
+            max_new_evaluations,
+            rng,
+            &self.statistics,
+            |rng, genotype| {
+                let mut child = genotype.clone();
+                let global: f64 = self.tau_prime * rng.sample::<f64, _>(StandardNormal);
+
+                for idx in 0..N {
+                    let local: f64 = self.tau * rng.sample::<f64, _>(StandardNormal);
+                    let step_size =
+                        (child.step_size(idx) * (global + local).exp()).max(self.min_step_size);
+                    child.set_step_size(idx, step_size);
+
+                    let step = A::from(step_size * rng.sample::<f64, _>(StandardNormal)).unwrap();
+                    let value = self.genome.get(idx).domain().clamp(child.get(idx) + step);
+                    child.set(idx, value);
+                }
+
+                child
+            },
+        )
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+}
+
+// Lanczos approximation of the gamma function
+fn gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 8] = [
+        676.5203681218851,
+        -1259.1392167224028,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507343278686905,
+        -0.13857109526572012,
+        9.984_369_578_019_572e-6,
+        1.5056327351493116e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        return PI / ((PI * x).sin() * gamma(1.0 - x));
+    }
+
+    let x = x - 1.0;
+    let t = x + COEFFICIENTS.len() as f64 - 0.5;
+    let sum = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(0.999_999_999_999_809_9, |acc, (i, c)| {
+            acc + c / (x + i as f64 + 1.0)
+        });
+
+    (2.0 * PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * sum
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerMutationMode {
+    // Replaces the allele by a uniformly random value from the domain
+    Uniform,
+    // Moves the allele at most `step` positions up or down in the (ordered) domain, which
+    // preserves locality
+    Creep { step: usize },
+}
+
+// Uniform mutation samples from the domain of each locus separately, so it applies to any discrete
+// genome, including ones with a different domain per locus (see `GenomeBuilder`)
+pub type UniformResetMutation<'a, Gnt, A, D> = IntegerMutation<'a, Gnt, A, D>;
+
+#[derive(Debug, Clone)]
+pub struct IntegerMutation<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A>,
+{
+    genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+    mode: IntegerMutationMode,
+    // The mutation probability of each locus
+    probabilities: Vec<f64>,
+    statistics: OperatorStatistics,
+}
+
+impl<'a, Gnt, A, D> IntegerMutation<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A>,
+{
+    // Mutates each gene with probability 1/l by default
+    pub fn with_genome(
+        genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+        mode: IntegerMutationMode,
+    ) -> Self {
+        if let IntegerMutationMode::Creep { step } = mode {
+            assert!(step > 0, "the creep step must be positive");
+        }
+
+        Self {
+            genome,
+            mode,
+            probabilities: vec![1.0 / genome.len() as f64; genome.len()],
+            statistics: OperatorStatistics::default(),
+        }
+    }
+
+    // Resets each mutated allele to a uniform sample from the domain of its locus
+    pub fn uniform_reset(genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>) -> Self {
+        Self::with_genome(genome, IntegerMutationMode::Uniform)
+    }
+
+    pub fn probability(mut self, probability: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "probability must be between 0 and 1"
+        );
+        self.probabilities = vec![probability; self.genome.len()];
+        self
+    }
+
+    // Mutates each locus with its own probability, e.g. to focus on loci that linkage analysis has
+    // identified as sensitive
+    pub fn per_locus_probabilities(mut self, probabilities: Vec<f64>) -> Self {
+        assert_eq!(
+            probabilities.len(),
+            self.genome.len(),
+            "there must be one probability per locus of the genome"
+        );
+        assert!(
+            probabilities.iter().all(|p| (0.0..=1.0).contains(p)),
+            "probabilities must be between 0 and 1"
+        );
+        self.probabilities = probabilities;
+        self
+    }
+
+    pub fn statistics(&self) -> &OperatorStatistics {
+        &self.statistics
+    }
+
+    fn mutate_allele<R>(&self, gene: &DiscreteGene<A, D>, allele: A, rng: &mut R) -> A
+    where
+        R: Rng + ?Sized,
+    {
+        match self.mode {
+            IntegerMutationMode::Uniform => gene.sample_uniform(rng),
+            IntegerMutationMode::Creep { step } => {
+                let domain = gene.domain();
+                let idx = domain.index_of(allele);
+                let delta = rng.gen_range(1..=step);
+
+                let new_idx = if rng.gen() {
+                    (idx + delta).min(domain.len() - 1)
+                } else {
+                    idx.saturating_sub(delta)
+                };
+
+                domain.get(new_idx)
+            }
+        }
+    }
+}
+
+impl<'a, Gnt, A, D, F> VariationOperator<Gnt, A, F> for IntegerMutation<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>> {
+        mutate_population(
+            population,
+            fitness_func,
+            max_new_evaluations,
+            rng,
+            &self.statistics,
+            |rng, genotype| {
+                genotype
+                    .iter()
+                    .zip(self.genome.iter())
+                    .zip(&self.probabilities)
+                    .map(|((allele, gene), probability)| {
+                        if rng.gen_bool(*probability) {
+                            self.mutate_allele(gene, allele, rng)
+                        } else {
+                            allele
+                        }
+                    })
+                    .collect_unsafe()
+            },
+        )
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+}
+
+// Structural mutation for variable-length sequences (e.g. architectures encoded as integer
+// vectors): with the given probability a locus sampled from the gene is inserted, and otherwise with
+// the same probability a locus is deleted. The remaining loci keep their alleles and order.
+pub fn structural_mutation<A, G, R>(
+    genotype: &[A],
+    gene: &G,
+    probability: f64,
+    rng: &mut R,
+) -> Vec<A>
+where
+    A: Allele,
+    G: Gene<A>,
+    R: Rng + ?Sized,
+{
+    assert!(
+        (0.0..=0.5).contains(&probability),
+        "the structural mutation probability must be in [0, 0.5]"
+    );
+
+    let mut child = genotype.to_vec();
+
+    let roll: f64 = rng.gen();
+    if roll < probability {
+        grow(&mut child, gene, rng);
+    } else if roll < 2.0 * probability {
+        shrink(&mut child, rng);
+    }
+
+    child
+}
+
+// Inserts a locus sampled from the gene at a random position (both ends included)
+pub fn grow<A, G, R>(genotype: &mut Vec<A>, gene: &G, rng: &mut R)
+where
+    A: Allele,
+    G: Gene<A>,
+    R: Rng + ?Sized,
+{
+    let idx = rng.gen_range(0..=genotype.len());
+    genotype.insert(idx, gene.sample_uniform(rng));
+}
+
+// Deletes a random locus, unless the genotype is empty
+pub fn shrink<A, R>(genotype: &mut Vec<A>, rng: &mut R)
+where
+    A: Allele,
+    R: Rng + ?Sized,
+{
+    if !genotype.is_empty() {
+        let idx = rng.gen_range(0..genotype.len());
+        genotype.remove(idx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        fitness::OptimizationGoal,
+        gene::{DisjointIntegralDomain, ExclusiveRangeRealDomain, InclusiveRangeRealDomain},
+        genome::GenomeBuilder,
+        genotype::DEFAULT_STEP_SIZE,
+        idom, rdom,
+        selection::TruncationSelection,
+        simplega::SimpleGABuilder,
+    };
+
+    const N: usize = 100;
+    type Gnt = [f64; N];
+
+    fn steps_from_origin<V>(operator: &mut V) -> Vec<f64>
+    where
+        V: VariationOperator<Gnt, f64, f64>,
+    {
+        let evaluation_func = |genotype: &Gnt| genotype.iter().sum::<f64>();
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+        let population = vec![Individual::from_genotype([0.0; N]); 100];
+
+        operator
+            .create_offspring(
+                &population,
+                &fitness_func,
+                None,
+                &mut SeededRng::from_entropy(),
+            )
+            .iter()
+            .flat_map(|child| child.genotype().iter().collect::<Vec<_>>())
+            .collect()
+    }
+
+    fn count_large_jumps(steps: &[f64]) -> usize {
+        steps.iter().filter(|step| step.abs() > 10.0).count()
+    }
+
+    #[test]
+    fn gamma_matches_known_values() {
+        assert_relative_eq!(gamma(1.0), 1.0, epsilon = 1e-10);
+        assert_relative_eq!(gamma(5.0), 24.0, epsilon = 1e-8);
+        assert_relative_eq!(gamma(0.5), PI.sqrt(), epsilon = 1e-10);
+    }
+
+    #[test]
+    fn heavy_tailed_mutations_produce_large_jumps() {
+        let genome = Genome::with_real_domain(&rdom!(-1e6..1e6));
+
+        let gaussian: Vec<f64> = (0..N * 100)
+            .map(|_| rand::thread_rng().sample(StandardNormal))
+            .collect();
+        let cauchy = steps_from_origin(&mut CauchyMutation::with_genome(&genome, 1.0));
+        let levy = steps_from_origin(&mut LevyMutation::with_genome(&genome, 1.5));
+
+        assert_eq!(count_large_jumps(&gaussian), 0);
+        assert!(count_large_jumps(&cauchy) > 0);
+        assert!(count_large_jumps(&levy) > 0);
+    }
+
+    #[test]
+    fn self_adaptive_step_sizes_shrink_on_sphere() {
+        const DIM: usize = 10;
+        type EsGnt = SelfAdaptiveGenotype<f64, DIM>;
+
+        let sphere = |genotype: &EsGnt| genotype.iter().map(|x| x * x).sum::<f64>();
+        let genome = Genome::with_real_domain(&rdom!(-5.0..5.0));
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&sphere)
+            .goal(OptimizationGoal::Minimize)
+            .selection(TruncationSelection::default())
+            .variation(SelfAdaptiveMutation::with_genome(&genome))
+            .build();
+
+        ga.run(20000);
+
+        let mean_step_size = ga
+            .iter()
+            .flat_map(|idv| idv.genotype().step_sizes().to_vec())
+            .sum::<f64>()
+            / (20 * DIM) as f64;
+
+        assert!(mean_step_size < 0.1 * DEFAULT_STEP_SIZE);
+        assert!(ga.best_individual().unwrap().fitness() < 1e-3);
+    }
+
+    #[test]
+    fn mutations_stay_within_domain() {
+        let genome = Genome::with_real_domain(&rdom!(-1.0..=1.0));
+
+        let steps = steps_from_origin(&mut CauchyMutation::with_genome(&genome, 10.0));
+
+        assert!(steps.iter().all(|step| (-1.0..=1.0).contains(step)));
+    }
+
+    fn mutated_alleles<D>(
+        genome: &Genome<[u8; 4], u8, DiscreteGene<u8, D>>,
+        mode: IntegerMutationMode,
+        parent: [u8; 4],
+    ) -> HashSet<u8>
+    where
+        D: DiscreteDomain<u8>,
+    {
+        let evaluation_func = |_: &[u8; 4]| 0usize;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+        let population = vec![Individual::from_genotype(parent); 200];
+
+        IntegerMutation::with_genome(genome, mode)
+            .probability(1.0)
+            .create_offspring(
+                &population,
+                &fitness_func,
+                None,
+                &mut SeededRng::from_entropy(),
+            )
+            .iter()
+            .flat_map(|child| child.genotype().iter().collect::<Vec<_>>())
+            .collect()
+    }
+
+    #[test]
+    fn uniform_integer_mutation_reaches_every_value() {
+        let genome = Genome::with_discrete_domain(&idom!(0..5));
+
+        let alleles = mutated_alleles(&genome, IntegerMutationMode::Uniform, [2; 4]);
+
+        assert_eq!(alleles, (0..5).collect());
+    }
+
+    #[test]
+    fn creep_mutation_only_moves_to_neighbours() {
+        let genome = Genome::with_discrete_domain(&idom!(1, 3, 5, 7, 9));
+
+        let alleles = mutated_alleles(&genome, IntegerMutationMode::Creep { step: 1 }, [5; 4]);
+
+        assert_eq!(alleles, HashSet::from([3, 7]));
+    }
+
+    #[test]
+    fn per_locus_probabilities_control_which_loci_mutate() {
+        let genome = Genome::with_discrete_domain(&idom!(0..10));
+        let evaluation_func = |_: &[u8; 4]| 0usize;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+        let population = vec![Individual::from_genotype([5; 4]); 200];
+
+        let offspring =
+            IntegerMutation::with_genome(&genome, IntegerMutationMode::Creep { step: 1 })
+                .per_locus_probabilities(vec![1.0, 0.0, 0.5, 0.0])
+                .create_offspring(
+                    &population,
+                    &fitness_func,
+                    None,
+                    &mut SeededRng::from_entropy(),
+                );
+
+        assert!(offspring.iter().all(|child| child.genotype()[0] != 5));
+        assert!(offspring.iter().all(|child| child.genotype()[1] == 5));
+        assert!(offspring.iter().all(|child| child.genotype()[3] == 5));
+    }
+
+    #[test]
+    fn mutation_statistics_count_changed_children() {
+        const POPULATION_SIZE: usize = 20;
+        const GENERATIONS: usize = 5;
+
+        let genome = Genome::with_discrete_domain(&idom!(0..10));
+        let evaluation_func = |genotype: &[u8; 4]| genotype.iter().map(usize::from).sum::<usize>();
+
+        let run = |probability: f64| {
+            let mut ga = SimpleGABuilder::new()
+                .genome(&genome)
+                .random_population(POPULATION_SIZE)
+                .evaluation_function(&evaluation_func)
+                .goal(OptimizationGoal::Maximize)
+                .selection(TruncationSelection::default())
+                .variation(IntegerMutation::uniform_reset(&genome).probability(probability))
+                .seed(0)
+                .build();
+            ga.run(POPULATION_SIZE * (GENERATIONS + 1));
+
+            let statistics = ga.variation_operator().statistics();
+            (
+                statistics.offspring_produced(),
+                statistics.effective_offspring(),
+            )
+        };
+
+        assert_eq!(run(0.0), (POPULATION_SIZE * GENERATIONS, 0));
+
+        // Resetting every locus rarely reproduces the parent
+        let (produced, effective) = run(1.0);
+        assert_eq!(produced, POPULATION_SIZE * GENERATIONS);
+        assert!(effective > produced * 9 / 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "one probability per locus")]
+    fn per_locus_probabilities_must_match_genome() {
+        let genome: Genome<[u8; 4], _, _> = Genome::with_discrete_domain(&idom!(0..10));
+
+        IntegerMutation::with_genome(&genome, IntegerMutationMode::Uniform)
+            .per_locus_probabilities(vec![0.5; 3]);
+    }
+
+    #[test]
+    fn uniform_reset_respects_per_locus_domains() {
+        let domains = [idom!(0..3), idom!(10..=12), idom!(100, 200), idom!(7)];
+        let genome: Genome<[u8; 4], _, _> = domains
+            .iter()
+            .fold(GenomeBuilder::new(), |builder, domain| {
+                builder.push_gene(DiscreteGene::with_domain(domain))
+            })
+            .build();
+
+        let evaluation_func = |_: &[u8; 4]| 0usize;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+        let parent = [1, 11, 100, 7];
+        let population = vec![Individual::from_genotype(parent); 200];
+
+        let offspring = UniformResetMutation::uniform_reset(&genome)
+            .per_locus_probabilities(vec![1.0, 1.0, 0.0, 1.0])
+            .create_offspring(
+                &population,
+                &fitness_func,
+                None,
+                &mut SeededRng::seed_from_u64(5),
+            );
+
+        for child in &offspring {
+            for (locus, allele) in child.genotype().iter().enumerate() {
+                assert!(domains[locus].iter().any(|value| value == allele));
+            }
+            assert_eq!(child.genotype()[2], parent[2]);
+        }
+
+        let reset_values: HashSet<_> = offspring.iter().map(|child| child.genotype()[1]).collect();
+        assert_eq!(reset_values, HashSet::from([10, 11, 12]));
+    }
+
+    // Whether `short` can be obtained by deleting elements from `long`
+    fn is_subsequence(short: &[u8], long: &[u8]) -> bool {
+        let mut long = long.iter();
+        short.iter().all(|allele| long.any(|other| other == allele))
+    }
+
+    #[test]
+    fn structural_mutation_changes_length_and_keeps_loci_valid() {
+        let gene = DiscreteGene::with_domain(&idom!(1..5));
+        let mut rng = SeededRng::seed_from_u64(0);
+
+        let mut genotype: Vec<u8> = (0..8).map(|_| gene.sample_uniform(&mut rng)).collect();
+        let mut lengths = HashSet::new();
+
+        for _ in 0..200 {
+            let child = structural_mutation(&genotype, &gene, 0.25, &mut rng);
+
+            match child.len() as isize - genotype.len() as isize {
+                1 => assert!(is_subsequence(&genotype, &child)),
+                -1 => assert!(is_subsequence(&child, &genotype)),
+                0 => assert_eq!(child, genotype),
+                _ => panic!("more than one locus was inserted or deleted"),
+            }
+            assert!(child.iter().all(|&allele| gene.contains(allele)));
+
+            lengths.insert(child.len());
+            genotype = child;
+        }
+
+        assert!(lengths.len() > 2);
+    }
+
+    #[test]
+    fn shrinking_empty_genotype_is_noop() {
+        let mut genotype: Vec<u8> = Vec::new();
+        shrink(&mut genotype, &mut SeededRng::seed_from_u64(0));
+        assert!(genotype.is_empty());
+    }
+}
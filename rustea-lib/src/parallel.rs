@@ -0,0 +1,141 @@
+// Parallel iteration is provided by rayon when the `parallel` feature is enabled. Otherwise,
+// the same methods are provided by serial iterators, such that operators can be written once.
+
+#[cfg(feature = "parallel")]
+pub use rayon::prelude::*;
+
+#[cfg(not(feature = "parallel"))]
+pub use self::serial::*;
+
+#[cfg(not(feature = "parallel"))]
+mod serial {
+    pub trait IntoParallelIterator {
+        type Iter: Iterator<Item = Self::Item>;
+        type Item;
+
+        fn into_par_iter(self) -> Self::Iter;
+    }
+
+    impl<I> IntoParallelIterator for I
+    where
+        I: IntoIterator,
+    {
+        type Iter = I::IntoIter;
+        type Item = I::Item;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub trait IntoParallelRefIterator<'a> {
+        type Iter: Iterator<Item = Self::Item>;
+        type Item: 'a;
+
+        fn par_iter(&'a self) -> Self::Iter;
+    }
+
+    impl<'a, T> IntoParallelRefIterator<'a> for T
+    where
+        T: 'a + ?Sized,
+        &'a T: IntoIterator,
+    {
+        type Iter = <&'a T as IntoIterator>::IntoIter;
+        type Item = <&'a T as IntoIterator>::Item;
+
+        fn par_iter(&'a self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub trait ParallelIterator: Iterator + Sized {
+        fn map_init<I, T, F, R>(self, init: I, map_op: F) -> MapInit<Self, T, F>
+        where
+            I: Fn() -> T,
+            F: FnMut(&mut T, Self::Item) -> R,
+        {
+            MapInit {
+                iter: self,
+                state: init(),
+                map_op,
+            }
+        }
+
+        fn flat_map_iter<F, U>(self, map_op: F) -> std::iter::FlatMap<Self, U, F>
+        where
+            F: FnMut(Self::Item) -> U,
+            U: IntoIterator,
+        {
+            self.flat_map(map_op)
+        }
+    }
+
+    // Serially, the state is initialized only once and shared by all items
+    pub struct MapInit<I, T, F> {
+        iter: I,
+        state: T,
+        map_op: F,
+    }
+
+    impl<I, T, F, R> Iterator for MapInit<I, T, F>
+    where
+        I: Iterator,
+        F: FnMut(&mut T, I::Item) -> R,
+    {
+        type Item = R;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let item = self.iter.next()?;
+            Some((self.map_op)(&mut self.state, item))
+        }
+    }
+
+    impl<I> ParallelIterator for I where I: Iterator {}
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    use super::*;
+    use crate::{
+        gene::{DiscreteDomain, DisjointIntegralDomain},
+        genome::Genome,
+        idom,
+        rng::{RngGenerator, XoshiroRngGenerator},
+    };
+
+    #[test]
+    fn seeded_batch_matches_serial_reference() {
+        const SEED: u64 = 42;
+        const COUNT: usize = 1000;
+        let genome: Genome<[u8; 16], _, _> = Genome::with_discrete_domain(&idom!(0..10));
+
+        let batch =
+            genome.random_genotype_batch(&mut Xoshiro256PlusPlus::seed_from_u64(SEED), COUNT);
+
+        // Reproduce the batch serially, chunk by chunk
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(SEED);
+        let mut generator =
+            XoshiroRngGenerator::<Xoshiro256PlusPlus>::from_seed(rand::Rng::gen(&mut rng));
+        let mut reference = Vec::with_capacity(COUNT);
+        while reference.len() < COUNT {
+            let mut chunk_rng = generator.next();
+            let n = (COUNT - reference.len()).min(256);
+            reference.extend((0..n).map(|_| genome.sample_uniform(&mut chunk_rng)));
+        }
+
+        assert_eq!(batch, reference);
+    }
+
+    #[test]
+    fn map_init_preserves_order() {
+        let squares: Vec<_> = (0..100usize)
+            .into_par_iter()
+            .map_init(|| 2, |factor, x| x * x * *factor)
+            .collect();
+
+        assert_eq!(squares, (0..100).map(|x| 2 * x * x).collect::<Vec<_>>());
+    }
+}
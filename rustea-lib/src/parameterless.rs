@@ -0,0 +1,263 @@
+use crate::{
+    fitness::{Fitness, OptimizationGoal},
+    gene::{Allele, Gene},
+    genotype::Genotype,
+    individual::Individual,
+    selection::SelectionOperator,
+    simplega::{SimpleGA, SimpleGABuilder, Status},
+    statistics::StatsCollector,
+    variation::VariationOperator,
+};
+
+// Parameterless GA (Harik & Lobo): runs a series of GAs with geometrically increasing
+// population sizes, where each population performs one generation for every `multiplier`
+// generations of the next smaller population. A population is terminated as soon as a
+// larger population has a better average fitness.
+pub struct ParameterlessGA<'a, Gnt, A, G, F, S, V, C>
+where
+    A: Allele,
+    G: Gene<A>,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    builder: SimpleGABuilder<'a, Gnt, A, G, F, S, V, C>,
+    initial_population_size: usize,
+    multiplier: usize,
+    populations: Vec<Option<SimpleGA<'a, Gnt, A, F, S, V, C>>>,
+    generations: Vec<usize>,
+    terminated_evaluations: usize,
+}
+
+impl<'a, Gnt, A, G, F, S, V, C> ParameterlessGA<'a, Gnt, A, G, F, S, V, C>
+where
+    A: Allele,
+    G: Gene<A>,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    // The builder must be fully configured, except for the population
+    pub fn with_builder(builder: SimpleGABuilder<'a, Gnt, A, G, F, S, V, C>) -> Self {
+        Self {
+            builder,
+            initial_population_size: 4,
+            multiplier: 4,
+            populations: Vec::new(),
+            generations: Vec::new(),
+            terminated_evaluations: 0,
+        }
+    }
+
+    pub fn initial_population_size(mut self, size: usize) -> Self {
+        self.initial_population_size = size;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: usize) -> Self {
+        assert!(multiplier >= 2, "the multiplier must be at least 2");
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.terminated_evaluations
+            + self
+                .populations
+                .iter()
+                .flatten()
+                .map(|ga| ga.evaluations())
+                .sum::<usize>()
+    }
+
+    // The sizes of the populations that have not been terminated
+    pub fn active_population_sizes(&self) -> Vec<usize> {
+        self.populations
+            .iter()
+            .enumerate()
+            .filter(|(_, ga)| ga.is_some())
+            .map(|(idx, _)| self.population_size(idx))
+            .collect()
+    }
+
+    pub fn best_individual(&self) -> Option<&Individual<Gnt, A, F>> {
+        self.populations
+            .iter()
+            .flatten()
+            .filter_map(|ga| ga.best_individual().map(|idv| (ga, idv)))
+            .min_by(|(ga, idv_a), (_, idv_b)| {
+                ga.fitness_func().cmp(&idv_a.fitness(), &idv_b.fitness())
+            })
+            .map(|(_, idv)| idv)
+    }
+
+    fn population_size(&self, idx: usize) -> usize {
+        self.initial_population_size << idx
+    }
+
+    // Spawns the next larger population, unless evaluating it would exceed the remaining budget
+    fn spawn_population(&mut self, remaining: usize) -> bool {
+        let idx = self.populations.len();
+        let mut ga = self
+            .builder
+            .clone()
+            .random_population(self.population_size(idx))
+            .derive_seed(idx as u64)
+            .build();
+
+        if ga.population().len() * ga.fitness_func().evaluation_cost() > remaining {
+            return false;
+        }
+        ga.initialize();
+
+        self.populations.push(Some(ga));
+        self.generations.push(0);
+        true
+    }
+
+    // Terminates every population smaller than `idx` whose average fitness is worse
+    fn terminate_surpassed(&mut self, idx: usize) {
+        let Some(ga) = &self.populations[idx] else {
+            return;
+        };
+
+        let mean = ga.mean_fitness();
+        let goal = ga.goal().clone();
+        let epsilon = ga.improvement_epsilon();
+
+        let Some(surpassed) = (0..idx).rev().find(|smaller| {
+            self.populations[*smaller]
+                .as_ref()
+                .is_some_and(|other| is_better(&goal, mean, other.mean_fitness(), epsilon))
+        }) else {
+            return;
+        };
+
+        for smaller in 0..=surpassed {
+            if let Some(ga) = self.populations[smaller].take() {
+                self.terminated_evaluations += ga.evaluations();
+            }
+        }
+    }
+
+    pub fn run(&mut self, evaluation_budget: usize) -> Status {
+        loop {
+            // Advance the smallest population; every `multiplier` generations, the next larger one
+            let mut idx = self
+                .populations
+                .iter()
+                .position(|ga| ga.is_some())
+                .unwrap_or(self.populations.len());
+
+            loop {
+                let remaining = evaluation_budget.saturating_sub(self.evaluations());
+
+                if idx == self.populations.len() && !self.spawn_population(remaining) {
+                    // The smaller populations continue with the budget that is left, if any
+                    if self.populations.iter().all(Option::is_none) {
+                        return Status::BudgetReached(self.evaluations());
+                    }
+                    break;
+                }
+
+                let ga = self.populations[idx].as_mut().unwrap();
+
+                if ga.target_reached() {
+                    return Status::TargetReached(self.evaluations());
+                }
+
+                // Never exceed the budget in the final generation
+                let affordable = remaining / ga.fitness_func().evaluation_cost();
+                if affordable == 0 {
+                    return Status::BudgetReached(self.evaluations());
+                }
+
+                ga.step_within(Some(affordable));
+                self.generations[idx] += 1;
+
+                // Before any population is terminated, as it may be this one
+                if ga.target_reached() {
+                    return Status::TargetReached(self.evaluations());
+                }
+
+                self.terminate_surpassed(idx);
+
+                if self.evaluations() >= evaluation_budget {
+                    return Status::BudgetReached(self.evaluations());
+                }
+
+                if self.generations[idx] % self.multiplier != 0 {
+                    break;
+                }
+
+                idx += 1;
+            }
+        }
+    }
+}
+
+// Whether `a` is better than `b` by more than `epsilon`
+fn is_better(goal: &OptimizationGoal, a: f64, b: f64, epsilon: f64) -> bool {
+    match goal.primary() {
+        OptimizationGoal::Maximize => a > b + epsilon,
+        _ => a < b - epsilon,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{genome::Genome, selection::TruncationSelection, variation::UniformCrossover};
+
+    const N: usize = 20;
+    type Gnt = [bool; N];
+
+    fn one_max(genotype: &Gnt) -> usize {
+        genotype.iter().filter(|bit| *bit).count()
+    }
+
+    #[test]
+    fn parameterless_ga_solves_one_max() {
+        let genome = Genome::with_bool_domain();
+
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .target(N);
+
+        let mut ga = ParameterlessGA::with_builder(builder);
+        let status = ga.run(1_000_000);
+
+        assert!(matches!(status, Status::TargetReached(_)));
+        assert_eq!(ga.best_individual().unwrap().fitness(), N);
+    }
+
+    #[test]
+    fn parameterless_ga_does_not_exceed_budget() {
+        let genome = Genome::with_bool_domain();
+
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .target(N + 1)
+            .seed(0);
+
+        for budget in [3, 50, 1_000, 4_321] {
+            let mut ga = ParameterlessGA::with_builder(builder.clone());
+            assert!(
+                matches!(ga.run(budget), Status::BudgetReached(evaluations) if evaluations <= budget)
+            );
+            assert!(ga.evaluations() <= budget);
+        }
+    }
+}
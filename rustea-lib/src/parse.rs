@@ -0,0 +1,384 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::{self, BufRead, BufReader, Read, Write},
+    str::FromStr,
+};
+
+use crate::{
+    fitness::Fitness,
+    gene::{Allele, Discrete, DiscreteDomain, DiscreteGene},
+    genome::Genome,
+    genotype::Genotype,
+    individual::Individual,
+    types::CollectUnsafe,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseGenotypeError {
+    // The number of alleles does not match the length of the genome
+    Length { expected: usize, found: usize },
+    // The allele at `index` could not be parsed or is not in the domain of its gene
+    Allele { index: usize, text: String },
+}
+
+impl Display for ParseGenotypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseGenotypeError::Length { expected, found } => {
+                write!(f, "expected {} alleles, found {}", expected, found)
+            }
+            ParseGenotypeError::Allele { index, text } => {
+                write!(f, "invalid allele '{}' at locus {}", text, index)
+            }
+        }
+    }
+}
+
+impl Error for ParseGenotypeError {}
+
+#[derive(Debug)]
+pub enum LoadPopulationError {
+    Io(io::Error),
+    // The genotype on the (one-based) `line` could not be parsed
+    Genotype {
+        line: usize,
+        error: ParseGenotypeError,
+    },
+    // The fitness on the (one-based) `line` could not be parsed
+    Fitness {
+        line: usize,
+        text: String,
+    },
+    // A row has more fields than a genotype and a fitness
+    Fields {
+        line: usize,
+        found: usize,
+    },
+}
+
+impl Display for LoadPopulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadPopulationError::Io(error) => write!(f, "{}", error),
+            LoadPopulationError::Genotype { line, error } => {
+                write!(f, "line {}: {}", line, error)
+            }
+            LoadPopulationError::Fitness { line, text } => {
+                write!(f, "line {}: invalid fitness '{}'", line, text)
+            }
+            LoadPopulationError::Fields { line, found } => {
+                write!(
+                    f,
+                    "line {}: expected at most 2 fields, found {}",
+                    line, found
+                )
+            }
+        }
+    }
+}
+
+impl Error for LoadPopulationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadPopulationError::Io(error) => Some(error),
+            LoadPopulationError::Genotype { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadPopulationError {
+    fn from(error: io::Error) -> Self {
+        LoadPopulationError::Io(error)
+    }
+}
+
+// Parses a bit string such as "1011"
+pub fn parse_bool_genotype<Gnt, D>(
+    genome: &Genome<Gnt, bool, DiscreteGene<bool, D>>,
+    text: &str,
+) -> Result<Gnt, ParseGenotypeError>
+where
+    D: DiscreteDomain<bool>,
+    Gnt: Genotype<bool>,
+{
+    let text = text.trim();
+    check_length(genome.len(), text.chars().count())?;
+
+    let alleles = text
+        .chars()
+        .enumerate()
+        .map(|(index, c)| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(ParseGenotypeError::Allele {
+                index,
+                text: c.to_string(),
+            }),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(alleles.into_iter().collect_unsafe())
+}
+
+// Parses a comma-separated list of integers such as "0,3,1"
+pub fn parse_int_genotype<Gnt, A, D>(
+    genome: &Genome<Gnt, A, DiscreteGene<A, D>>,
+    text: &str,
+) -> Result<Gnt, ParseGenotypeError>
+where
+    A: Allele + Discrete + FromStr,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A>,
+{
+    let parts: Vec<_> = text.split(',').map(str::trim).collect();
+    check_length(genome.len(), parts.len())?;
+
+    let alleles = parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, part)| {
+            part.parse::<A>()
+                .ok()
+                .filter(|allele| genome.get(index).domain().iter().any(|a| a == *allele))
+                .ok_or_else(|| ParseGenotypeError::Allele {
+                    index,
+                    text: part.to_string(),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(alleles.into_iter().collect_unsafe())
+}
+
+pub fn format_bool_genotype<Gnt>(genotype: &Gnt) -> String
+where
+    Gnt: Genotype<bool>,
+{
+    genotype
+        .iter()
+        .map(|bit| if bit { '1' } else { '0' })
+        .collect()
+}
+
+pub fn format_int_genotype<Gnt, A>(genotype: &Gnt) -> String
+where
+    A: Allele + Display,
+    Gnt: Genotype<A>,
+{
+    genotype
+        .iter()
+        .map(|allele| allele.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// Writes one row per individual: its formatted genotype, followed by its fitness if it has been
+// evaluated. Use ',' as the delimiter for CSV or '\t' for TSV; genotypes that contain the
+// delimiter (e.g. comma-separated integers in a CSV) are quoted.
+pub fn save_population_csv<W, Gnt, A, F, Fmt>(
+    writer: W,
+    population: &[Individual<Gnt, A, F>],
+    delimiter: char,
+    format: Fmt,
+) -> io::Result<()>
+where
+    W: Write,
+    A: Allele,
+    F: Fitness + Display,
+    Gnt: Genotype<A>,
+    Fmt: Fn(&Gnt) -> String,
+{
+    let mut writer = io::BufWriter::new(writer);
+
+    for individual in population {
+        let genotype = format(individual.genotype());
+        if genotype.contains(delimiter) {
+            write!(writer, "\"{}\"", genotype)?;
+        } else {
+            write!(writer, "{}", genotype)?;
+        }
+
+        if individual.is_evaluated() {
+            write!(writer, "{}{}", delimiter, individual.fitness())?;
+        }
+        writeln!(writer)?;
+    }
+
+    writer.flush()
+}
+
+// Reads a population written by `save_population_csv`, parsing the genotypes with `parse` (e.g.
+// `parse_bool_genotype`). Individuals without a fitness column are left unevaluated, and empty
+// lines are skipped.
+pub fn load_population_csv<R, Gnt, A, F, P>(
+    reader: R,
+    delimiter: char,
+    parse: P,
+) -> Result<Vec<Individual<Gnt, A, F>>, LoadPopulationError>
+where
+    R: Read,
+    A: Allele,
+    F: Fitness + FromStr,
+    Gnt: Genotype<A>,
+    P: Fn(&str) -> Result<Gnt, ParseGenotypeError>,
+{
+    let mut population = Vec::new();
+
+    for (index, row) in BufReader::new(reader).lines().enumerate() {
+        let row = row?;
+        let line = index + 1;
+        if row.trim().is_empty() {
+            continue;
+        }
+
+        let (genotype, rest) = match row.strip_prefix('"').and_then(|row| row.split_once('"')) {
+            Some((genotype, rest)) => (genotype, rest.strip_prefix(delimiter).unwrap_or(rest)),
+            None => row.split_once(delimiter).unwrap_or((&row, "")),
+        };
+
+        let genotype =
+            parse(genotype).map_err(|error| LoadPopulationError::Genotype { line, error })?;
+        let mut individual = Individual::from_genotype(genotype);
+
+        let fields: Vec<_> = rest.split(delimiter).map(str::trim).collect();
+        match fields[..] {
+            [""] => (),
+            [fitness] => {
+                let fitness = fitness.parse().map_err(|_| LoadPopulationError::Fitness {
+                    line,
+                    text: fitness.to_string(),
+                })?;
+                individual.set_fitness(fitness);
+            }
+            _ => {
+                return Err(LoadPopulationError::Fields {
+                    line,
+                    found: fields.len() + 1,
+                })
+            }
+        }
+
+        population.push(individual);
+    }
+
+    Ok(population)
+}
+
+fn check_length(expected: usize, found: usize) -> Result<(), ParseGenotypeError> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(ParseGenotypeError::Length { expected, found })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{gene::DisjointIntegralDomain, idom, rng::SeededRng};
+
+    #[test]
+    fn bool_genotype_round_trip() {
+        let genome: Genome<[bool; 6], _, _> = Genome::with_bool_domain();
+
+        let genotype = parse_bool_genotype(&genome, "101101").unwrap();
+
+        assert_eq!(genotype, [true, false, true, true, false, true]);
+        assert_eq!(format_bool_genotype(&genotype), "101101");
+        assert_eq!(
+            parse_bool_genotype(&genome, "1011"),
+            Err(ParseGenotypeError::Length {
+                expected: 6,
+                found: 4
+            })
+        );
+        assert!(parse_bool_genotype(&genome, "10x101").is_err());
+    }
+
+    #[test]
+    fn int_genotype_round_trip() {
+        let genome: Genome<[u8; 3], _, _> = Genome::with_discrete_domain(&idom!(0..4));
+
+        let genotype = parse_int_genotype(&genome, "0, 3,1").unwrap();
+
+        assert_eq!(genotype, [0, 3, 1]);
+        assert_eq!(format_int_genotype(&genotype), "0,3,1");
+        assert_eq!(
+            parse_int_genotype(&genome, &format_int_genotype(&genotype)),
+            Ok(genotype)
+        );
+        assert_eq!(
+            parse_int_genotype(&genome, "0,4,1"),
+            Err(ParseGenotypeError::Allele {
+                index: 1,
+                text: "4".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn bool_population_csv_round_trip() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let genome: Genome<[bool; 8], _, _> = Genome::with_bool_domain();
+
+        let mut population: Vec<Individual<_, _, f64>> = genome
+            .random_genotype_batch(&mut rng, 10)
+            .into_iter()
+            .map(Individual::from_genotype)
+            .collect();
+        for (i, individual) in population.iter_mut().enumerate().skip(2) {
+            individual.set_fitness(i as f64 - 2.5);
+        }
+
+        let mut csv = Vec::new();
+        save_population_csv(&mut csv, &population, ',', format_bool_genotype).unwrap();
+        let loaded: Vec<Individual<_, _, f64>> = load_population_csv(csv.as_slice(), ',', |text| {
+            parse_bool_genotype(&genome, text)
+        })
+        .unwrap();
+
+        assert_eq!(loaded.len(), population.len());
+        for (loaded, original) in loaded.iter().zip(&population) {
+            assert_eq!(loaded.genotype(), original.genotype());
+            assert_eq!(loaded.is_evaluated(), original.is_evaluated());
+            if original.is_evaluated() {
+                assert_eq!(loaded.fitness(), original.fitness());
+            }
+        }
+    }
+
+    #[test]
+    fn int_population_csv_and_tsv() {
+        let genome: Genome<[u8; 3], _, _> = Genome::with_discrete_domain(&idom!(0..4));
+        let parse = |text: &str| parse_int_genotype(&genome, text);
+
+        let mut individual = Individual::from_genotype([0, 3, 1]);
+        individual.set_fitness(7);
+        let population = vec![individual, Individual::from_genotype([2, 2, 0])];
+
+        for (delimiter, expected) in [
+            (',', "\"0,3,1\",7\n\"2,2,0\"\n"),
+            ('\t', "0,3,1\t7\n2,2,0\n"),
+        ] {
+            let mut text = Vec::new();
+            save_population_csv(&mut text, &population, delimiter, format_int_genotype).unwrap();
+            assert_eq!(String::from_utf8(text.clone()).unwrap(), expected);
+
+            let loaded: Vec<Individual<_, _, usize>> =
+                load_population_csv(text.as_slice(), delimiter, parse).unwrap();
+            assert_eq!(loaded[0].genotype(), &[0, 3, 1]);
+            assert_eq!(loaded[0].fitness(), 7);
+            assert!(!loaded[1].is_evaluated());
+        }
+
+        let error = load_population_csv::<_, _, _, usize, _>("0,3,1\tx\n".as_bytes(), '\t', parse);
+        assert!(matches!(
+            error,
+            Err(LoadPopulationError::Fitness { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,540 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    distance::{DistanceMetric, ExactEquality, GenotypeEquality, Hamming},
+    fitness::{Fitness, FitnessFunc},
+    gene::Allele,
+    genotype::Genotype,
+    individual::Individual,
+};
+
+// A collection of individuals, with the common queries over their fitnesses and genotypes
+#[derive(Debug, Clone)]
+pub struct Population<Gnt, A, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    individuals: Vec<Individual<Gnt, A, F>>,
+}
+
+impl<Gnt, A, F> Population<Gnt, A, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    pub fn into_inner(self) -> Vec<Individual<Gnt, A, F>> {
+        self.individuals
+    }
+
+    pub fn best(
+        &self,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) -> Option<&Individual<Gnt, A, F>> {
+        self.individuals
+            .iter()
+            .min_by(|idv_a, idv_b| fitness_func.cmp(&idv_a.fitness(), &idv_b.fitness()))
+    }
+
+    pub fn worst(
+        &self,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) -> Option<&Individual<Gnt, A, F>> {
+        self.individuals
+            .iter()
+            .max_by(|idv_a, idv_b| fitness_func.cmp(&idv_a.fitness(), &idv_b.fitness()))
+    }
+
+    pub fn mean_fitness(&self) -> f64 {
+        self.individuals
+            .iter()
+            .map(|idv| idv.fitness().as_f64())
+            .sum::<f64>()
+            / self.individuals.len() as f64
+    }
+
+    // Sorts the individuals from best to worst
+    pub fn sort_by_fitness(&mut self, fitness_func: &FitnessFunc<'_, Gnt, A, F>) {
+        self.individuals
+            .sort_by(|idv_a, idv_b| fitness_func.cmp(&idv_a.fitness(), &idv_b.fitness()));
+    }
+
+    // Inserts an individual into a population sorted from best to worst, keeping it sorted. The
+    // position is found by binary search, so (e.g. in steady-state generations) the population does
+    // not have to be re-sorted after every insertion. Equal fitnesses keep their insertion order,
+    // as with `sort_by_fitness`.
+    pub fn insert_sorted(
+        &mut self,
+        individual: Individual<Gnt, A, F>,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) {
+        let idx = self.individuals.partition_point(|idv| {
+            fitness_func
+                .cmp(&idv.fitness(), &individual.fitness())
+                .is_le()
+        });
+
+        self.individuals.insert(idx, individual);
+    }
+
+    // Replaces the worst individual of a sorted population if the new individual is strictly
+    // better, keeping the population sorted. Returns the individual that did not survive.
+    pub fn replace_worst_sorted(
+        &mut self,
+        individual: Individual<Gnt, A, F>,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) -> Option<Individual<Gnt, A, F>> {
+        let worst = self.individuals.last()?;
+
+        if fitness_func
+            .cmp(&individual.fitness(), &worst.fitness())
+            .is_lt()
+        {
+            let worst = self.individuals.pop();
+            self.insert_sorted(individual, fitness_func);
+            worst
+        } else {
+            Some(individual)
+        }
+    }
+
+    // Removes every individual whose genotype already occurred earlier in the population
+    pub fn dedup(&mut self) {
+        self.dedup_by(&ExactEquality)
+    }
+
+    // As `dedup`, where genotypes are compared by the given equality (e.g. up to an epsilon)
+    pub fn dedup_by<E>(&mut self, equality: &E)
+    where
+        E: GenotypeEquality<Gnt, A>,
+    {
+        let mut unique: Vec<Individual<Gnt, A, F>> = Vec::with_capacity(self.individuals.len());
+
+        for idv in self.individuals.drain(..) {
+            if !unique
+                .iter()
+                .any(|other| equality.equal(other.genotype(), idv.genotype()))
+            {
+                unique.push(idv);
+            }
+        }
+
+        self.individuals = unique;
+    }
+
+    // Merges the populations of separate runs (e.g. with different operators) into the `size` best
+    // distinct individuals, e.g. to seed another run. The runs may have used different fitness
+    // functions, so the distinct genotypes are evaluated again with the shared fitness function.
+    pub fn merge(
+        a: &[Individual<Gnt, A, F>],
+        b: &[Individual<Gnt, A, F>],
+        size: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) -> Self {
+        Self::merge_by(a, b, size, fitness_func, &ExactEquality)
+    }
+
+    // As `merge`, where duplicates are detected by the given equality
+    pub fn merge_by<E>(
+        a: &[Individual<Gnt, A, F>],
+        b: &[Individual<Gnt, A, F>],
+        size: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        equality: &E,
+    ) -> Self
+    where
+        E: GenotypeEquality<Gnt, A>,
+    {
+        let mut merged: Self = a
+            .iter()
+            .chain(b)
+            .map(|idv| Individual::from_genotype(idv.genotype().clone()))
+            .collect();
+        merged.dedup_by(equality);
+
+        fitness_func.evaluate_all(&mut merged);
+        merged.sort_by_fitness(fitness_func);
+        merged.truncate(size);
+
+        merged
+    }
+
+    // Whether every individual has the same genotype
+    pub fn is_converged(&self) -> bool {
+        self.is_converged_by(&ExactEquality)
+    }
+
+    pub fn is_converged_by<E>(&self, equality: &E) -> bool
+    where
+        E: GenotypeEquality<Gnt, A>,
+    {
+        let Some((first, rest)) = self.individuals.split_first() else {
+            return true;
+        };
+
+        rest.iter()
+            .all(|idv| equality.equal(idv.genotype(), first.genotype()))
+    }
+
+    // The mean pairwise Hamming distance, as a fraction of the genotype length; 0 for a fully
+    // converged population
+    pub fn diversity(&self) -> f64 {
+        let n = self.individuals.len();
+        if n < 2 {
+            return 0.0;
+        }
+
+        let total: f64 = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                Hamming.distance(
+                    self.individuals[i].genotype(),
+                    self.individuals[j].genotype(),
+                )
+            })
+            .sum();
+
+        let pairs = (n * (n - 1) / 2) as f64;
+        total / pairs / Gnt::LEN as f64
+    }
+
+    // Groups the individuals into clusters around the best individuals, e.g. to report the distinct
+    // optima of a niched population. Going from best to worst, an individual joins the first
+    // cluster whose representative lies within `distance_threshold`, or otherwise becomes the
+    // representative of a new cluster. Each cluster is ordered from best to worst, so that its
+    // first individual is the representative, and the clusters are ordered by their
+    // representatives.
+    pub fn clusters<M>(
+        &self,
+        metric: &M,
+        distance_threshold: f64,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) -> Vec<Vec<&Individual<Gnt, A, F>>>
+    where
+        M: DistanceMetric<Gnt, A>,
+    {
+        let mut sorted: Vec<_> = self.individuals.iter().collect();
+        sorted.sort_by(|a, b| fitness_func.cmp(&a.fitness(), &b.fitness()));
+
+        let mut clusters: Vec<Vec<&Individual<Gnt, A, F>>> = Vec::new();
+        for idv in sorted {
+            match clusters.iter_mut().find(|cluster| {
+                metric.distance(cluster[0].genotype(), idv.genotype()) <= distance_threshold
+            }) {
+                Some(cluster) => cluster.push(idv),
+                None => clusters.push(vec![idv]),
+            }
+        }
+
+        clusters
+    }
+}
+
+impl<Gnt, A, F> From<Vec<Individual<Gnt, A, F>>> for Population<Gnt, A, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn from(individuals: Vec<Individual<Gnt, A, F>>) -> Self {
+        Self { individuals }
+    }
+}
+
+impl<Gnt, A, F> FromIterator<Individual<Gnt, A, F>> for Population<Gnt, A, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn from_iter<T: IntoIterator<Item = Individual<Gnt, A, F>>>(iter: T) -> Self {
+        Self {
+            individuals: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<Gnt, A, F> Deref for Population<Gnt, A, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    type Target = Vec<Individual<Gnt, A, F>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.individuals
+    }
+}
+
+impl<Gnt, A, F> DerefMut for Population<Gnt, A, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.individuals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        distance::{ApproximateEquality, Euclidean},
+        fitness::OptimizationGoal,
+        genome::Genome,
+        selection::TruncationSelection,
+        simplega::SimpleGABuilder,
+        variation::UniformCrossover,
+    };
+
+    type Gnt = [bool; 4];
+
+    fn ones(genotype: &Gnt) -> usize {
+        genotype.iter().filter(|bit| *bit).count()
+    }
+
+    fn population(
+        fitness_func: &FitnessFunc<'_, Gnt, bool, usize>,
+    ) -> Population<Gnt, bool, usize> {
+        [
+            [true, true, false, false],
+            [false, false, false, false],
+            [true, true, true, false],
+            [true, true, false, false],
+        ]
+        .into_iter()
+        .map(|genotype| {
+            let mut idv = Individual::from_genotype(genotype);
+            fitness_func.evaluate(&mut idv);
+            idv
+        })
+        .collect()
+    }
+
+    #[test]
+    fn best_and_worst_follow_goal() {
+        let fitness_func = FitnessFunc::new(&ones, OptimizationGoal::Maximize);
+        let population = population(&fitness_func);
+
+        assert_eq!(population.best(&fitness_func).unwrap().fitness(), 3);
+        assert_eq!(population.worst(&fitness_func).unwrap().fitness(), 0);
+
+        let fitness_func = FitnessFunc::new(&ones, OptimizationGoal::Minimize);
+        assert_eq!(population.best(&fitness_func).unwrap().fitness(), 0);
+    }
+
+    #[test]
+    fn merge_keeps_best_distinct_individuals() {
+        let fitness_func = FitnessFunc::new(&ones, OptimizationGoal::Maximize);
+
+        // The fitnesses of the earlier runs are not comparable, so they are ignored
+        let individuals = |genotypes: &[Gnt], fitness| -> Vec<Individual<Gnt, bool, usize>> {
+            genotypes
+                .iter()
+                .map(|genotype| {
+                    let mut idv = Individual::from_genotype(*genotype);
+                    idv.set_fitness(fitness);
+                    idv
+                })
+                .collect()
+        };
+        let a = individuals(
+            &[
+                [true, true, true, false],
+                [false, false, false, false],
+                [true, false, false, false],
+            ],
+            100,
+        );
+        let b = individuals(
+            &[
+                [true, true, true, false],
+                [true, true, true, true],
+                [false, true, true, false],
+            ],
+            0,
+        );
+
+        let merged = Population::merge(&a, &b, 3, &fitness_func);
+        let genotypes: Vec<_> = merged.iter().map(|idv| *idv.genotype()).collect();
+        assert_eq!(
+            genotypes,
+            vec![
+                [true, true, true, true],
+                [true, true, true, false],
+                [false, true, true, false],
+            ]
+        );
+        assert_eq!(merged[0].fitness(), 4);
+
+        // The five distinct genotypes are evaluated once each
+        assert_eq!(fitness_func.evaluations(), 5);
+
+        let genome = Genome::with_bool_domain();
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .population(merged.into_inner())
+            .evaluation_function(&ones)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .seed(0)
+            .build();
+        ga.initialize();
+        assert_eq!(ga.population().len(), 3);
+        assert_eq!(ga.best_individual().unwrap().fitness(), 4);
+    }
+
+    #[test]
+    fn mean_fitness() {
+        let fitness_func = FitnessFunc::new(&ones, OptimizationGoal::Maximize);
+
+        assert_abs_diff_eq!(population(&fitness_func).mean_fitness(), 7.0 / 4.0);
+    }
+
+    #[test]
+    fn sort_by_fitness_puts_best_first() {
+        let fitness_func = FitnessFunc::new(&ones, OptimizationGoal::Maximize);
+        let mut population = population(&fitness_func);
+
+        population.sort_by_fitness(&fitness_func);
+
+        let fitnesses: Vec<_> = population.iter().map(|idv| idv.fitness()).collect();
+        assert_eq!(fitnesses, vec![3, 2, 2, 0]);
+    }
+
+    #[test]
+    fn dedup_removes_repeated_genotypes() {
+        let fitness_func = FitnessFunc::new(&ones, OptimizationGoal::Maximize);
+        let mut population = population(&fitness_func);
+
+        population.dedup();
+
+        assert_eq!(population.len(), 3);
+        assert_eq!(population[0].genotype(), &[true, true, false, false]);
+    }
+
+    #[test]
+    fn dedup_real_genotypes_up_to_epsilon() {
+        let sum = |genotype: &[f64; 2]| genotype.iter().sum::<f64>();
+        let fitness_func = FitnessFunc::new(&sum, OptimizationGoal::Minimize);
+        let mut population: Population<[f64; 2], f64, f64> =
+            [[0.25, 1.0], [0.25 + 1e-9, 1.0 - 1e-9], [0.25 + 1e-3, 1.0]]
+                .into_iter()
+                .map(|genotype| {
+                    let mut idv = Individual::from_genotype(genotype);
+                    fitness_func.evaluate(&mut idv);
+                    idv
+                })
+                .collect::<Vec<_>>()
+                .into();
+
+        let mut exact = population.clone();
+        exact.dedup();
+        assert_eq!(exact.len(), 3);
+
+        let equality = ApproximateEquality::with_epsilon(1e-6);
+        assert!(!population.is_converged_by(&equality));
+
+        population.dedup_by(&equality);
+        assert_eq!(population.len(), 2);
+        assert_eq!(population[1].genotype(), &[0.25 + 1e-3, 1.0]);
+    }
+
+    #[test]
+    fn diversity_of_converged_and_mixed_populations() {
+        let fitness_func = FitnessFunc::new(&ones, OptimizationGoal::Maximize);
+        let population = population(&fitness_func);
+
+        // Pairwise distances: 2, 1, 0, 3, 2, 1
+        assert_abs_diff_eq!(population.diversity(), 9.0 / 6.0 / 4.0);
+
+        let converged: Population<Gnt, bool, usize> = vec![population[0].clone(); 5].into();
+        assert_eq!(converged.diversity(), 0.0);
+
+        assert!(!population.is_converged());
+        assert!(converged.is_converged());
+    }
+
+    #[test]
+    fn sorted_insertions_match_full_sort() {
+        let fitness_func = FitnessFunc::new(&ones, OptimizationGoal::Maximize);
+        let mut rng = rand::thread_rng();
+        let mut random_individual = || {
+            let mut idv = Individual::from_genotype([(); 4].map(|_| rng.gen_bool(0.5)));
+            fitness_func.evaluate(&mut idv);
+            idv
+        };
+
+        let mut population = population(&fitness_func);
+        population.sort_by_fitness(&fitness_func);
+        let mut inserted = population.clone();
+
+        for _ in 0..200 {
+            let idv = random_individual();
+            population.push(idv.clone());
+            inserted.insert_sorted(idv, &fitness_func);
+        }
+        population.sort_by_fitness(&fitness_func);
+
+        let genotypes = |population: &Population<Gnt, bool, usize>| -> Vec<Gnt> {
+            population.iter().map(|idv| *idv.genotype()).collect()
+        };
+        assert_eq!(genotypes(&inserted), genotypes(&population));
+
+        // Steady-state replacement keeps the size fixed and only ever improves the population
+        let mut steady = inserted.clone();
+        steady.truncate(10);
+        for _ in 0..200 {
+            let idv = random_individual();
+            let mut expected = steady.clone();
+            expected.push(idv.clone());
+            expected.sort_by_fitness(&fitness_func);
+            expected.truncate(10);
+
+            steady.replace_worst_sorted(idv, &fitness_func);
+
+            let fitnesses = |population: &Population<Gnt, bool, usize>| -> Vec<usize> {
+                population.iter().map(|idv| idv.fitness()).collect()
+            };
+            assert_eq!(fitnesses(&steady), fitnesses(&expected));
+        }
+    }
+
+    #[test]
+    fn clusters_of_two_peaks() {
+        // Peaks at -2 (height 1) and 3 (height 2)
+        let two_peaks = |genotype: &[f64; 1]| {
+            let x = genotype[0];
+            (1.0 - (x + 2.0).abs()).max(2.0 - (x - 3.0).abs())
+        };
+        let fitness_func = FitnessFunc::new(&two_peaks, OptimizationGoal::Maximize);
+
+        let population: Population<[f64; 1], f64, f64> =
+            [-2.3, 3.1, -1.9, 2.6, -2.05, 3.0, -1.5, 3.4]
+                .into_iter()
+                .map(|x| {
+                    let mut idv = Individual::from_genotype([x]);
+                    fitness_func.evaluate(&mut idv);
+                    idv
+                })
+                .collect();
+
+        let clusters = population.clusters(&Euclidean, 1.0, &fitness_func);
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0][0].genotype(), &[3.0]);
+        assert_eq!(clusters[1][0].genotype(), &[-2.05]);
+        assert_eq!(clusters[0].len(), 4);
+        assert_eq!(clusters[1].len(), 4);
+        assert!(clusters[1].iter().all(|idv| idv.genotype()[0] < 0.0));
+    }
+}
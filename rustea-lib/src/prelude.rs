@@ -0,0 +1,66 @@
+// The types needed to assemble and run a GA, so that they can be imported at once with
+// `use rustea_lib::prelude::*`
+pub use crate::{
+    bdom,
+    benchmark::{benchmark, BenchmarkResult},
+    ecga::Ecga,
+    fitness::{Fitness, FitnessFunc, OptimizationGoal},
+    gene::{
+        BoolDomain, DiscreteDomain, DiscreteGene, DisjointIntegralDomain, ExclusiveRangeRealDomain,
+        Gene, InclusiveRangeRealDomain, RealDomain, RealGene,
+    },
+    genome::{Genome, GenomeBuilder},
+    genotype::{BitGenotype128, BitGenotype64, Genotype, SizedArray, SizedVec},
+    gom::Gom,
+    idom,
+    individual::Individual,
+    mutation::{CauchyMutation, IntegerMutation, LevyMutation, SelfAdaptiveMutation},
+    rdom,
+    selection::{
+        AgeLayeredSelection, CopyOffspringSelection, LexicaseSelection, NoSelection,
+        RouletteWheelSelection, SelectionOperator, TournamentSelection, TruncationSelection,
+    },
+    simplega::{Learning, SelectionMode, SimpleGA, SimpleGABuilder, Status},
+    variation::{
+        BestImprovementHillClimb, BlockUniformCrossover, FirstImprovementHillClimb, NoVariation,
+        OnePointCrossover, TwoPointCrossover, Umda, UniformCrossover, VariationOperator,
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    const N: usize = 16;
+
+    fn one_max(genotype: &[bool; N]) -> usize {
+        genotype.iter().filter(|bit| *bit).count()
+    }
+
+    #[test]
+    fn ga_from_prelude_imports() {
+        let genome = Genome::with_discrete_domain(&bdom!());
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(50)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TournamentSelection::new(4, true))
+            .variation(UniformCrossover::default())
+            .target(N)
+            .seed(0)
+            .build();
+
+        assert!(matches!(ga.run(20_000), Status::TargetReached(_)));
+
+        let domains: Genome<[u8; 2], _, _> = GenomeBuilder::new()
+            .push_gene(DiscreteGene::with_domain(&idom!(0..4)))
+            .push_gene(DiscreteGene::with_domain(&idom!(1, 3)))
+            .build();
+        assert_eq!(domains.cardinality(), Some(8));
+
+        let reals: Genome<[f64; 2], _, _> = Genome::with_real_domain(&rdom!(0.0..1.0));
+        assert!(reals.contains_genotype(&[0.5, 0.25]));
+    }
+}
@@ -0,0 +1,52 @@
+// Reports the progress of a run, once per generation
+pub trait ProgressReporter {
+    fn report(&mut self, evaluations: usize, budget: usize);
+
+    // Called once when the run stops, for whatever reason
+    fn finish(&mut self) {}
+}
+
+#[derive(Clone, Default)]
+pub struct NoProgress;
+
+impl ProgressReporter for NoProgress {
+    fn report(&mut self, _evaluations: usize, _budget: usize) {}
+}
+
+// Shows the number of evaluations used out of the budget as a terminal progress bar
+#[cfg(feature = "progress")]
+pub struct ProgressBarReporter {
+    bar: indicatif::ProgressBar,
+}
+
+#[cfg(feature = "progress")]
+impl ProgressBarReporter {
+    pub fn new() -> Self {
+        Self {
+            bar: indicatif::ProgressBar::new(0),
+        }
+    }
+
+    pub fn with_bar(bar: indicatif::ProgressBar) -> Self {
+        Self { bar }
+    }
+}
+
+#[cfg(feature = "progress")]
+impl Default for ProgressBarReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "progress")]
+impl ProgressReporter for ProgressBarReporter {
+    fn report(&mut self, evaluations: usize, budget: usize) {
+        self.bar.set_length(budget as u64);
+        self.bar.set_position(evaluations.min(budget) as u64);
+    }
+
+    fn finish(&mut self) {
+        self.bar.finish();
+    }
+}
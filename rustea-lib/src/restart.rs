@@ -0,0 +1,249 @@
+use crate::{
+    fitness::Fitness,
+    gene::{Allele, Gene},
+    genotype::Genotype,
+    individual::Individual,
+    selection::SelectionOperator,
+    simplega::{SimpleGA, SimpleGABuilder, Status},
+    statistics::StatsCollector,
+    variation::VariationOperator,
+};
+
+// Runs a GA until it reaches the target or stagnates, after which it is restarted from scratch
+// with double the population size, for as long as the evaluation budget allows. A simpler
+// alternative to the parameterless GA, which runs several populations side by side.
+pub struct RestartingGA<'a, Gnt, A, G, F, S, V, C>
+where
+    A: Allele,
+    G: Gene<A>,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    builder: SimpleGABuilder<'a, Gnt, A, G, F, S, V, C>,
+    population_size: usize,
+    patience: usize,
+    ga: Option<SimpleGA<'a, Gnt, A, F, S, V, C>>,
+    restarts: usize,
+    finished_evaluations: usize,
+}
+
+impl<'a, Gnt, A, G, F, S, V, C> RestartingGA<'a, Gnt, A, G, F, S, V, C>
+where
+    A: Allele,
+    G: Gene<A>,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    // The builder must be fully configured, except for the population
+    pub fn with_builder(builder: SimpleGABuilder<'a, Gnt, A, G, F, S, V, C>) -> Self {
+        Self {
+            builder,
+            population_size: 4,
+            patience: 10,
+            ga: None,
+            restarts: 0,
+            finished_evaluations: 0,
+        }
+    }
+
+    pub fn initial_population_size(mut self, size: usize) -> Self {
+        self.population_size = size;
+        self
+    }
+
+    // The number of generations without improvement of the best fitness (by more than the
+    // improvement epsilon of the builder) after which a run is considered to have failed
+    pub fn patience(mut self, generations: usize) -> Self {
+        self.patience = generations;
+        self
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.finished_evaluations + self.ga.as_ref().map_or(0, |ga| ga.evaluations())
+    }
+
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    // The population size of the current (or last) run
+    pub fn population_size(&self) -> usize {
+        self.population_size
+    }
+
+    pub fn best_individual(&self) -> Option<&Individual<Gnt, A, F>> {
+        self.ga.as_ref().and_then(|ga| ga.best_individual())
+    }
+
+    pub fn run(&mut self, evaluation_budget: usize) -> Status {
+        loop {
+            let mut ga = self
+                .builder
+                .clone()
+                .random_population(self.population_size)
+                .derive_seed(self.restarts as u64)
+                .build();
+
+            // A run that cannot evaluate its initial population within the budget is not started
+            let initial_evaluations = ga.population().len() * ga.fitness_func().evaluation_cost();
+            if self.finished_evaluations + initial_evaluations > evaluation_budget {
+                return Status::BudgetReached(self.finished_evaluations);
+            }
+            ga.initialize();
+
+            let status = loop {
+                let evaluations = self.finished_evaluations + ga.evaluations();
+
+                if ga.target_reached() {
+                    break Some(Status::TargetReached(evaluations));
+                }
+
+                if evaluations >= evaluation_budget {
+                    break Some(Status::BudgetReached(evaluations));
+                }
+
+                if ga.stagnant_generations() >= self.patience {
+                    break None;
+                }
+
+                // The cap counts calls of the fitness function, each of which may cost several
+                // evaluations (e.g. with robust evaluation)
+                let affordable =
+                    (evaluation_budget - evaluations) / ga.fitness_func().evaluation_cost();
+                if affordable == 0 {
+                    break Some(Status::BudgetReached(evaluations));
+                }
+
+                ga.step_within(Some(affordable));
+            };
+
+            if let Some(status) = status {
+                self.ga = Some(ga);
+                return status;
+            }
+
+            // The run has stagnated; restart with a larger population and the remaining budget,
+            // unless the budget cannot evaluate the larger population, in which case the
+            // stagnated run is the last one
+            let evaluations = self.finished_evaluations + ga.evaluations();
+            let initial_evaluations =
+                2 * self.population_size * ga.fitness_func().evaluation_cost();
+            if evaluations + initial_evaluations > evaluation_budget {
+                self.ga = Some(ga);
+                return Status::BudgetReached(evaluations);
+            }
+
+            self.finished_evaluations += ga.evaluations();
+            self.population_size *= 2;
+            self.restarts += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        fitness::OptimizationGoal, genome::Genome, rng::SeededRng, selection::TruncationSelection,
+        variation::UniformCrossover,
+    };
+
+    const N: usize = 32;
+    type Gnt = [bool; N];
+
+    fn one_max(genotype: &Gnt) -> usize {
+        genotype.iter().filter(|bit| *bit).count()
+    }
+
+    #[test]
+    fn restarts_with_larger_population_until_solved() {
+        let genome = Genome::with_bool_domain();
+
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .target(N);
+
+        // A population of two converges long before it can solve the problem
+        let mut ga = RestartingGA::with_builder(builder).initial_population_size(2);
+        let status = ga.run(1_000_000);
+
+        assert!(matches!(status, Status::TargetReached(_)));
+        assert!(ga.restarts() > 0);
+        assert_eq!(ga.population_size(), 2 << ga.restarts());
+        assert_eq!(ga.best_individual().unwrap().fitness(), N);
+    }
+
+    #[test]
+    fn robust_evaluation_does_not_exceed_budget() {
+        const SAMPLES: usize = 5;
+        const BUDGET: usize = 1003;
+
+        let flip_one = |genotype: &Gnt, rng: &mut SeededRng| {
+            let mut perturbed = *genotype;
+            let locus = rng.gen_range(0..N);
+            perturbed[locus] = !perturbed[locus];
+            perturbed
+        };
+
+        let genome = Genome::with_bool_domain();
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .evaluation_function(&one_max)
+            .robust_evaluation(&flip_one, SAMPLES)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .seed(0);
+
+        let mut ga = RestartingGA::with_builder(builder)
+            .initial_population_size(10)
+            .patience(1_000);
+
+        // The last three evaluations do not suffice for a single robust evaluation
+        assert!(matches!(ga.run(BUDGET), Status::BudgetReached(1000)));
+        assert_eq!(ga.evaluations(), 1000);
+    }
+
+    #[test]
+    fn unaffordable_restart_is_not_started() {
+        const BUDGET: usize = 500;
+
+        let genome = Genome::with_bool_domain();
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .target(N + 1)
+            .seed(0);
+
+        // The target cannot be reached, so the runs keep stagnating and doubling in size
+        let mut ga = RestartingGA::with_builder(builder.clone())
+            .initial_population_size(2)
+            .patience(2);
+
+        assert!(
+            matches!(ga.run(BUDGET), Status::BudgetReached(evaluations) if evaluations <= BUDGET)
+        );
+        assert!(ga.evaluations() <= BUDGET);
+        assert!(ga.best_individual().is_some());
+
+        // Too small a budget for the initial population does not start a run at all
+        let mut ga = RestartingGA::with_builder(builder);
+        assert!(matches!(ga.run(3), Status::BudgetReached(0)));
+        assert!(ga.best_individual().is_none());
+    }
+}
@@ -2,6 +2,56 @@ use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg64;
 use rand_xoshiro::{Xoshiro256PlusPlus, Xoshiro256StarStar};
 
+// The random number generator threaded through the GA loop, so that seeded runs are reproducible
+pub type SeededRng = Xoshiro256PlusPlus;
+
+// Derives an independent generator for each of `n` work items, so that the results do not depend
+// on how the items are scheduled over threads
+pub fn item_rngs(rng: &mut SeededRng, n: usize) -> Vec<SeededRng> {
+    let stream = SeedStream::new(rng.gen());
+    (0..n as u64)
+        .map(|item| stream.derive(item).rng())
+        .collect()
+}
+
+// A tree of seeds, where each seed is derived from its parent seed and an index only. A run derives
+// the seed of each generation from its master seed and the generation index, and the seed of each
+// work item from the seed of its generation and the item index, so that a run is reproducible
+// regardless of the number of threads and the order in which generations and items are processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeedStream {
+    seed: u64,
+}
+
+impl SeedStream {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // The stream of the child with the given index, mixed with the SplitMix64 finalizer
+    pub fn derive(&self, index: u64) -> Self {
+        let mut z = self
+            .seed
+            .wrapping_add(index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        Self::new(z ^ (z >> 31))
+    }
+
+    pub fn generation(&self, generation: usize) -> Self {
+        self.derive(generation as u64)
+    }
+
+    pub fn rng(&self) -> SeededRng {
+        SeededRng::seed_from_u64(self.seed)
+    }
+}
+
 pub trait RngGenerator<R>
 where
     R: SeedableRng + ?Sized,
@@ -59,3 +109,36 @@ impl RngGenerator<Xoshiro256StarStar> for XoshiroRngGenerator<Xoshiro256StarStar
 //         rng
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derived_seeds_depend_on_parent_and_index_only() {
+        let stream = SeedStream::new(42);
+
+        assert_eq!(stream.generation(3), SeedStream::new(42).generation(3));
+        assert_ne!(stream.generation(3), stream.generation(4));
+        assert_ne!(stream.generation(3), SeedStream::new(43).generation(3));
+        assert_ne!(stream.derive(0).derive(1), stream.derive(1).derive(0));
+
+        let mut a = stream.generation(3).rng();
+        let mut b = stream.generation(3).rng();
+        assert_eq!(a.gen::<u64>(), b.gen::<u64>());
+    }
+
+    #[test]
+    fn item_rngs_are_distinct() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let seeds: Vec<u64> = item_rngs(&mut rng, 100)
+            .iter_mut()
+            .map(|rng| rng.gen())
+            .collect();
+
+        let mut distinct = seeds.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), seeds.len());
+    }
+}
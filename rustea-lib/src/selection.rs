@@ -1,152 +1,1211 @@
-use rand::seq::SliceRandom;
+use derivative::Derivative;
+use rand::{seq::SliceRandom, Rng};
+use rand_distr::StandardNormal;
 
 use crate::{
-    fitness::{Fitness, FitnessFunc},
+    fitness::{Fitness, FitnessFunc, OptimizationGoal},
     gene::Allele,
     genotype::Genotype,
     individual::Individual,
+    model::CumulativeDistribution,
+    parallel::*,
+    rng::{item_rngs, SeededRng},
 };
 
-pub trait SelectionOperator: Clone {
-    fn select<Gnt, A, F>(
+// A GA keeps the same operator instance for the entire run, so operators may keep state across
+// generations (e.g. a temperature schedule). Operators are only cloned when a GA is built, so every
+// run gets its own copy.
+pub trait SelectionOperator<Gnt, A, F>: Clone
+where
+    Self: Sized,
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    // Called when a run starts, to clear the state of a previous run
+    fn reset(&mut self) {}
+
+    // Chooses `count` survivors from the pool and returns their indices, where the pool holds the
+    // parents followed by the offspring. An index may occur more than once if the operator selects
+    // with replacement.
+    fn select_indices(
+        &mut self,
+        pool: &[Individual<Gnt, A, F>],
+        count: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        rng: &mut SeededRng,
+    ) -> Vec<usize>;
+
+    // Replaces the population by as many survivors from the parents and offspring combined
+    fn select(
         &mut self,
         population: &mut Vec<Individual<Gnt, A, F>>,
         offspring: Vec<Individual<Gnt, A, F>>,
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        rng: &mut SeededRng,
+    ) {
+        let population_size = population.len();
+
+        // Moving the parents out keeps the allocation of the population, which the survivors reuse
+        let mut pool = Vec::with_capacity(population_size + offspring.len());
+        pool.append(population);
+        pool.extend(offspring);
+
+        let indices = self.select_indices(&pool, population_size, fitness_func, rng);
+        population.extend(indices.into_iter().map(|idx| pool[idx].clone()));
+    }
+
+    // Selects from offspring that are still being produced. Operators that can select online
+    // override this to start before all offspring exist; the default collects them first.
+    fn select_iter<I>(
+        &mut self,
+        population: &mut Vec<Individual<Gnt, A, F>>,
+        offspring: I,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        rng: &mut SeededRng,
     ) where
-        Self: Sized,
-        A: Allele,
-        F: Fitness,
-        Gnt: Genotype<A>;
+        I: Iterator<Item = Individual<Gnt, A, F>>,
+    {
+        self.select(population, offspring.collect(), fitness_func, rng)
+    }
+
+    // Whether the offspring replace the population unchanged
+    fn copies_offspring(&self) -> bool {
+        false
+    }
+
+    // The number of individuals that `select` lets compete, given the numbers of parents and
+    // offspring; generational operators leave the parents out
+    fn pool_size(&self, parents: usize, offspring: usize) -> usize {
+        parents + offspring
+    }
+
+    // For operators that hold competitions (e.g. tournaments): the average number of competitions
+    // each individual of the last pool took part in
+    fn participations(&self) -> Option<f64> {
+        None
+    }
+
+    // Whether the best individual of the pool always survives (under plus selection), which
+    // quickly drains the diversity of a population that is not mutated
+    fn is_elitist(&self) -> bool {
+        false
+    }
+}
+
+// How many individuals competed for the survivor slots in a generation, for tuning
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectionPressure {
+    pool_size: usize,
+    survivors: usize,
+    participations: Option<f64>,
+}
+
+impl SelectionPressure {
+    pub fn new(pool_size: usize, survivors: usize, participations: Option<f64>) -> Self {
+        Self {
+            pool_size,
+            survivors,
+            participations,
+        }
+    }
+
+    // The number of individuals offered to selection, e.g. the parents and offspring combined
+    pub fn pool_size(&self) -> usize {
+        self.pool_size
+    }
+
+    pub fn survivors(&self) -> usize {
+        self.survivors
+    }
+
+    // The number of individuals that competed per survivor slot
+    pub fn ratio(&self) -> f64 {
+        self.pool_size as f64 / self.survivors as f64
+    }
+
+    // See `SelectionOperator::participations`
+    pub fn participations(&self) -> Option<f64> {
+        self.participations
+    }
 }
 
 #[derive(Clone)]
 pub struct NoSelection;
 
-impl SelectionOperator for NoSelection {
-    fn select<Gnt, A, F>(
+impl<Gnt, A, F> SelectionOperator<Gnt, A, F> for NoSelection
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    // The parents survive
+    fn select_indices(
+        &mut self,
+        _pool: &[Individual<Gnt, A, F>],
+        count: usize,
+        _fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        _rng: &mut SeededRng,
+    ) -> Vec<usize> {
+        (0..count).collect()
+    }
+
+    fn pool_size(&self, parents: usize, _offspring: usize) -> usize {
+        parents
+    }
+
+    fn select(
         &mut self,
         population: &mut Vec<Individual<Gnt, A, F>>,
         offspring: Vec<Individual<Gnt, A, F>>,
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
-    ) where
-        Self: Sized,
-        A: Allele,
-        F: Fitness,
-        Gnt: Genotype<A>,
-    {
+        _rng: &mut SeededRng,
+    ) {
     }
 }
 
 #[derive(Clone)]
 pub struct CopyOffspringSelection;
 
-impl SelectionOperator for CopyOffspringSelection {
-    fn select<Gnt, A, F>(
+impl<Gnt, A, F> SelectionOperator<Gnt, A, F> for CopyOffspringSelection
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    // The last (i.e. most recent) individuals survive
+    fn select_indices(
+        &mut self,
+        pool: &[Individual<Gnt, A, F>],
+        count: usize,
+        _fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        _rng: &mut SeededRng,
+    ) -> Vec<usize> {
+        (pool.len().saturating_sub(count)..pool.len()).collect()
+    }
+
+    // The population takes the size of the offspring
+    fn select(
         &mut self,
         population: &mut Vec<Individual<Gnt, A, F>>,
         offspring: Vec<Individual<Gnt, A, F>>,
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        _rng: &mut SeededRng,
+    ) {
+        population.clear();
+        population.extend_from_slice(&offspring)
+    }
+
+    fn pool_size(&self, _parents: usize, offspring: usize) -> usize {
+        offspring
+    }
+
+    fn copies_offspring(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Derivative, Clone)]
+#[derivative(Default)]
+pub struct TruncationSelection {
+    // Whether offspring survive over parents of equal fitness
+    #[derivative(Default(value = "true"))]
+    prefer_offspring_on_tie: bool,
+}
+
+impl TruncationSelection {
+    pub fn prefer_offspring_on_tie(mut self, prefer_offspring: bool) -> Self {
+        self.prefer_offspring_on_tie = prefer_offspring;
+        self
+    }
+}
+
+impl TruncationSelection {
+    // Moves the best `population_size` individuals of the pool to the front and drops the rest
+    fn truncate<Gnt, A, F>(
+        &self,
+        pool: &mut Vec<(bool, Individual<Gnt, A, F>)>,
+        population_size: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
     ) where
-        Self: Sized,
         A: Allele,
         F: Fitness,
         Gnt: Genotype<A>,
     {
-        population.clear();
-        population.extend_from_slice(&offspring)
+        // Only the best individuals need to be moved to the front; their internal order is irrelevant
+        if population_size < pool.len() {
+            pool.select_nth_unstable_by(
+                population_size,
+                |(offspring_a, idv_a), (offspring_b, idv_b)| {
+                    fitness_func
+                        .cmp(&idv_a.fitness(), &idv_b.fitness())
+                        .then_with(|| {
+                            if self.prefer_offspring_on_tie {
+                                offspring_b.cmp(offspring_a)
+                            } else {
+                                offspring_a.cmp(offspring_b)
+                            }
+                        })
+                },
+            );
+        }
+
+        pool.truncate(population_size);
     }
 }
 
-#[derive(Clone)]
-pub struct TruncationSelection;
+impl<Gnt, A, F> SelectionOperator<Gnt, A, F> for TruncationSelection
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    // Offspring come after the parents in the pool, so ties are broken on the index
+    fn select_indices(
+        &mut self,
+        pool: &[Individual<Gnt, A, F>],
+        count: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        _rng: &mut SeededRng,
+    ) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..pool.len()).collect();
 
-impl SelectionOperator for TruncationSelection {
-    fn select<Gnt, A, F>(
+        // Only the best individuals need to be moved to the front; their internal order is irrelevant
+        if count < indices.len() {
+            indices.select_nth_unstable_by(count, |a, b| {
+                fitness_func
+                    .cmp(&pool[*a].fitness(), &pool[*b].fitness())
+                    .then_with(|| {
+                        if self.prefer_offspring_on_tie {
+                            b.cmp(a)
+                        } else {
+                            a.cmp(b)
+                        }
+                    })
+            });
+        }
+
+        indices.truncate(count);
+        indices
+    }
+
+    // Truncates the pool whenever it has doubled in size, so that at most twice the population
+    // is held in memory, however many offspring are produced
+    fn select_iter<I>(
         &mut self,
         population: &mut Vec<Individual<Gnt, A, F>>,
-        offspring: Vec<Individual<Gnt, A, F>>,
+        offspring: I,
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        _rng: &mut SeededRng,
     ) where
-        Self: Sized,
-        A: Allele,
-        F: Fitness,
-        Gnt: Genotype<A>,
+        I: Iterator<Item = Individual<Gnt, A, F>>,
     {
         let population_size = population.len();
-        population.extend(offspring);
-        population.sort_by(|idv_a, idv_b| fitness_func.cmp(&idv_a.fitness(), &idv_b.fitness()));
-        population.truncate(population_size);
+
+        let mut pool = Vec::with_capacity(2 * population_size);
+        pool.extend(population.drain(..).map(|idv| (false, idv)));
+
+        for idv in offspring {
+            pool.push((true, idv));
+
+            if pool.len() >= 2 * population_size {
+                self.truncate(&mut pool, population_size, fitness_func);
+            }
+        }
+
+        self.truncate(&mut pool, population_size, fitness_func);
+
+        population.extend(pool.into_iter().map(|(_, idv)| idv));
+    }
+
+    fn is_elitist(&self) -> bool {
+        true
     }
 }
 
+// Fitness-proportionate selection (with replacement) from the parents and offspring combined.
+// Fitnesses are shifted such that the worst individual in the pool has zero weight. Non-finite
+// fitnesses (e.g. NaN from a buggy evaluation function) get zero weight too, unless no fitness in
+// the pool is finite, in which case the pool is sampled uniformly.
+#[derive(Clone, Default)]
+pub struct RouletteWheelSelection;
+
+impl<Gnt, A, F> SelectionOperator<Gnt, A, F> for RouletteWheelSelection
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn select_indices(
+        &mut self,
+        pool: &[Individual<Gnt, A, F>],
+        count: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        rng: &mut SeededRng,
+    ) -> Vec<usize> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let fitnesses: Vec<f64> = pool.iter().map(|idv| idv.fitness().as_f64()).collect();
+        let finite = || fitnesses.iter().copied().filter(|f| f.is_finite());
+        let min = finite().fold(f64::INFINITY, f64::min);
+        let max = finite().fold(f64::NEG_INFINITY, f64::max);
+
+        // A pool of equal fitnesses is sampled uniformly. The fitnesses are halved, so that the
+        // distance between extreme fitnesses (e.g. `f64::MIN` and `f64::MAX`) does not overflow.
+        let weights = fitnesses
+            .iter()
+            .map(|f| match fitness_func.goal().primary() {
+                _ if min > max => 1.0,
+                _ if !f.is_finite() => 0.0,
+                _ if max == min => 1.0,
+                OptimizationGoal::Maximize => f / 2.0 - min / 2.0,
+                _ => max / 2.0 - f / 2.0,
+            });
+        let distribution = CumulativeDistribution::from_weights(weights);
+
+        (0..count).map(|_| distribution.sample(rng)).collect()
+    }
+}
+
+// With `include_parents`, the parents compete with their offspring, and the best individual wins
+// every (deterministic) tournament it takes part in. It is only guaranteed to take part if the
+// pool splits into at most `count` tournaments, i.e. if the pool holds at most `count` times the
+// tournament size (e.g. mu + lambda with lambda = mu and tournaments of at least two); only then
+// does the best fitness never regress. Otherwise, selection is generational: the tournaments are
+// held among the offspring only, and the best individual can be lost. Pair either case with
+// `SimpleGABuilder::force_elitism` to always keep it.
 #[derive(Clone)]
 pub struct TournamentSelection {
     tournament_size: usize,
     include_parents: bool,
+    probability: f64,
+    participations: Option<f64>,
 }
 
-impl SelectionOperator for TournamentSelection {
-    fn select<Gnt, A, F>(
-        &mut self,
-        population: &mut Vec<Individual<Gnt, A, F>>,
-        offspring: Vec<Individual<Gnt, A, F>>,
+impl TournamentSelection {
+    pub fn new(tournament_size: usize, include_parents: bool) -> Self {
+        assert!(tournament_size > 0, "the tournament size must be positive");
+
+        Self {
+            tournament_size,
+            include_parents,
+            probability: 1.0,
+            participations: None,
+        }
+    }
+
+    // Makes the tournaments probabilistic: the best competitor wins with probability `p`, the
+    // second best with p(1 - p), and so on, with the worst taking the remaining probability. A
+    // lower probability lowers the selection pressure; 1.0 always selects the best competitor.
+    pub fn probability(mut self, probability: f64) -> Self {
+        assert!(
+            probability > 0.0 && probability <= 1.0,
+            "the probability must be in the range (0, 1]"
+        );
+        self.probability = probability;
+        self
+    }
+}
+
+impl TournamentSelection {
+    fn winner<Gnt, A, F>(
+        &self,
+        tournament: &[usize],
+        pool: &[Individual<Gnt, A, F>],
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
-    ) where
-        Self: Sized,
+        rng: &mut SeededRng,
+    ) -> usize
+    where
         A: Allele,
         F: Fitness,
         Gnt: Genotype<A>,
     {
-        let population_size = population.len();
-        let pool_size = offspring.len()
-            + if self.include_parents {
-                population_size
-            } else {
-                0
-            };
+        if self.probability == 1.0 {
+            return *tournament
+                .iter()
+                .min_by(|a, b| fitness_func.cmp(&pool[**a].fitness(), &pool[**b].fitness()))
+                .unwrap();
+        }
+
+        let mut ranked = tournament.to_vec();
+        ranked.sort_by(|a, b| fitness_func.cmp(&pool[*a].fitness(), &pool[*b].fitness()));
+
+        let (last, rest) = ranked.split_last().unwrap();
+        *rest
+            .iter()
+            .find(|_| rng.gen_bool(self.probability))
+            .unwrap_or(last)
+    }
+}
+
+impl<Gnt, A, F> SelectionOperator<Gnt, A, F> for TournamentSelection
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn select_indices(
+        &mut self,
+        pool: &[Individual<Gnt, A, F>],
+        count: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        rng: &mut SeededRng,
+    ) -> Vec<usize> {
+        let pool_size = pool.len();
+        assert!(
+            pool_size > 0 || count == 0,
+            "cannot select from an empty pool"
+        );
 
-        let mut pool: Vec<_> = Vec::with_capacity(pool_size);
+        // Every iteration splits the shuffled pool into tournaments, so that each individual
+        // competes once per iteration. If the tournament size does not divide the pool size, the
+        // last tournament of an iteration is smaller, and if the number of tournaments does not
+        // divide `count`, the last iteration is cut short.
+        let mut order: Vec<usize> = (0..pool_size).collect();
+        let mut winners = Vec::with_capacity(count);
+        let mut competitors = 0;
+
+        while winners.len() < count {
+            order.shuffle(rng);
+
+            // The tournaments of an iteration are independent, so they are held in parallel, each
+            // with its own generator
+            let tournaments: Vec<_> = order
+                .chunks(self.tournament_size)
+                .take(count - winners.len())
+                .collect();
+            competitors += tournaments.iter().map(|t| t.len()).sum::<usize>();
+
+            let iteration_winners: Vec<_> = item_rngs(rng, tournaments.len())
+                .into_par_iter()
+                .zip(tournaments.par_iter())
+                .map(|(mut rng, tournament)| self.winner(tournament, pool, fitness_func, &mut rng))
+                .collect();
+
+            winners.extend(iteration_winners);
+        }
+
+        assert!(winners.len() == count);
+        self.participations = (pool_size > 0).then(|| competitors as f64 / pool_size as f64);
+        winners
+    }
 
+    fn participations(&self) -> Option<f64> {
+        self.participations
+    }
+
+    fn pool_size(&self, parents: usize, offspring: usize) -> usize {
         if self.include_parents {
-            pool.append(population);
+            parents + offspring
+        } else {
+            offspring
         }
-        pool.extend(offspring);
+    }
 
-        // N - pool size
-        // p - pop size
-        // o - offspring size
-        // k - tournament size
-        // t - number of times each individual is considered
-        // t = k*p / N
-        assert!(pool_size % self.tournament_size == 0);
-        assert!(self.tournament_size * population_size % pool_size == 0);
+    // Only deterministic tournaments that include the parents keep the best, as long as the pool
+    // is small enough for everyone to compete (see above)
+    fn is_elitist(&self) -> bool {
+        self.include_parents && self.probability == 1.0
+    }
+
+    // Without the parents, the tournaments are held among the offspring only
+    fn select(
+        &mut self,
+        population: &mut Vec<Individual<Gnt, A, F>>,
+        offspring: Vec<Individual<Gnt, A, F>>,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        rng: &mut SeededRng,
+    ) {
+        let population_size = population.len();
 
-        let num_iterations = self.tournament_size * population_size / pool_size;
-        let num_tournaments = pool_size / self.tournament_size;
+        let pool = if self.include_parents {
+            let mut pool = std::mem::take(population);
+            pool.extend(offspring);
+            pool
+        } else {
+            population.clear();
+            offspring
+        };
 
-        population.clear();
+        let indices = self.select_indices(&pool, population_size, fitness_func, rng);
+        population.extend(indices.into_iter().map(|idx| pool[idx].clone()));
+    }
+}
 
-        let mut rng = rand::thread_rng();
+// Selects each survivor by filtering the pool on the test cases in a random order,
+// keeping only the individuals that are elite on the current case. The case function is called
+// once per individual of the pool every generation; these calls bypass the fitness function, so
+// they are not counted as evaluations and do not use up the budget.
+#[derive(Clone)]
+pub struct LexicaseSelection<'a, Gnt, F>
+where
+    F: Fitness,
+{
+    case_func: &'a (dyn Fn(&Gnt) -> Vec<F> + Send + Sync),
+}
 
-        for _ in 0..num_iterations {
-            pool.shuffle(&mut rng);
+impl<'a, Gnt, F> LexicaseSelection<'a, Gnt, F>
+where
+    F: Fitness,
+{
+    pub fn with_cases(case_func: &'a (dyn Fn(&Gnt) -> Vec<F> + Send + Sync)) -> Self {
+        Self { case_func }
+    }
+}
 
-            let mut winners: Vec<_> = (0..num_tournaments)
-                .map(|i| {
-                    let winner = pool
-                        [self.tournament_size * i..self.tournament_size * i + self.tournament_size]
+impl<'a, Gnt, A, F> SelectionOperator<Gnt, A, F> for LexicaseSelection<'a, Gnt, F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn select_indices(
+        &mut self,
+        pool: &[Individual<Gnt, A, F>],
+        count: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        rng: &mut SeededRng,
+    ) -> Vec<usize> {
+        assert!(
+            !pool.is_empty() || count == 0,
+            "cannot select from an empty pool"
+        );
+
+        let cases: Vec<_> = pool
+            .par_iter()
+            .map(|idv| (self.case_func)(idv.genotype()))
+            .collect();
+
+        let num_cases = cases.first().map_or(0, Vec::len);
+        assert!(
+            cases.iter().all(|c| c.len() == num_cases),
+            "every individual must be scored on the same number of cases"
+        );
+
+        let mut case_order: Vec<_> = (0..num_cases).collect();
+
+        (0..count)
+            .map(|_| {
+                case_order.shuffle(rng);
+
+                let mut candidates: Vec<_> = (0..pool.len()).collect();
+
+                for case in case_order.iter() {
+                    if candidates.len() <= 1 {
+                        break;
+                    }
+
+                    let elite = candidates
                         .iter()
-                        .min_by(|idv_a, idv_b| fitness_func.cmp(&idv_a.fitness(), &idv_b.fitness()))
+                        .map(|idx| cases[*idx][*case])
+                        .min_by(|a, b| fitness_func.cmp(a, b))
                         .unwrap();
 
-                    winner.clone()
+                    candidates.retain(|idx| fitness_func.cmp(&cases[*idx][*case], &elite).is_eq());
+                }
+
+                // Remaining ties are broken randomly
+                *candidates.choose(rng).unwrap()
+            })
+            .collect()
+    }
+}
+
+// Age-layered truncation selection (after ALPS): the pool is split into layers by age, each layer
+// holding `age_gap` consecutive ages (the last layer holds all older individuals), and every layer
+// gets an equal share of the survivors. Young individuals then only compete with each other, so that
+// a single old lineage cannot take over the population. Slots that a layer cannot fill go to the
+// best of the remaining individuals.
+#[derive(Clone)]
+pub struct AgeLayeredSelection {
+    layers: usize,
+    age_gap: usize,
+}
+
+impl AgeLayeredSelection {
+    pub fn with_layers(layers: usize, age_gap: usize) -> Self {
+        assert!(layers > 0, "there must be at least one age layer");
+        assert!(age_gap > 0, "the age gap must be positive");
+
+        Self { layers, age_gap }
+    }
+
+    fn layer(&self, age: usize) -> usize {
+        (age / self.age_gap).min(self.layers - 1)
+    }
+}
+
+impl<Gnt, A, F> SelectionOperator<Gnt, A, F> for AgeLayeredSelection
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn select_indices(
+        &mut self,
+        pool: &[Individual<Gnt, A, F>],
+        count: usize,
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        _rng: &mut SeededRng,
+    ) -> Vec<usize> {
+        let by_fitness =
+            |a: &usize, b: &usize| fitness_func.cmp(&pool[*a].fitness(), &pool[*b].fitness());
+
+        let mut layers = vec![Vec::new(); self.layers];
+        for (idx, idv) in pool.iter().enumerate() {
+            layers[self.layer(idv.age())].push(idx);
+        }
+
+        let mut selected = Vec::with_capacity(count);
+        let mut remaining = Vec::new();
+
+        for (layer, mut indices) in layers.into_iter().enumerate() {
+            // Stable, so that ties are broken on the index
+            indices.sort_by(by_fitness);
+
+            let slots = count / self.layers + usize::from(layer < count % self.layers);
+            let rest = indices.split_off(slots.min(indices.len()));
+
+            selected.extend(indices);
+            remaining.extend(rest);
+        }
+
+        remaining.sort_by(by_fitness);
+        selected.extend(remaining.into_iter().take(count - selected.len()));
+
+        selected
+    }
+}
+
+// The genotype of the synthetic populations of the selection diagnostics, which only depend on
+// fitness
+pub type SyntheticGenotype = [bool; 1];
+
+fn synthetic_population(fitnesses: &[f64]) -> Vec<Individual<SyntheticGenotype, bool, f64>> {
+    fitnesses
+        .iter()
+        .map(|fitness| {
+            let mut individual = Individual::from_genotype([false]);
+            individual.set_fitness(*fitness);
+            individual
+        })
+        .collect()
+}
+
+// Measures the selection intensity of an operator empirically over one generation: the mean
+// fitness gain of the survivors over the pool, in standard deviations of the pool. Parents and
+// offspring both have standard normally distributed fitnesses, to be maximized.
+pub fn selection_intensity<S>(operator: &mut S, population_size: usize, rng: &mut SeededRng) -> f64
+where
+    S: SelectionOperator<SyntheticGenotype, bool, f64>,
+{
+    assert!(
+        population_size > 1,
+        "the population needs at least two individuals"
+    );
+
+    let evaluation_func = |_: &SyntheticGenotype| 0.0;
+    let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+    let pool: Vec<f64> = (0..2 * population_size)
+        .map(|_| rng.sample(StandardNormal))
+        .collect();
+    let (parents, offspring) = pool.split_at(population_size);
+
+    let mean = pool.iter().sum::<f64>() / pool.len() as f64;
+    let variance = pool.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / pool.len() as f64;
+
+    let mut population = synthetic_population(parents);
+    operator.reset();
+    operator.select(
+        &mut population,
+        synthetic_population(offspring),
+        &fitness_func,
+        rng,
+    );
+
+    let selected_mean =
+        population.iter().map(|idv| idv.fitness()).sum::<f64>() / population.len() as f64;
+
+    (selected_mean - mean) / variance.sqrt()
+}
+
+// The number of generations it takes copies of a single best individual to take over the
+// population under selection alone, where the offspring are copies of their parents. None if
+// the best individual is lost or has not taken over within `max_generations`.
+pub fn takeover_time<S>(
+    operator: &mut S,
+    population_size: usize,
+    max_generations: usize,
+    rng: &mut SeededRng,
+) -> Option<usize>
+where
+    S: SelectionOperator<SyntheticGenotype, bool, f64>,
+{
+    assert!(
+        population_size > 1,
+        "the population needs at least two individuals"
+    );
+
+    let evaluation_func = |_: &SyntheticGenotype| 0.0;
+    let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+    let mut fitnesses = vec![0.0; population_size];
+    fitnesses[0] = 1.0;
+    let mut population = synthetic_population(&fitnesses);
+    operator.reset();
+
+    for generation in 1..=max_generations {
+        let offspring = population.clone();
+        operator.select(&mut population, offspring, &fitness_func, rng);
+
+        match population.iter().filter(|idv| idv.fitness() == 1.0).count() {
+            0 => return None,
+            best if best == population.len() => return Some(generation),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::fitness::OptimizationGoal;
+
+    type Gnt = [bool; 2];
+
+    fn individuals(fitnesses: &[i32]) -> Vec<Individual<Gnt, bool, i32>> {
+        fitnesses
+            .iter()
+            .enumerate()
+            .map(|(i, fitness)| {
+                let mut idv = Individual::from_genotype([i % 2 == 0, i / 2 == 0]);
+                idv.set_fitness(*fitness);
+                idv
+            })
+            .collect()
+    }
+
+    fn tournament_winners(fitnesses: &[i32], goal: OptimizationGoal) -> Vec<i32> {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, goal);
+
+        let (parents, offspring) = fitnesses.split_at(fitnesses.len() / 2);
+        let mut population = individuals(parents);
+
+        // A single tournament spanning the entire pool, so the best individual must win
+        let mut selection = TournamentSelection::new(fitnesses.len(), true);
+        selection.select(
+            &mut population,
+            individuals(offspring),
+            &fitness_func,
+            &mut rng,
+        );
+
+        population.iter().map(|idv| idv.fitness()).collect()
+    }
+
+    #[test]
+    fn tournament_minimize_with_negative_and_zero_fitness() {
+        assert_eq!(
+            tournament_winners(&[0, -1, 3, -7], OptimizationGoal::Minimize),
+            vec![-7, -7]
+        );
+        assert_eq!(
+            tournament_winners(&[0, 0, 5, 2], OptimizationGoal::Minimize),
+            vec![0, 0]
+        );
+    }
+
+    #[test]
+    fn tournament_maximize_with_negative_and_zero_fitness() {
+        assert_eq!(
+            tournament_winners(&[-4, -1, -3, -7], OptimizationGoal::Maximize),
+            vec![-1, -1]
+        );
+        assert_eq!(
+            tournament_winners(&[-2, 0, -5, -9], OptimizationGoal::Maximize),
+            vec![0, 0]
+        );
+    }
+
+    #[test]
+    fn seeded_tournaments_are_reproducible() {
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let pool = individuals(&(0..64).collect::<Vec<_>>());
+
+        let survivors = |seed| {
+            TournamentSelection::new(4, true)
+                .probability(0.8)
+                .select_indices(
+                    &pool,
+                    32,
+                    &fitness_func,
+                    &mut SeededRng::seed_from_u64(seed),
+                )
+        };
+
+        assert_eq!(survivors(1), survivors(1));
+        assert_ne!(survivors(1), survivors(2));
+    }
+
+    #[test]
+    fn probabilistic_tournament_lets_worse_individual_win() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        const TOURNAMENTS: usize = 20_000;
+
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let pool = individuals(&[1, 2]);
+
+        let mut selection = TournamentSelection::new(2, true).probability(0.5);
+        let winners = (0..TOURNAMENTS / 2)
+            .flat_map(|_| selection.select_indices(&pool, 2, &fitness_func, &mut rng))
+            .filter(|&winner| winner == 1)
+            .count();
+
+        let fraction = winners as f64 / TOURNAMENTS as f64;
+        assert!((fraction - 0.5).abs() < 0.02, "{}", fraction);
+
+        let mut selection = TournamentSelection::new(2, true);
+        assert!((0..100)
+            .all(|_| selection.select_indices(&pool, 2, &fitness_func, &mut rng) == vec![1, 1]));
+    }
+
+    #[test]
+    fn tournaments_with_indivisible_sizes() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let pool = individuals(&(0..10).collect::<Vec<_>>());
+
+        for (tournament_size, count) in [(3, 7), (4, 10), (3, 25), (11, 3)] {
+            let winners = TournamentSelection::new(tournament_size, true).select_indices(
+                &pool,
+                count,
+                &fitness_func,
+                &mut rng,
+            );
+            assert_eq!(winners.len(), count);
+        }
+    }
+
+    #[test]
+    fn only_tournaments_with_parents_keep_the_best() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        // The best individual is a parent, and the population size is not a multiple of the
+        // tournament size
+        let parents: Vec<i32> = (0..10).map(|i| 100 - i).collect();
+        let offspring: Vec<i32> = (0..10).collect();
+
+        let best_survivor = |include_parents, rng: &mut SeededRng| {
+            let mut population = individuals(&parents);
+            TournamentSelection::new(3, include_parents).select(
+                &mut population,
+                individuals(&offspring),
+                &fitness_func,
+                rng,
+            );
+            assert_eq!(population.len(), parents.len());
+            population.iter().map(|idv| idv.fitness()).max().unwrap()
+        };
+
+        assert!((0..100).all(|_| best_survivor(true, &mut rng) == 100));
+
+        // Generational tournaments only see the offspring, so the best parent is lost
+        assert_eq!(best_survivor(false, &mut rng), 9);
+    }
+
+    #[test]
+    fn truncation_survivors_match_full_sort() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let mut fitnesses: Vec<i32> = (-50..50).collect();
+        fitnesses.shuffle(&mut rand::thread_rng());
+
+        let (parents, offspring) = fitnesses.split_at(40);
+        let mut population = individuals(parents);
+        TruncationSelection::default().select(
+            &mut population,
+            individuals(offspring),
+            &fitness_func,
+            &mut rng,
+        );
+
+        let mut survivors: Vec<_> = population.iter().map(|idv| idv.fitness()).collect();
+        survivors.sort();
+
+        let mut expected = fitnesses.clone();
+        expected.sort_by(|a, b| fitness_func.cmp(a, b));
+        expected.truncate(40);
+        expected.sort();
+
+        assert_eq!(survivors, expected);
+    }
+
+    #[test]
+    fn nan_fitness_is_selected_last() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0.0;
+
+        for goal in [OptimizationGoal::Minimize, OptimizationGoal::Maximize] {
+            let fitness_func = FitnessFunc::new(&evaluation_func, goal);
+            let individuals: Vec<Individual<Gnt, bool, f64>> = [1.0, f64::NAN, -3.0, 2.0]
+                .into_iter()
+                .map(|fitness| {
+                    let mut idv = Individual::from_genotype([false, false]);
+                    idv.set_fitness(fitness);
+                    idv
                 })
                 .collect();
 
-            population.append(&mut winners);
+            let (parents, offspring) = individuals.split_at(2);
+            let mut population = parents.to_vec();
+            TruncationSelection::default().select(
+                &mut population,
+                offspring.to_vec(),
+                &fitness_func,
+                &mut rng,
+            );
+            assert!(population.iter().all(|idv| !idv.fitness().is_nan()));
+
+            let mut tournament = TournamentSelection::new(individuals.len(), true);
+            let winners = tournament.select_indices(&individuals, 4, &fitness_func, &mut rng);
+            assert!(winners.iter().all(|&winner| winner != 1));
+
+            let selected =
+                RouletteWheelSelection.select_indices(&individuals, 100, &fitness_func, &mut rng);
+            assert!(selected.iter().all(|&idx| idx != 1));
+        }
+    }
+
+    #[test]
+    fn roulette_handles_non_finite_and_extreme_fitnesses() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0.0;
+
+        for goal in [OptimizationGoal::Minimize, OptimizationGoal::Maximize] {
+            let fitness_func = FitnessFunc::new(&evaluation_func, goal);
+            let with_fitness = |fitness: f64| {
+                let mut idv = Individual::from_genotype([false, false]);
+                idv.set_fitness(fitness);
+                idv
+            };
+
+            // Failed evaluations get the extreme fitnesses, e.g. from `FailurePolicy::WorstFitness`
+            let pool: Vec<_> = [f64::MIN, 1.0, f64::INFINITY, f64::MAX, f64::NAN]
+                .into_iter()
+                .map(with_fitness)
+                .collect();
+            let selected =
+                RouletteWheelSelection.select_indices(&pool, 100, &fitness_func, &mut rng);
+            assert!(selected.iter().all(|&idx| idx < 4 && idx != 2));
+
+            let pool: Vec<_> = [f64::NAN, f64::NAN].into_iter().map(with_fitness).collect();
+            let selected =
+                RouletteWheelSelection.select_indices(&pool, 10, &fitness_func, &mut rng);
+            assert_eq!(selected.len(), 10);
+
+            let selected = RouletteWheelSelection.select_indices(
+                &Vec::<Individual<Gnt, bool, f64>>::new(),
+                0,
+                &fitness_func,
+                &mut rng,
+            );
+            assert!(selected.is_empty());
         }
+    }
+
+    #[test]
+    fn selection_reuses_population_buffer() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+
+        let fitnesses = [7, 3, 9, 1, 4, 8, 2, 6];
+        let pool = individuals(&fitnesses);
+        let (parents, offspring) = pool.split_at(4);
+
+        let mut selection = TruncationSelection::default();
+        let mut expected: Vec<_> = selection
+            .select_indices(&pool, 4, &fitness_func, &mut rng)
+            .into_iter()
+            .map(|idx| fitnesses[idx])
+            .collect();
+        expected.sort();
+
+        let mut population = parents.to_vec();
+        let buffer = population.as_ptr();
+        selection.select(&mut population, offspring.to_vec(), &fitness_func, &mut rng);
+
+        assert_eq!(population.as_ptr(), buffer);
+        let mut survivors: Vec<_> = population.iter().map(|idv| idv.fitness()).collect();
+        survivors.sort();
+        assert_eq!(survivors, expected);
+        assert_eq!(survivors, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn truncation_ties_follow_offspring_preference() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+
+        let tied = |genotype: Gnt| {
+            let mut idv = Individual::from_genotype(genotype);
+            idv.set_fitness(5);
+            idv
+        };
+
+        for prefer_offspring in [true, false] {
+            let mut population = vec![tied([false, false]); 4];
+            let offspring = vec![tied([true, true]); 4];
+
+            TruncationSelection::default()
+                .prefer_offspring_on_tie(prefer_offspring)
+                .select(&mut population, offspring, &fitness_func, &mut rng);
+
+            assert_eq!(population.len(), 4);
+            assert!(population
+                .iter()
+                .all(|idv| *idv.genotype() == [prefer_offspring; 2]));
+        }
+    }
+
+    #[test]
+    fn lexicase_keeps_specialist_discarded_by_truncation() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        type CaseGnt = [u8; 3];
+
+        // The genotype holds the scores on each of the three cases
+        let case_func = |genotype: &CaseGnt| genotype.iter().map(|s| s as usize).collect();
+        let evaluation_func = |genotype: &CaseGnt| genotype.iter().map(|s| s as usize).sum();
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let evaluated = |genotype: CaseGnt| {
+            let mut idv = Individual::from_genotype(genotype);
+            fitness_func.evaluate(&mut idv);
+            idv
+        };
+
+        let specialist = [5, 0, 0];
+        let generalists = [[2, 2, 2]; 10];
+
+        let mut population: Vec<_> = generalists.iter().map(|g| evaluated(*g)).collect();
+        TruncationSelection::default().select(
+            &mut population,
+            vec![evaluated(specialist)],
+            &fitness_func,
+            &mut rng,
+        );
+        assert!(population.iter().all(|idv| *idv.genotype() != specialist));
+
+        let mut selection = LexicaseSelection::with_cases(&case_func);
+        let selected_specialist = (0..10).any(|_| {
+            let mut population: Vec<_> = generalists.iter().map(|g| evaluated(*g)).collect();
+            selection.select(
+                &mut population,
+                vec![evaluated(specialist)],
+                &fitness_func,
+                &mut rng,
+            );
+            population.iter().any(|idv| *idv.genotype() == specialist)
+        });
+        assert!(selected_specialist);
+    }
+
+    #[test]
+    fn lexicase_selects_nothing_from_empty_pool() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let case_func = |genotype: &Gnt| genotype.iter().map(i32::from).collect();
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+
+        let indices = LexicaseSelection::with_cases(&case_func).select_indices(
+            &[],
+            0,
+            &fitness_func,
+            &mut rng,
+        );
+        assert!(indices.is_empty());
+    }
+
+    #[test]
+    fn truncation_indices_are_best_of_pool() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+
+        let pool = individuals(&[4, -2, 7, 0, 3, -5]);
+
+        let mut indices =
+            TruncationSelection::default().select_indices(&pool, 3, &fitness_func, &mut rng);
+        indices.sort();
+
+        assert_eq!(indices, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn age_layers_get_equal_share_of_survivors() {
+        let mut rng = SeededRng::seed_from_u64(0);
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+        let mut selection = AgeLayeredSelection::with_layers(2, 3);
+
+        // Without old individuals, the old layer's share goes to the best young ones
+        let mut pool = individuals(&[4, -2, 7, 0, 3, -5]);
+        let mut indices = selection.select_indices(&pool, 4, &fitness_func, &mut rng);
+        indices.sort();
+        assert_eq!(indices, vec![1, 3, 4, 5]);
+
+        for idv in pool[..3].iter_mut() {
+            (0..5).for_each(|_| idv.grow_older());
+        }
+
+        let mut indices = selection.select_indices(&pool, 4, &fitness_func, &mut rng);
+        indices.sort();
+        assert_eq!(indices, vec![0, 1, 3, 5]);
+    }
+
+    #[test]
+    fn truncation_is_more_intense_than_binary_tournaments() {
+        const POPULATION_SIZE: usize = 1000;
+        let mut rng = SeededRng::seed_from_u64(0);
+
+        let truncation = selection_intensity(
+            &mut TruncationSelection::default(),
+            POPULATION_SIZE,
+            &mut rng,
+        );
+        let tournament = selection_intensity(
+            &mut TournamentSelection::new(2, true),
+            POPULATION_SIZE,
+            &mut rng,
+        );
+
+        // Keeping the better half of a normal distribution gives an intensity of about 0.8, and
+        // binary tournaments give 1 / sqrt(pi)
+        assert!((truncation - 0.8).abs() < 0.05, "{}", truncation);
+        assert!((tournament - 0.56).abs() < 0.05, "{}", tournament);
+        assert!(truncation > tournament);
 
-        assert!(population.len() == population_size)
+        // Truncation doubles the copies of the best individual every generation
+        let truncation = takeover_time(&mut TruncationSelection::default(), 64, 100, &mut rng);
+        assert_eq!(truncation, Some(6));
+        let tournament = takeover_time(&mut TournamentSelection::new(2, true), 64, 100, &mut rng);
+        assert!(tournament.unwrap() >= truncation.unwrap());
     }
 }
@@ -1,12 +1,30 @@
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    ops::{ControlFlow, Index},
+    slice::Iter,
+    sync::Arc,
+};
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
 
 use crate::{
-    fitness::{Fitness, FitnessFunc, OptimizationGoal},
-    gene::{Allele, Gene},
+    archive::SharedArchive,
+    distance::{DistanceMetric, GenotypeEquality},
+    fitness::{
+        bind_context, bind_failure_policy, bind_perturbation, BoundedFitness, EvaluationFunc,
+        FailurePolicy, FallibleEvaluationFunc, Fitness, FitnessCache, FitnessFunc, GenotypeCache,
+        OptimizationGoal, Perturbation,
+    },
+    gene::{Allele, Discrete, Gene},
     genome::Genome,
     genotype::Genotype,
     individual::Individual,
-    selection::SelectionOperator,
+    parallel::*,
+    population::Population,
+    progress::{NoProgress, ProgressReporter},
+    rng::{SeedStream, SeededRng},
+    selection::{CopyOffspringSelection, SelectionOperator, SelectionPressure},
+    statistics::{NoStats, StatsCollector},
     variation::VariationOperator,
 };
 
@@ -14,98 +32,716 @@ use crate::{
 pub enum Status {
     TargetReached(usize),
     BudgetReached(usize),
+    // All genotypes are identical and the variation operator cannot introduce new ones
+    Converged(usize),
+    // The generation callback stopped the run
+    StoppedByCallback(usize),
+    // The best individual satisfies the stop condition
+    ConditionMet(usize),
+}
+
+// The number of generations in a row without new evaluations after which a run is considered
+// converged, e.g. when every offspring is found in the cache because the search space is exhausted
+const STALLED_GENERATIONS: usize = 100;
+
+const DIVERSITY_WARNING: &str = "the variation operator does not mutate and the selection is elitist, so the population may lose its diversity and converge prematurely; consider adding mutation, a local search or comma selection";
+
+// A success condition on the best individual, which generalizes the fitness target
+pub type StopCondition<'a, Gnt, A, F> = dyn Fn(&Individual<Gnt, A, F>) -> bool + Send + Sync + 'a;
+
+// Improves a genotype in place, e.g. by hill climbing, evaluating candidates with the fitness
+// function
+pub type LocalSearch<'a, Gnt, A, F> =
+    dyn Fn(&mut Gnt, &FitnessFunc<'_, Gnt, A, F>) + Send + Sync + 'a;
+
+// How offspring benefit from local search: Lamarckian learning replaces an offspring by its
+// improved genotype, whereas Baldwinian learning only gives the offspring the improved fitness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Learning {
+    #[default]
+    Lamarckian,
+    Baldwinian,
 }
 
-pub struct SimpleGA<'a, Gnt, A, F, S, V>
+// Whether survivors are selected from the parents and offspring combined, (mu + lambda), or from
+// the offspring only, (mu, lambda). When the evaluation budget caps the last generation at fewer
+// offspring than mu (or failed offspring are skipped, see `FailurePolicy::Skip`), comma selection
+// falls back to plus selection for that generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    #[default]
+    Plus,
+    Comma,
+}
+
+pub struct SimpleGA<'a, Gnt, A, F, S, V, C = NoStats>
 where
     A: Allele,
     F: Fitness,
-    S: SelectionOperator,
+    S: SelectionOperator<Gnt, A, F>,
     V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
     Gnt: Genotype<A>,
 {
     // genome: Gnm,
-    population: Vec<Individual<Gnt, A, F>>,
+    population: Population<Gnt, A, F>,
     fitness_func: FitnessFunc<'a, Gnt, A, F>,
     selection_operator: S,
     variation_operator: V,
+    stats_collector: C,
     target_fitness: Option<F>,
+    target_fraction: Option<f64>,
+    stop_condition: Option<&'a StopCondition<'a, Gnt, A, F>>,
+    local_search: Option<(&'a LocalSearch<'a, Gnt, A, F>, f64)>,
+    learning: Learning,
+    lambda: Option<usize>,
+    selection_mode: SelectionMode,
+    seed: u64,
+    rng: SeededRng,
+    generation: usize,
+    force_elitism: bool,
+    streaming: bool,
+    best_ever: Option<Individual<Gnt, A, F>>,
+    best_history: Option<Vec<Gnt>>,
+    improvement_epsilon: f64,
+    // The best fitness that counted as an improvement, and the generations since
+    best_improved: Option<F>,
+    stagnant_generations: usize,
+    selection_pressure: Option<SelectionPressure>,
+    diversity_warning: Option<&'static str>,
 }
 
-impl<'a, Gnt, A, F, S, V> SimpleGA<'a, Gnt, A, F, S, V>
+impl<'a, Gnt, A, F, S, V, C> SimpleGA<'a, Gnt, A, F, S, V, C>
 where
     A: Allele,
     F: Fitness,
-    S: SelectionOperator,
+    S: SelectionOperator<Gnt, A, F>,
     V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
     Gnt: Genotype<A>,
 {
     pub fn best_individual(&self) -> Option<&Individual<Gnt, A, F>> {
-        self.population
-            .iter()
-            .min_by(|idv_a, idv_b| self.fitness_func.cmp(&idv_a.fitness(), &idv_b.fitness()))
+        self.population.best(&self.fitness_func)
     }
 
     pub fn worst_individual(&self) -> Option<&Individual<Gnt, A, F>> {
+        self.population.worst(&self.fitness_func)
+    }
+
+    // Adds the `count` best individuals of the population to a shared archive, locking it once
+    pub fn contribute_to<E>(&self, archive: &SharedArchive<Gnt, A, F, E>, count: usize)
+    where
+        E: GenotypeEquality<Gnt, A>,
+    {
+        let mut best: Vec<_> = self.population.iter().collect();
+        best.sort_by(|a, b| self.fitness_func.cmp(&a.fitness(), &b.fitness()));
+
+        archive.lock().unwrap().extend(best.into_iter().take(count));
+    }
+
+    // The best individual of each cluster of the population (see `Population::clusters`), from
+    // best to worst, as the distinct optima that were found
+    pub fn cluster_population<M>(
+        &self,
+        metric: &M,
+        distance_threshold: f64,
+    ) -> Vec<Individual<Gnt, A, F>>
+    where
+        M: DistanceMetric<Gnt, A>,
+    {
         self.population
-            .iter()
-            .max_by(|idv_a, idv_b| self.fitness_func.cmp(&idv_a.fitness(), &idv_b.fitness()))
+            .clusters(metric, distance_threshold, &self.fitness_func)
+            .into_iter()
+            .map(|cluster| cluster[0].clone())
+            .collect()
+    }
+
+    pub fn population(&self) -> &[Individual<Gnt, A, F>] {
+        &self.population
+    }
+
+    pub fn iter(&self) -> Iter<'_, Individual<Gnt, A, F>> {
+        self.population.iter()
+    }
+
+    pub fn stats(&self) -> &C {
+        &self.stats_collector
+    }
+
+    // The variation operator, e.g. to read its statistics after a run
+    pub fn variation_operator(&self) -> &V {
+        &self.variation_operator
+    }
+
+    pub fn mean_fitness(&self) -> f64 {
+        self.population.mean_fitness()
+    }
+
+    pub fn goal(&self) -> &OptimizationGoal {
+        self.fitness_func.goal()
+    }
+
+    pub fn fitness_func(&self) -> &FitnessFunc<'a, Gnt, A, F> {
+        &self.fitness_func
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.fitness_func.evaluations()
+    }
+
+    // The best genotype after each generation of the current run, if recording was enabled on the
+    // builder, e.g. to animate how the solution evolves
+    pub fn best_history(&self) -> &[Gnt] {
+        self.best_history.as_deref().unwrap_or_default()
+    }
+
+    // The margin by which the best fitness has to improve to count as progress
+    pub fn improvement_epsilon(&self) -> f64 {
+        self.improvement_epsilon
+    }
+
+    // Whether `new` improves on `old` by more than the improvement epsilon
+    pub fn is_improvement(&self, new: &F, old: &F) -> bool {
+        self.fitness_func
+            .is_improvement(new, old, self.improvement_epsilon)
+    }
+
+    // The number of generations since the best fitness last improved by more than the
+    // improvement epsilon. Improvements within the epsilon do not reset the count, but they do
+    // add up.
+    pub fn stagnant_generations(&self) -> usize {
+        self.stagnant_generations
+    }
+
+    // The warning issued when the GA was built, if it was enabled on the builder (see
+    // `SimpleGABuilder::warn_on_diversity_loss`)
+    pub fn diversity_warning(&self) -> Option<&'static str> {
+        self.diversity_warning
+    }
+
+    // How many individuals competed for the survivor slots in the last generation, e.g. to tune
+    // the offspring count or tournament size from a callback
+    pub fn selection_pressure(&self) -> Option<SelectionPressure> {
+        self.selection_pressure
+    }
+
+    // The seed of this run, either given to the builder or drawn from entropy; building again
+    // with the same seed replays the run exactly
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // The number of generations performed so far
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn target_reached(&self) -> bool {
+        let Some(target) = self.target_fitness else {
+            return false;
+        };
+
+        let at_target = |idv: &Individual<Gnt, A, F>| {
+            // TODO: does not check for approximate equality; may not work for floating points
+            self.fitness_func.cmp(&idv.fitness(), &target).is_le()
+        };
+
+        match self.target_fraction {
+            Some(fraction) => {
+                let required = (fraction * self.population.len() as f64).ceil() as usize;
+                self.iter().filter(|idv| at_target(idv)).count() >= required
+            }
+            None => self.best_individual().is_some_and(at_target),
+        }
+    }
+
+    pub fn condition_met(&self) -> bool {
+        match self.stop_condition {
+            Some(condition) => self.best_individual().is_some_and(condition),
+            None => false,
+        }
+    }
+
+    // Whether the population has fully converged while variation does not mutate, so that (as with
+    // EDAs and crossover) no new genotypes can be created anymore
+    pub fn converged(&self) -> bool {
+        !self.variation_operator.mutates() && self.population.is_converged()
+    }
+
+    // Evaluates the individuals of the population that have not been evaluated yet (e.g. of a
+    // population given to the builder), without running a generation. The evaluations count
+    // towards the budget of a later `run`. Returns the number of new evaluations.
+    pub fn evaluate_population(&mut self) -> usize {
+        self.fitness_func.evaluate_all(&mut self.population)
+    }
+
+    // Evaluates the individuals of the initial population that have not been evaluated yet, so
+    // that a population evaluated by `evaluate_population` (or given to the builder with its
+    // fitnesses) is not charged to the budget twice
+    pub fn initialize(&mut self) {
+        self.selection_operator.reset();
+
+        self.evaluate_population();
+
+        if self.force_elitism {
+            self.best_ever = self.best_individual().cloned();
+        }
+
+        if let Some(history) = &mut self.best_history {
+            history.clear();
+        }
+
+        self.best_improved = self.best_individual().map(|idv| idv.fitness());
+        self.stagnant_generations = 0;
+        self.selection_pressure = None;
+
+        self.stats_collector
+            .collect(&self.population, &self.fitness_func);
+    }
+
+    // Lets externally produced individuals (e.g. improved by a local search) compete with the
+    // population between generations: they are evaluated if needed, after which the selection
+    // operator selects as many survivors as the population holds from the population and the
+    // injected individuals combined. Even a generational operator (e.g. tournaments without the
+    // parents) lets them compete, rather than replacing the population by them.
+    pub fn inject(&mut self, mut individuals: Vec<Individual<Gnt, A, F>>) {
+        assert!(
+            !self.selection_operator.copies_offspring(),
+            "cannot inject individuals when the selection operator replaces the population"
+        );
+
+        self.fitness_func.evaluate_all(&mut individuals);
+
+        let count = self.population.len();
+        self.population.extend(individuals);
+        let indices = self.selection_operator.select_indices(
+            &self.population,
+            count,
+            &self.fitness_func,
+            &mut self.rng,
+        );
+
+        let survivors: Vec<_> = indices
+            .into_iter()
+            .map(|idx| self.population[idx].clone())
+            .collect();
+        self.population = survivors.into();
+
+        if self.force_elitism {
+            self.preserve_best();
+        }
+    }
+
+    // Performs a single generation of variation and selection
+    pub fn step(&mut self) {
+        self.step_within(None)
+    }
+
+    // Performs a single generation, creating at most `max_new_evaluations` offspring if given
+    pub fn step_within(&mut self, max_new_evaluations: Option<usize>) {
+        // Every generation draws from its own generator, derived from the seed of the run
+        self.rng = SeedStream::new(self.seed).generation(self.generation).rng();
+        self.generation += 1;
+
+        // The parents grow older, so that the ones that survive are one generation older than
+        // the offspring they compete with
+        self.population.iter_mut().for_each(Individual::grow_older);
+
+        // Copying selection keeps all offspring anyway, and needs the parents to fill the places
+        // of missing (e.g. skipped) offspring
+        if self.streaming
+            && self.selection_mode == SelectionMode::Plus
+            && self.local_search.is_none()
+            && !self.selection_operator.copies_offspring()
+        {
+            // The offspring are selected as they are produced, so the parents cannot be borrowed
+            // from the population
+            let parents = self
+                .lambda_parents()
+                .unwrap_or_else(|| self.population.to_vec());
+
+            let offspring = self.variation_operator.create_offspring_iter(
+                &parents,
+                &self.fitness_func,
+                max_new_evaluations,
+                &mut self.rng,
+            );
+
+            let parents = self.population.len();
+            let mut created = 0;
+            self.selection_operator.select_iter(
+                &mut self.population,
+                offspring
+                    .filter(|idv| !idv.is_skipped())
+                    .inspect(|_| created += 1),
+                &self.fitness_func,
+                &mut self.rng,
+            );
+            self.record_selection_pressure(self.selection_operator.pool_size(parents, created));
+        } else {
+            self.vary_and_select(max_new_evaluations);
+        }
+
+        if self.force_elitism {
+            self.preserve_best();
+        }
+
+        self.stats_collector
+            .collect(&self.population, &self.fitness_func);
+
+        if let Some(history) = &mut self.best_history {
+            if let Some(best) = self.population.best(&self.fitness_func) {
+                history.push(best.genotype().clone());
+            }
+        }
+
+        let best = self.best_individual().map(|idv| idv.fitness());
+        match (best, self.best_improved) {
+            (Some(new), Some(old)) if !self.is_improvement(&new, &old) => {
+                self.stagnant_generations += 1;
+            }
+            _ => {
+                self.best_improved = best;
+                self.stagnant_generations = 0;
+            }
+        }
+    }
+
+    fn vary_and_select(&mut self, max_new_evaluations: Option<usize>) {
+        // Perform variation
+        let mut offspring = match self.lambda_parents() {
+            Some(parents) => self.variation_operator.create_offspring(
+                &parents,
+                &self.fitness_func,
+                max_new_evaluations,
+                &mut self.rng,
+            ),
+            None => self.variation_operator.create_offspring(
+                &self.population,
+                &self.fitness_func,
+                max_new_evaluations,
+                &mut self.rng,
+            ),
+        };
+        self.improve(&mut offspring);
+        offspring.retain(|idv| !idv.is_skipped());
+
+        // Perform selection
+        let mu = self.population.len();
+        let pool_size = match self.selection_mode {
+            SelectionMode::Comma if offspring.len() >= mu => offspring.len(),
+            _ if self.selection_operator.copies_offspring() && offspring.len() < mu => {
+                mu + offspring.len()
+            }
+            _ => self.selection_operator.pool_size(mu, offspring.len()),
+        };
+
+        match self.selection_mode {
+            // A capped generation (or one whose failed offspring were skipped) may not produce
+            // enough offspring to replace all parents, in which case the parents compete with them
+            // as under plus selection
+            SelectionMode::Plus | SelectionMode::Comma if offspring.len() < mu => {
+                if self.selection_operator.copies_offspring() {
+                    // Copying selection would shrink the population to the offspring, so the
+                    // last parents keep the places that the offspring cannot fill
+                    let mut pool = std::mem::take(&mut *self.population);
+                    pool.extend(offspring);
+                    let indices = self.selection_operator.select_indices(
+                        &pool,
+                        mu,
+                        &self.fitness_func,
+                        &mut self.rng,
+                    );
+                    self.population
+                        .extend(indices.into_iter().map(|idx| pool[idx].clone()));
+                } else {
+                    self.selection_operator.select(
+                        &mut self.population,
+                        offspring,
+                        &self.fitness_func,
+                        &mut self.rng,
+                    );
+                }
+            }
+            SelectionMode::Plus => {
+                self.selection_operator.select(
+                    &mut self.population,
+                    offspring,
+                    &self.fitness_func,
+                    &mut self.rng,
+                );
+            }
+            SelectionMode::Comma => {
+                // The parents are discarded, so the operator selects mu survivors among the
+                // offspring alone
+                let indices = self.selection_operator.select_indices(
+                    &offspring,
+                    mu,
+                    &self.fitness_func,
+                    &mut self.rng,
+                );
+
+                self.population.clear();
+                self.population
+                    .extend(indices.into_iter().map(|idx| offspring[idx].clone()));
+            }
+        }
+
+        self.record_selection_pressure(pool_size);
+    }
+
+    fn record_selection_pressure(&mut self, pool_size: usize) {
+        self.selection_pressure = Some(SelectionPressure::new(
+            pool_size,
+            self.population.len(),
+            self.selection_operator.participations(),
+        ));
+    }
+
+    // Applies the local search to each offspring with the configured probability. Its evaluations
+    // count towards the budget, but are not capped by it.
+    fn improve(&mut self, offspring: &mut [Individual<Gnt, A, F>]) {
+        let Some((local_search, fraction)) = self.local_search else {
+            return;
+        };
+
+        let chosen: Vec<bool> = (0..offspring.len())
+            .map(|_| self.rng.gen_bool(fraction))
+            .collect();
+
+        offspring
+            .into_par_iter()
+            .zip(chosen)
+            .filter(|(_, chosen)| *chosen)
+            .for_each(|(child, _)| {
+                let mut genotype = child.genotype().clone();
+                local_search(&mut genotype, &self.fitness_func);
+
+                match self.learning {
+                    Learning::Lamarckian => {
+                        *child = Individual::from_genotype(genotype);
+                        self.fitness_func.evaluate(child);
+                    }
+                    Learning::Baldwinian => {
+                        child.set_fitness(self.fitness_func.evaluate_genotype(&genotype));
+                    }
+                }
+            });
+    }
+
+    // The parents of the offspring when lambda differs from the population size
+    fn lambda_parents(&mut self) -> Option<Vec<Individual<Gnt, A, F>>> {
+        match self.lambda {
+            Some(lambda) if lambda != self.population.len() => {
+                // Spread the lambda parent slots evenly over the (shuffled) population
+                let mut parents = self.population.to_vec();
+                parents.shuffle(&mut self.rng);
+
+                Some(parents.into_iter().cycle().take(lambda).collect())
+            }
+            _ => None,
+        }
+    }
+
+    // Puts the best individual found so far back in place of the worst one, if selection dropped
+    // it, and records any improvement
+    fn preserve_best(&mut self) {
+        if let Some(best) = &self.best_ever {
+            let kept = self.best_individual().is_some_and(|idv| {
+                self.fitness_func
+                    .cmp(&idv.fitness(), &best.fitness())
+                    .is_le()
+            });
+
+            if !kept {
+                let worst = self
+                    .population
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, idv_a), (_, idv_b)| {
+                        self.fitness_func.cmp(&idv_a.fitness(), &idv_b.fitness())
+                    })
+                    .map(|(idx, _)| idx);
+
+                match worst {
+                    Some(idx) => self.population[idx] = best.clone(),
+                    None => self.population.push(best.clone()),
+                }
+            }
+        }
+
+        self.best_ever = self.best_individual().cloned();
     }
 
     pub fn run(&mut self, evaluation_budget: usize) -> Status {
-        // Perform initial evaluation
-        self.population.iter_mut().for_each(|idv| {
-            self.fitness_func.evaluate(idv);
+        self.run_with_progress(evaluation_budget, &mut NoProgress)
+    }
+
+    pub fn run_with_progress<P: ProgressReporter>(
+        &mut self,
+        evaluation_budget: usize,
+        reporter: &mut P,
+    ) -> Status {
+        let status = self.run_reporting(evaluation_budget, reporter, &mut |_| {
+            ControlFlow::Continue(())
         });
+        reporter.finish();
+        status
+    }
+
+    // Runs as `run`, calling the callback after every generation. The run stops as soon as the
+    // callback breaks, e.g. when an external condition is met.
+    pub fn run_with_callback<Cb>(&mut self, evaluation_budget: usize, mut callback: Cb) -> Status
+    where
+        Cb: FnMut(&Self) -> ControlFlow<()>,
+    {
+        self.run_reporting(evaluation_budget, &mut NoProgress, &mut callback)
+    }
+
+    fn run_reporting<P, Cb>(
+        &mut self,
+        evaluation_budget: usize,
+        reporter: &mut P,
+        callback: &mut Cb,
+    ) -> Status
+    where
+        P: ProgressReporter,
+        Cb: FnMut(&Self) -> ControlFlow<()>,
+    {
+        self.initialize();
+
+        let mut stalled = 0;
 
         // Main loop
         while self.fitness_func.evaluations() < evaluation_budget {
             // Check if target fitness is reached
-            if let Some(target) = self.target_fitness {
-                if let Some(idv) = self.best_individual() {
-                    // TODO: does not check for approximate equality; may not work for floating points
-                    if self.fitness_func.cmp(&idv.fitness(), &target).is_le() {
-                        return Status::TargetReached(self.fitness_func.evaluations());
-                    }
-                }
+            if self.target_reached() {
+                return Status::TargetReached(self.fitness_func.evaluations());
+            }
+
+            if self.condition_met() {
+                return Status::ConditionMet(self.fitness_func.evaluations());
+            }
+
+            if self.converged() {
+                return Status::Converged(self.fitness_func.evaluations());
+            }
+
+            // Never exceed the budget in the final generation
+            let affordable = (evaluation_budget - self.fitness_func.evaluations())
+                / self.fitness_func.evaluation_cost();
+            if affordable == 0 {
+                break;
             }
 
-            // Perform variation
-            let offspring = self
-                .variation_operator
-                .create_offspring(&self.population, &self.fitness_func);
+            let evaluations = self.fitness_func.evaluations();
+            let lookups = self.fitness_func.total_lookups();
+            self.step_within(Some(affordable));
+            reporter.report(self.fitness_func.evaluations(), evaluation_budget);
+
+            if callback(self).is_break() {
+                return Status::StoppedByCallback(self.fitness_func.evaluations());
+            }
 
-            // Perform selection
-            self.selection_operator
-                .select(&mut self.population, offspring, &self.fitness_func);
+            // Without any new offspring to evaluate (e.g. without variation), the budget would
+            // never run out, and no generation can introduce new genotypes. Offspring that are
+            // all found in the cache do not use up the budget either, but a later generation may
+            // still find new genotypes.
+            if self.fitness_func.evaluations() == evaluations {
+                stalled += 1;
+            } else {
+                stalled = 0;
+            }
 
-            // println!(
-            //     "Best fitness: {:?}",
-            //     self.best_individual().unwrap().fitness()
-            // )
+            if self.fitness_func.total_lookups() == lookups || stalled == STALLED_GENERATIONS {
+                return Status::Converged(self.fitness_func.evaluations());
+            }
         }
 
         Status::BudgetReached(self.fitness_func.evaluations())
     }
 }
 
+impl<'a, 'b, Gnt, A, F, S, V, C> IntoIterator for &'b SimpleGA<'a, Gnt, A, F, S, V, C>
+where
+    A: Allele,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    type Item = &'b Individual<Gnt, A, F>;
+
+    type IntoIter = Iter<'b, Individual<Gnt, A, F>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.population.iter()
+    }
+}
+
+impl<'a, Gnt, A, F, S, V, C> Index<usize> for SimpleGA<'a, Gnt, A, F, S, V, C>
+where
+    A: Allele,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    type Output = Individual<Gnt, A, F>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.population[index]
+    }
+}
+
+type NewCache<'a, Gnt, F> = fn() -> Box<dyn FitnessCache<Gnt, F> + 'a>;
+
+// A fallible evaluation function with its failure policy and the worst fitness under a goal
+type Fallible<'a, Gnt, F> = (
+    &'a FallibleEvaluationFunc<'a, Gnt, F>,
+    FailurePolicy,
+    fn(&OptimizationGoal) -> F,
+);
+
 #[derive(Clone)]
-pub struct SimpleGABuilder<'a, Gnt, A, G, F, S, V>
+pub struct SimpleGABuilder<'a, Gnt, A, G, F, S, V, C = NoStats>
 where
     A: Allele,
     G: Gene<A>,
     F: Fitness,
-    S: SelectionOperator,
+    S: SelectionOperator<Gnt, A, F>,
     V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
     Gnt: Genotype<A>,
 {
     genome: Option<&'a Genome<Gnt, A, G>>,
     population: Option<Vec<Individual<Gnt, A, F>>>,
-    evaluation_func: Option<&'a (dyn Fn(&Gnt) -> F + Send + Sync)>,
+    population_size: Option<usize>,
+    evaluation_func: Option<Arc<EvaluationFunc<'a, Gnt, F>>>,
+    fallible_evaluation_func: Option<Fallible<'a, Gnt, F>>,
+    robust_evaluation: Option<(&'a Perturbation<'a, Gnt>, usize)>,
     goal: OptimizationGoal,
     selection_operator: Option<S>,
     variation_operator: Option<V>,
+    stats_collector: C,
     target_fitness: Option<F>,
+    target_fraction: Option<f64>,
+    stop_condition: Option<&'a StopCondition<'a, Gnt, A, F>>,
+    local_search: Option<(&'a LocalSearch<'a, Gnt, A, F>, f64)>,
+    learning: Learning,
+    mu: Option<usize>,
+    lambda: Option<usize>,
+    selection_mode: SelectionMode,
+    seed: Option<u64>,
+    force_elitism: bool,
+    streaming: bool,
+    record_best_history: bool,
+    warn_on_diversity_loss: bool,
+    improvement_epsilon: Option<f64>,
+    // Creates a fresh cache for every GA that is built, so that runs do not share fitnesses
+    new_cache: Option<NewCache<'a, Gnt, F>>,
 }
 
 impl<'a, Gnt, A, G, F, S, V> SimpleGABuilder<'a, Gnt, A, G, F, S, V>
@@ -113,7 +749,7 @@ where
     A: Allele,
     G: Gene<A>,
     F: Fitness,
-    S: SelectionOperator,
+    S: SelectionOperator<Gnt, A, F>,
     V: VariationOperator<Gnt, A, F>,
     Gnt: Genotype<A>,
 {
@@ -121,42 +757,140 @@ where
         Self {
             genome: None,
             population: None,
+            population_size: None,
             evaluation_func: None,
+            fallible_evaluation_func: None,
+            robust_evaluation: None,
             goal: OptimizationGoal::Minimize,
             selection_operator: None,
             variation_operator: None,
+            stats_collector: NoStats,
             target_fitness: None,
+            target_fraction: None,
+            stop_condition: None,
+            local_search: None,
+            learning: Learning::Lamarckian,
+            mu: None,
+            lambda: None,
+            selection_mode: SelectionMode::Plus,
+            seed: None,
+            force_elitism: false,
+            streaming: false,
+            record_best_history: false,
+            warn_on_diversity_loss: false,
+            improvement_epsilon: None,
+            new_cache: None,
         }
     }
+}
+
+impl<'a, Gnt, A, G, F, V> SimpleGABuilder<'a, Gnt, A, G, F, CopyOffspringSelection, V>
+where
+    A: Allele,
+    G: Gene<A>,
+    F: Fitness,
+    V: VariationOperator<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    // For operators that select their own offspring (such as optimal mixing), where selection
+    // reduces to copying those offspring into the population
+    pub fn with_optimal_mixing(operator: V) -> Self {
+        Self::new()
+            .selection(CopyOffspringSelection)
+            .variation(operator)
+    }
+}
 
+impl<'a, Gnt, A, G, F, S, V, C> SimpleGABuilder<'a, Gnt, A, G, F, S, V, C>
+where
+    A: Allele,
+    G: Gene<A>,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
     pub fn genome(mut self, genome: &'a Genome<Gnt, A, G>) -> Self {
         self.genome = Some(genome);
         self
     }
 
-    pub fn random_population(mut self, size: usize) -> Self {
-        let Some(genome) = self.genome else {
-            panic!("Failed to initialize population: the genome must be defined before the population can be initialized");
-        };
+    // Starts from the given individuals (e.g. the merged populations of earlier runs), which are
+    // evaluated when the run starts
+    pub fn population(mut self, population: Vec<Individual<Gnt, A, F>>) -> Self {
+        assert!(!population.is_empty(), "the population must not be empty");
 
-        let mut rng = rand::thread_rng();
+        self.population = Some(population);
+        self.population_size = None;
 
-        let population = (0..size)
-            .map(|_| Individual::sample_uniform(&mut rng, genome))
-            .collect();
+        self
+    }
 
-        self.population = Some(population);
+    // The population is sampled when the GA is built, so that it depends on the seed
+    pub fn random_population(mut self, size: usize) -> Self {
+        if self.genome.is_none() {
+            panic!("Failed to initialize population: the genome must be defined before the population can be initialized");
+        }
+
+        self.population = None;
+        self.population_size = Some(size);
 
         self
     }
 
     pub fn goal(mut self, goal: OptimizationGoal) -> Self {
+        F::check_goal(&goal);
         self.goal = goal;
         self
     }
 
     pub fn evaluation_function(mut self, func: &'a (dyn Fn(&Gnt) -> F + Send + Sync)) -> Self {
-        self.evaluation_func = Some(func);
+        self.evaluation_func = Some(Arc::new(func));
+        self.fallible_evaluation_func = None;
+        self
+    }
+
+    // An evaluation function that may fail for some genotypes, with the policy to apply when it does
+    pub fn fallible_evaluation(
+        mut self,
+        func: &'a FallibleEvaluationFunc<'a, Gnt, F>,
+        policy: FailurePolicy,
+    ) -> Self
+    where
+        F: BoundedFitness,
+    {
+        self.fallible_evaluation_func = Some((func, policy, F::worst));
+        self.evaluation_func = None;
+        self
+    }
+
+    pub fn evaluation_function_with_context<Ctx>(
+        mut self,
+        func: &'a (dyn Fn(&Gnt, &Ctx) -> F + Send + Sync),
+        context: &'a Ctx,
+    ) -> Self
+    where
+        Ctx: Sync,
+    {
+        self.evaluation_func = Some(bind_context(func, context));
+        self.fallible_evaluation_func = None;
+        self
+    }
+
+    // Evaluates each genotype as its average fitness over `samples` perturbed copies (see
+    // `bind_perturbation`), where every sample counts as an evaluation
+    pub fn robust_evaluation(
+        mut self,
+        perturbation: &'a Perturbation<'a, Gnt>,
+        samples: usize,
+    ) -> Self {
+        assert!(samples > 0, "at least one perturbed sample is required");
+        assert!(
+            F::mean(&[F::default()]).is_some(),
+            "robust evaluation requires a fitness type that can be averaged"
+        );
+        self.robust_evaluation = Some((perturbation, samples));
         self
     }
 
@@ -175,33 +909,1406 @@ where
         self
     }
 
-    pub fn build(self) -> SimpleGA<'a, Gnt, A, F, S, V> {
-        let Some(population) = self.population else {
-            panic!("Failed to build: population not initialized");
-        };
+    // Only consider the target reached once this fraction of the population has reached it,
+    // rather than the best individual only (e.g. to study takeover times)
+    pub fn stop_when_fraction_at_target(mut self, fraction: f64) -> Self {
+        assert!(
+            fraction > 0.0 && fraction <= 1.0,
+            "the fraction of the population must be in (0, 1]"
+        );
+        self.target_fraction = Some(fraction);
+        self
+    }
 
-        let Some(evaluation_func) = self.evaluation_func else {
-            panic!("Failed to build: evaluation function not specified");
-        };
+    // Improves a fraction of the offspring of every generation with a local search before
+    // selection, which makes the GA memetic (see `learning`). Streaming is disabled with a local
+    // search.
+    pub fn local_search(
+        mut self,
+        local_search: &'a LocalSearch<'a, Gnt, A, F>,
+        fraction: f64,
+    ) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "the fraction of improved offspring must be in [0, 1]"
+        );
+        self.local_search = Some((local_search, fraction));
+        self
+    }
 
-        let fitness_func = FitnessFunc::new(evaluation_func, self.goal);
+    pub fn learning(mut self, learning: Learning) -> Self {
+        self.learning = learning;
+        self
+    }
 
-        let Some(selection_operator) = self.selection_operator else {
-            panic!("Failed to build: selection operator not specified");
-        };
+    // Stops the run once the best individual satisfies an arbitrary condition (e.g. on its
+    // genotype), in addition to the fitness target if one is set
+    pub fn stop_when(mut self, condition: &'a StopCondition<'a, Gnt, A, F>) -> Self {
+        self.stop_condition = Some(condition);
+        self
+    }
 
-        let Some(variation_operator) = self.variation_operator else {
-            panic!("Failed to build: variation operator not specified");
-        };
+    // The number of parents; a random population of this size is created if none was given
+    pub fn mu(mut self, mu: usize) -> Self {
+        self.mu = Some(mu);
+        self
+    }
 
-        let target_fitness = self.target_fitness;
+    // The number of offspring created each generation; defaults to the population size
+    pub fn lambda(mut self, lambda: usize) -> Self {
+        self.lambda = Some(lambda);
+        self
+    }
 
-        SimpleGA {
-            population,
-            fitness_func,
-            selection_operator,
-            variation_operator,
-            target_fitness,
-        }
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.selection_mode = mode;
+        self
+    }
+
+    // Guarantees that the best individual found so far survives selection, whatever the
+    // selection operator
+    pub fn force_elitism(mut self, force_elitism: bool) -> Self {
+        self.force_elitism = force_elitism;
+        self
+    }
+
+    // Lets selection consume offspring while they are being created, rather than after the whole
+    // generation has been evaluated. Only applies to plus selection by an operator that does not
+    // copy the offspring.
+    pub fn streaming(mut self, streaming: bool) -> Self {
+        self.streaming = streaming;
+        self
+    }
+
+    // Records a copy of the best genotype after every generation (see `SimpleGA::best_history`)
+    pub fn record_best_history(mut self, record_best_history: bool) -> Self {
+        self.record_best_history = record_best_history;
+        self
+    }
+
+    // Checks, when building, whether the variation does not mutate (e.g. an EDA or plain
+    // crossover) while the selection is elitist, without another source of diversity such as a
+    // local search or comma selection. Such a GA often converges prematurely and stalls. The
+    // warning is left to the caller to report (see `SimpleGA::diversity_warning`).
+    pub fn warn_on_diversity_loss(mut self, warn: bool) -> Self {
+        self.warn_on_diversity_loss = warn;
+        self
+    }
+
+    // The margin by which the best fitness has to improve to count as progress when detecting
+    // stagnation (see `SimpleGA::stagnant_generations`). Defaults to
+    // `Fitness::IMPROVEMENT_EPSILON`, which is zero for integers and tiny for floats.
+    pub fn improvement_epsilon(mut self, epsilon: f64) -> Self {
+        assert!(
+            epsilon >= 0.0,
+            "the improvement epsilon must be non-negative"
+        );
+        self.improvement_epsilon = Some(epsilon);
+        self
+    }
+
+    // Seeds the random number generator of the run; a seed is drawn from entropy if omitted
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    // Replaces the seed by the seed of the `index`th of several runs built from this builder (e.g.
+    // restarts), so that the runs differ but remain reproducible. Without a seed, every run draws
+    // its own seed anyway.
+    pub fn derive_seed(mut self, index: u64) -> Self {
+        self.seed = self
+            .seed
+            .map(|seed| SeedStream::new(seed).derive(index).seed());
+        self
+    }
+
+    pub fn stats<C2>(self, collector: C2) -> SimpleGABuilder<'a, Gnt, A, G, F, S, V, C2>
+    where
+        C2: StatsCollector<Gnt, A, F>,
+    {
+        SimpleGABuilder {
+            genome: self.genome,
+            population: self.population,
+            population_size: self.population_size,
+            evaluation_func: self.evaluation_func,
+            fallible_evaluation_func: self.fallible_evaluation_func,
+            robust_evaluation: self.robust_evaluation,
+            goal: self.goal,
+            selection_operator: self.selection_operator,
+            variation_operator: self.variation_operator,
+            stats_collector: collector,
+            target_fitness: self.target_fitness,
+            target_fraction: self.target_fraction,
+            stop_condition: self.stop_condition,
+            local_search: self.local_search,
+            learning: self.learning,
+            mu: self.mu,
+            lambda: self.lambda,
+            selection_mode: self.selection_mode,
+            seed: self.seed,
+            force_elitism: self.force_elitism,
+            streaming: self.streaming,
+            record_best_history: self.record_best_history,
+            warn_on_diversity_loss: self.warn_on_diversity_loss,
+            improvement_epsilon: self.improvement_epsilon,
+            new_cache: self.new_cache,
+        }
+    }
+
+    pub fn build(self) -> SimpleGA<'a, Gnt, A, F, S, V, C> {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let mut rng = SeededRng::seed_from_u64(seed);
+
+        let population = match (self.population, self.population_size.or(self.mu)) {
+            (Some(population), _) => population,
+            (None, Some(size)) => {
+                let Some(genome) = self.genome else {
+                    panic!(
+                        "Failed to build: the genome must be defined to create a random population"
+                    );
+                };
+
+                genome
+                    .random_genotype_batch(&mut rng, size)
+                    .into_iter()
+                    .map(Individual::from_genotype)
+                    .collect()
+            }
+            (None, None) => panic!("Failed to build: population not initialized"),
+        };
+
+        if let Some(mu) = self.mu {
+            assert_eq!(
+                population.len(),
+                mu,
+                "Failed to build: population size does not match mu"
+            );
+        }
+
+        if self.selection_mode == SelectionMode::Comma {
+            assert!(
+                self.lambda.unwrap_or(population.len()) >= population.len(),
+                "Failed to build: comma selection requires lambda to be at least mu"
+            );
+        }
+
+        // The worst fitness assigned to failed evaluations depends on the goal, so the failure
+        // policy is only bound once the goal is final
+        let evaluation_func = match self.fallible_evaluation_func {
+            Some((func, policy, worst)) => {
+                Some(bind_failure_policy(func, policy, worst(&self.goal)))
+            }
+            None => self.evaluation_func,
+        };
+
+        let Some(evaluation_func) = evaluation_func else {
+            panic!("Failed to build: evaluation function not specified");
+        };
+
+        let mut fitness_func = match self.robust_evaluation {
+            Some((perturbation, samples)) => FitnessFunc::from_shared(
+                bind_perturbation(evaluation_func, perturbation, samples, rng.gen()),
+                self.goal,
+            )
+            .with_evaluation_cost(samples),
+            None => FitnessFunc::from_shared(evaluation_func, self.goal),
+        };
+        if let Some(new_cache) = self.new_cache {
+            fitness_func = fitness_func.with_cache(new_cache());
+        }
+
+        let Some(selection_operator) = self.selection_operator else {
+            panic!("Failed to build: selection operator not specified");
+        };
+
+        let Some(variation_operator) = self.variation_operator else {
+            panic!("Failed to build: variation operator not specified");
+        };
+
+        // Copying selection makes the offspring the next population, which would grow or shrink
+        // every generation
+        if selection_operator.copies_offspring() && variation_operator.offspring_ratio() != 1.0 {
+            panic!("Failed to build: a generation gap other than 1 changes the population size under copying selection (use a selection operator that selects survivors)");
+        }
+
+        if selection_operator.copies_offspring()
+            && variation_operator
+                .fixed_offspring_count()
+                .is_some_and(|count| count != population.len())
+        {
+            panic!("Failed to build: the variation operator creates a fixed number of offspring that differs from the population size, which changes it under copying selection (use a selection operator that selects survivors)");
+        }
+
+        if variation_operator.selects_offspring() && !selection_operator.copies_offspring() {
+            panic!("Failed to build: the variation operator selects its own offspring, so the selection operator must copy them (use CopyOffspringSelection)");
+        }
+
+        let target_fitness = self.target_fitness;
+
+        let elitist = self.force_elitism
+            || (self.selection_mode == SelectionMode::Plus && selection_operator.is_elitist());
+        let diversity_warning = (self.warn_on_diversity_loss
+            && elitist
+            && !variation_operator.mutates()
+            && self.local_search.is_none())
+        .then_some(DIVERSITY_WARNING);
+
+        SimpleGA {
+            population: population.into(),
+            fitness_func,
+            selection_operator,
+            variation_operator,
+            stats_collector: self.stats_collector,
+            target_fitness,
+            target_fraction: self.target_fraction,
+            stop_condition: self.stop_condition,
+            local_search: self.local_search,
+            learning: self.learning,
+            lambda: self.lambda,
+            selection_mode: self.selection_mode,
+            seed,
+            rng,
+            generation: 0,
+            force_elitism: self.force_elitism,
+            streaming: self.streaming,
+            best_ever: None,
+            best_history: self.record_best_history.then(Vec::new),
+            improvement_epsilon: self.improvement_epsilon.unwrap_or(F::IMPROVEMENT_EPSILON),
+            best_improved: None,
+            stagnant_generations: 0,
+            selection_pressure: None,
+            diversity_warning,
+        }
+    }
+}
+
+impl<'a, Gnt, A, G, F, S, V, C> SimpleGABuilder<'a, Gnt, A, G, F, S, V, C>
+where
+    A: Allele + Discrete,
+    G: Gene<A>,
+    F: Fitness,
+    S: SelectionOperator<Gnt, A, F>,
+    V: VariationOperator<Gnt, A, F>,
+    C: StatsCollector<Gnt, A, F>,
+    Gnt: Genotype<A>,
+{
+    // Caches the fitness of every evaluated genotype, so that duplicate genotypes are not
+    // evaluated again. Cache hits do not count towards the evaluation budget. The cache is
+    // unbounded (see `GenotypeCache`).
+    pub fn cache_evaluations(mut self) -> Self {
+        self.new_cache = Some(|| Box::new(GenotypeCache::<A, F>::default()));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fitness::EvalError,
+        gene::{BoolDomain, DiscreteDomain, DisjointIntegralDomain},
+        idom,
+        selection::{RouletteWheelSelection, TournamentSelection, TruncationSelection},
+        variation::{NoVariation, Umda, UniformCrossover},
+    };
+
+    const N: usize = 16;
+    type Gnt = [bool; N];
+
+    fn one_max(genotype: &Gnt) -> usize {
+        genotype.iter().filter(|bit| *bit).count()
+    }
+
+    // Replaces every parent by the worst possible offspring
+    #[derive(Clone)]
+    struct ClearAll;
+
+    impl VariationOperator<Gnt, bool, usize> for ClearAll {
+        fn create_offspring(
+            &mut self,
+            population: &[Individual<Gnt, bool, usize>],
+            fitness_func: &FitnessFunc<'_, Gnt, bool, usize>,
+            max_new_evaluations: Option<usize>,
+            _rng: &mut SeededRng,
+        ) -> Vec<Individual<Gnt, bool, usize>> {
+            population
+                .iter()
+                .take(max_new_evaluations.unwrap_or(usize::MAX))
+                .map(|_| {
+                    let mut child = Individual::from_genotype([false; N]);
+                    fitness_func.evaluate(&mut child);
+                    child
+                })
+                .collect()
+        }
+
+        fn mutates(&self) -> bool {
+            true
+        }
+    }
+
+    fn best_fitness_after_step(mode: SelectionMode) -> (usize, usize) {
+        const MU: usize = 10;
+        const LAMBDA: usize = 30;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .mu(MU)
+            .lambda(LAMBDA)
+            .selection_mode(mode)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(ClearAll)
+            .build();
+
+        ga.initialize();
+        let before = ga.best_individual().unwrap().fitness();
+
+        ga.step();
+        assert_eq!(ga.population().len(), MU);
+        assert_eq!(ga.evaluations(), MU + LAMBDA);
+
+        (before, ga.best_individual().unwrap().fitness())
+    }
+
+    #[test]
+    fn reported_seed_replays_the_run() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default());
+
+        let mut ga = builder.clone().build();
+        ga.run(200);
+
+        let mut replay = builder.seed(ga.seed()).build();
+        replay.run(200);
+
+        assert_eq!(replay.seed(), ga.seed());
+        assert_eq!(
+            replay.best_individual().unwrap().genotype(),
+            ga.best_individual().unwrap().genotype()
+        );
+        for (idv, replayed) in ga.iter().zip(replay.iter()) {
+            assert_eq!(idv.genotype(), replayed.genotype());
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn seeded_run_does_not_depend_on_thread_count() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let run_with_threads = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+
+            pool.install(|| {
+                let mut ga = SimpleGABuilder::new()
+                    .genome(&genome)
+                    .random_population(50)
+                    .evaluation_function(&one_max)
+                    .goal(OptimizationGoal::Maximize)
+                    .selection(TournamentSelection::new(4, true))
+                    .variation(UniformCrossover::default())
+                    .seed(7)
+                    .build();
+                ga.initialize();
+
+                for _ in 0..10 {
+                    ga.step();
+                }
+
+                ga.iter()
+                    .map(|idv| (*idv.genotype(), idv.fitness()))
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(run_with_threads(1), run_with_threads(4));
+    }
+
+    // Truncation selection that counts the generations it selected in during the current run
+    #[derive(Clone, Default)]
+    struct CountingSelection {
+        generations: usize,
+    }
+
+    impl SelectionOperator<Gnt, bool, usize> for CountingSelection {
+        fn reset(&mut self) {
+            self.generations = 0;
+        }
+
+        fn select_indices(
+            &mut self,
+            pool: &[Individual<Gnt, bool, usize>],
+            count: usize,
+            fitness_func: &FitnessFunc<'_, Gnt, bool, usize>,
+            rng: &mut SeededRng,
+        ) -> Vec<usize> {
+            self.generations += 1;
+            TruncationSelection::default().select_indices(pool, count, fitness_func, rng)
+        }
+    }
+
+    #[test]
+    fn selection_state_persists_within_a_run() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(CountingSelection::default())
+            .variation(UniformCrossover::default())
+            .seed(0)
+            .build();
+
+        ga.run(20 + 5 * 20);
+        assert_eq!(ga.selection_operator.generations, 5);
+
+        // The evaluated population carries over, so the next run only evaluates offspring
+        ga.run(ga.evaluations() + 3 * 20);
+        assert_eq!(ga.selection_operator.generations, 3);
+    }
+
+    #[test]
+    fn surviving_parents_grow_older() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        // The offspring are no better than any parent, so all parents survive
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(10)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(ClearAll)
+            .seed(0)
+            .build();
+        ga.initialize();
+        assert!(ga.iter().all(|idv| idv.age() == 0));
+
+        for generation in 1..=3 {
+            ga.step();
+            assert!(ga.iter().all(|idv| idv.age() == generation));
+        }
+
+        // The offspring replace the parents
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(10)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(CopyOffspringSelection)
+            .variation(ClearAll)
+            .build();
+        ga.initialize();
+        ga.step();
+        assert!(ga.iter().all(|idv| idv.age() == 0));
+    }
+
+    #[test]
+    fn run_stops_when_fraction_of_population_at_target() {
+        const POPULATION_SIZE: usize = 40;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut evaluations = Vec::new();
+        for fraction in [0.25, 0.5, 1.0] {
+            let mut ga = SimpleGABuilder::new()
+                .genome(&genome)
+                .random_population(POPULATION_SIZE)
+                .evaluation_function(&one_max)
+                .goal(OptimizationGoal::Maximize)
+                .selection(TruncationSelection::default())
+                .variation(UniformCrossover::default())
+                .target(N)
+                .stop_when_fraction_at_target(fraction)
+                .seed(1)
+                .build();
+
+            let status = ga.run(100_000);
+            assert!(matches!(status, Status::TargetReached(_)));
+
+            let all_ones = ga.iter().filter(|idv| idv.fitness() == N).count();
+            assert!(all_ones as f64 >= fraction * POPULATION_SIZE as f64);
+            evaluations.push(ga.evaluations());
+        }
+
+        // The runs are identical until they stop, so a larger fraction takes longer to reach
+        assert!(evaluations.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(evaluations[0] < evaluations[2]);
+    }
+
+    #[test]
+    fn robust_evaluation_prefers_robust_optimum() {
+        const SAMPLES: usize = 20;
+
+        // All ones is the best genotype, but all of its neighbours are poor, whereas all zeros
+        // and its neighbours are nearly as good
+        fn peaks(genotype: &Gnt) -> f64 {
+            match one_max(genotype) {
+                N => 10.0,
+                0 | 1 => 8.0,
+                _ => 0.0,
+            }
+        }
+
+        let flip_one = |genotype: &Gnt, rng: &mut SeededRng| {
+            let mut perturbed = *genotype;
+            if rng.gen_bool(0.5) {
+                let idx = rng.gen_range(0..N);
+                perturbed[idx] = !perturbed[idx];
+            }
+            perturbed
+        };
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(10)
+            .evaluation_function(&peaks)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .robust_evaluation(&flip_one, SAMPLES)
+            .seed(0)
+            .build();
+
+        let brittle = [true; N];
+        let robust = [false; N];
+        assert!(peaks(&brittle) > peaks(&robust));
+
+        let fitness_func = ga.fitness_func();
+        let evaluations = fitness_func.evaluations();
+        let mut individual = Individual::from_genotype(brittle);
+        let brittle_fitness = fitness_func.evaluate(&mut individual);
+        let mut individual = Individual::from_genotype(robust);
+        let robust_fitness = fitness_func.evaluate(&mut individual);
+
+        assert!(robust_fitness > brittle_fitness);
+        assert_eq!(robust_fitness, 8.0);
+        assert_eq!(fitness_func.evaluations() - evaluations, 2 * SAMPLES);
+    }
+
+    #[test]
+    fn run_stops_when_condition_is_met() {
+        // Only the genotype matters: the first half must be ones, regardless of the fitness
+        let first_half_ones =
+            |best: &Individual<Gnt, bool, usize>| best.genotype()[..N / 2].iter().all(|bit| *bit);
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(30)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TournamentSelection::new(4, true))
+            .variation(UniformCrossover::default())
+            .stop_when(&first_half_ones)
+            .seed(3)
+            .build();
+
+        let status = ga.run(100_000);
+        assert!(matches!(status, Status::ConditionMet(evaluations) if evaluations < 100_000));
+
+        let best = ga.best_individual().unwrap();
+        assert!(first_half_ones(best));
+        assert!(ga.condition_met());
+    }
+
+    #[test]
+    fn best_history_records_every_generation() {
+        const GENERATIONS: usize = 15;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .seed(5);
+
+        let mut ga = builder.clone().record_best_history(true).build();
+        ga.initialize();
+        for _ in 0..GENERATIONS {
+            ga.step();
+        }
+
+        let history = ga.best_history();
+        assert_eq!(history.len(), GENERATIONS);
+        assert_eq!(history.len(), ga.generation());
+        assert_eq!(
+            history.last(),
+            Some(ga.best_individual().unwrap().genotype())
+        );
+
+        // Truncation selection is elitist, so the recorded genotypes never get worse
+        assert!(history
+            .windows(2)
+            .all(|pair| one_max(&pair[0]) <= one_max(&pair[1])));
+
+        let mut ga = builder.build();
+        ga.run(1_000);
+        assert!(ga.best_history().is_empty());
+    }
+
+    #[test]
+    fn injected_individual_survives_truncation() {
+        const POPULATION_SIZE: usize = 20;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .seed(2)
+            .build();
+        ga.initialize();
+        ga.step();
+        assert!(ga.best_individual().unwrap().fitness() < N);
+
+        let evaluations = ga.evaluations();
+        ga.inject(vec![Individual::from_genotype([true; N])]);
+
+        assert_eq!(ga.evaluations(), evaluations + 1);
+        assert_eq!(ga.population().len(), POPULATION_SIZE);
+        assert_eq!(ga.best_individual().unwrap().genotype(), &[true; N]);
+
+        ga.step();
+        assert!(ga.iter().any(|idv| idv.genotype() == &[true; N]));
+    }
+
+    #[test]
+    fn hill_climbing_speeds_up_convergence() {
+        // Sets the first bit that is not set yet, if that improves the fitness
+        let hill_climb = |genotype: &mut Gnt, fitness_func: &FitnessFunc<'_, Gnt, bool, usize>| {
+            let fitness = fitness_func.evaluate_genotype(genotype);
+            if let Some(idx) = genotype.iter().position(|bit| !bit) {
+                let mut neighbour = *genotype;
+                neighbour[idx] = true;
+                if fitness_func.evaluate_genotype(&neighbour) > fitness {
+                    *genotype = neighbour;
+                }
+            }
+        };
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let generations_to_target = |local_search: Option<&LocalSearch<'_, Gnt, bool, usize>>,
+                                     learning| {
+            let builder = SimpleGABuilder::new()
+                .genome(&genome)
+                .random_population(10)
+                .evaluation_function(&one_max)
+                .goal(OptimizationGoal::Maximize)
+                .selection(TruncationSelection::default())
+                .variation(UniformCrossover::default())
+                .learning(learning)
+                .target(N)
+                .seed(4);
+            let mut ga = match local_search {
+                Some(local_search) => builder.local_search(local_search, 1.0).build(),
+                None => builder.build(),
+            };
+
+            match ga.run(100_000) {
+                Status::TargetReached(_) => Some(ga.generation()),
+                _ => None,
+            }
+        };
+
+        // Without mutation, the plain GA loses bits that the local search restores
+        let memetic = generations_to_target(Some(&hill_climb), Learning::Lamarckian).unwrap();
+        assert!(memetic <= N);
+        assert_eq!(generations_to_target(None, Learning::Lamarckian), None);
+
+        // Baldwinian offspring keep their genotype, but are selected for their potential
+        assert!(generations_to_target(Some(&hill_climb), Learning::Baldwinian).is_some());
+    }
+
+    #[test]
+    fn callback_stops_the_run() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .build();
+
+        let mut generations = Vec::new();
+        let status = ga.run_with_callback(100_000, |ga| {
+            generations.push(ga.generation());
+            if ga.generation() == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+
+        assert!(matches!(status, Status::StoppedByCallback(evaluations) if evaluations == 80));
+        assert_eq!(generations, vec![1, 2, 3]);
+        assert_eq!(ga.generation(), 3);
+    }
+
+    #[test]
+    fn run_does_not_exceed_budget() {
+        const POPULATION_SIZE: usize = 100;
+        const BUDGET: usize = 250;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(Umda::with_genome(&genome))
+            .build();
+
+        // The final generation may only create 50 offspring
+        let status = ga.run(BUDGET);
+
+        assert!(matches!(status, Status::BudgetReached(BUDGET)));
+        assert_eq!(ga.evaluations(), BUDGET);
+        assert_eq!(ga.population().len(), POPULATION_SIZE);
+    }
+
+    #[test]
+    fn forced_elitism_keeps_best_under_roulette_selection() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(10)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(RouletteWheelSelection)
+            .variation(ClearAll)
+            .force_elitism(true)
+            .build();
+
+        ga.initialize();
+        let best = ga.best_individual().unwrap().fitness();
+
+        for _ in 0..20 {
+            ga.step();
+            assert_eq!(ga.best_individual().unwrap().fitness(), best);
+            assert_eq!(ga.population().len(), 10);
+        }
+    }
+
+    #[test]
+    fn comma_selection_can_regress() {
+        let (before, after) = best_fitness_after_step(SelectionMode::Comma);
+
+        assert!(before > 0);
+        assert_eq!(after, 0);
+    }
+
+    #[test]
+    fn plus_selection_keeps_the_best() {
+        let (before, after) = best_fitness_after_step(SelectionMode::Plus);
+
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn population_is_accessible_after_run() {
+        const POPULATION_SIZE: usize = 20;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .build();
+
+        ga.run(1000);
+
+        assert_eq!(ga.population().len(), POPULATION_SIZE);
+        assert_eq!((&ga).into_iter().count(), POPULATION_SIZE);
+        for (idx, idv) in ga.iter().enumerate() {
+            assert_eq!(idv.fitness(), ga[idx].fitness());
+            assert_eq!(idv.fitness(), one_max(idv.genotype()));
+        }
+    }
+
+    fn survivors_after_run(streaming: bool) -> Vec<Gnt> {
+        // Every genotype has a distinct fitness, so the survivors do not depend on tie-breaking
+        let binary_value = |genotype: &Gnt| {
+            genotype
+                .iter()
+                .fold(0usize, |value, bit| 2 * value + bit as usize)
+        };
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .lambda(60)
+            .evaluation_function(&binary_value)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(Umda::with_genome(&genome))
+            .streaming(streaming)
+            .seed(7)
+            .build();
+
+        ga.run(500);
+
+        let mut survivors: Vec<_> = ga.iter().map(|idv| *idv.genotype()).collect();
+        survivors.sort();
+        survivors
+    }
+
+    #[test]
+    fn streaming_selects_same_survivors() {
+        assert_eq!(survivors_after_run(true), survivors_after_run(false));
+    }
+
+    #[test]
+    fn failed_evaluations_do_not_stop_run() {
+        // Genotypes with the first bit set cannot be evaluated
+        let fallible_one_max = |genotype: &Gnt| {
+            if genotype[0] {
+                Err(EvalError::new("first bit set"))
+            } else {
+                Ok(one_max(genotype))
+            }
+        };
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(50)
+            .fallible_evaluation(&fallible_one_max, FailurePolicy::WorstFitness)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .target(N - 1)
+            .seed(11)
+            .build();
+
+        assert!(matches!(ga.run(20000), Status::TargetReached(_)));
+
+        let best = ga.best_individual().unwrap();
+        assert!(!best.genotype()[0]);
+        assert!(ga
+            .iter()
+            .filter(|idv| idv.genotype()[0])
+            .all(|idv| idv.fitness() == usize::MIN));
+    }
+
+    #[test]
+    fn skipped_offspring_do_not_compete() {
+        const POPULATION_SIZE: usize = 20;
+
+        // Only the offspring of `ClearAll` have all bits cleared, and their evaluation fails
+        let fallible_one_max = |genotype: &Gnt| match one_max(genotype) {
+            0 => Err(EvalError::new("all bits cleared")),
+            ones => Ok(ones),
+        };
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        for streaming in [false, true] {
+            let mut ga = SimpleGABuilder::new()
+                .genome(&genome)
+                .random_population(POPULATION_SIZE)
+                .fallible_evaluation(&fallible_one_max, FailurePolicy::Skip)
+                .goal(OptimizationGoal::Maximize)
+                .selection(CopyOffspringSelection)
+                .variation(ClearAll)
+                .streaming(streaming)
+                .seed(0)
+                .build();
+
+            ga.initialize();
+            ga.step();
+
+            // Copying selection would have replaced the parents by the failed offspring
+            assert_eq!(ga.evaluations(), 2 * POPULATION_SIZE);
+            assert_eq!(ga.population().len(), POPULATION_SIZE);
+            assert!(ga.iter().all(|idv| !idv.is_skipped() && idv.fitness() > 0));
+        }
+    }
+
+    #[test]
+    fn run_stops_on_converged_population() {
+        const POPULATION_SIZE: usize = 20;
+
+        // With a single value per gene, every genotype is the same
+        let genome = Genome::<[u8; N], _, _>::with_discrete_domain(&idom!(0..1));
+        let sum = |genotype: &[u8; N]| genotype.iter().map(usize::from).sum::<usize>();
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&sum)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(Umda::with_genome(&genome))
+            .build();
+
+        assert!(matches!(ga.run(10_000), Status::Converged(POPULATION_SIZE)));
+    }
+
+    #[derive(Default)]
+    struct CountingReporter {
+        reports: usize,
+        finished: bool,
+    }
+
+    impl ProgressReporter for CountingReporter {
+        fn report(&mut self, evaluations: usize, budget: usize) {
+            assert!(evaluations <= budget);
+            self.reports += 1;
+        }
+
+        fn finish(&mut self) {
+            self.finished = true;
+        }
+    }
+
+    #[test]
+    fn progress_is_reported_once_per_generation() {
+        const POPULATION_SIZE: usize = 10;
+        const BUDGET: usize = 100;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(CopyOffspringSelection)
+            .variation(ClearAll)
+            .build();
+
+        let mut reporter = CountingReporter::default();
+        assert!(matches!(
+            ga.run_with_progress(BUDGET, &mut reporter),
+            Status::BudgetReached(BUDGET)
+        ));
+
+        // The initial population is not a generation
+        assert_eq!(reporter.reports, BUDGET / POPULATION_SIZE - 1);
+        assert!(reporter.finished);
+    }
+
+    #[test]
+    fn evaluate_population_evaluates_each_individual_once() {
+        const POPULATION_SIZE: usize = 30;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .build();
+
+        assert!(ga.iter().all(|idv| !idv.is_evaluated()));
+
+        assert_eq!(ga.evaluate_population(), POPULATION_SIZE);
+        assert!(ga
+            .iter()
+            .all(|idv| idv.is_evaluated() && idv.fitness() == one_max(idv.genotype())));
+        assert_eq!(ga.evaluations(), POPULATION_SIZE);
+
+        // Already evaluated individuals are not evaluated again
+        assert_eq!(ga.evaluate_population(), 0);
+        assert_eq!(ga.evaluations(), POPULATION_SIZE);
+    }
+
+    #[test]
+    fn run_does_not_evaluate_an_evaluated_population_again() {
+        const POPULATION_SIZE: usize = 20;
+        const BUDGET: usize = 200;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(CopyOffspringSelection)
+            .variation(ClearAll)
+            .build();
+
+        ga.evaluate_population();
+        assert!(matches!(ga.run(BUDGET), Status::BudgetReached(BUDGET)));
+
+        // Only the initial evaluations and the offspring are charged to the budget
+        assert_eq!(ga.evaluations(), BUDGET);
+        assert_eq!(ga.generation(), BUDGET / POPULATION_SIZE - 1);
+    }
+
+    #[test]
+    fn no_variation_carries_fitness_forward() {
+        const POPULATION_SIZE: usize = 30;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(NoVariation)
+            .build();
+
+        ga.initialize();
+        assert_eq!(ga.evaluations(), POPULATION_SIZE);
+
+        for _ in 0..5 {
+            ga.step();
+            assert_eq!(ga.evaluations(), POPULATION_SIZE);
+            assert_eq!(ga.population().len(), POPULATION_SIZE);
+        }
+
+        // Without new evaluations the budget is never used up, so the run stops by itself
+        assert!(matches!(ga.run(10_000), Status::Converged(_)));
+    }
+
+    #[test]
+    fn cache_hits_do_not_count_as_evaluations() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(40)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .target(N)
+            .seed(3);
+
+        let mut plain = builder.clone().build();
+        let mut cached = builder.cache_evaluations().build();
+
+        assert!(matches!(plain.run(100_000), Status::TargetReached(_)));
+        assert!(matches!(cached.run(100_000), Status::TargetReached(_)));
+
+        // Caching does not change the fitnesses, so both runs follow the same trajectory
+        let fitness_func = cached.fitness_func();
+        assert_eq!(
+            fitness_func.total_lookups(),
+            plain.fitness_func().total_lookups()
+        );
+        assert!(fitness_func.cache_hits() > 0);
+        assert!(fitness_func.real_evaluations() < plain.fitness_func().real_evaluations());
+        assert_eq!(cached.evaluations(), fitness_func.real_evaluations());
+    }
+
+    #[test]
+    fn run_stops_when_only_cache_hits_remain() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        // Every offspring has the same genotype, so after the first generation the cache holds
+        // all fitnesses and the unreachable target leaves nothing to stop the run
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(10)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(ClearAll)
+            .target(N + 1)
+            .cache_evaluations()
+            .seed(0)
+            .build();
+
+        assert!(matches!(ga.run(usize::MAX), Status::Converged(11)));
+        assert_eq!(ga.generation(), STALLED_GENERATIONS + 1);
+    }
+
+    #[test]
+    fn improvements_within_epsilon_count_as_stagnation() {
+        // Every bit adds a tiny amount, while the optimum stands out
+        fn fitness(genotype: &Gnt) -> f64 {
+            if genotype.iter().all(|bit| bit) {
+                10.0
+            } else {
+                one_max(genotype) as f64 * 0.01
+            }
+        }
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .population(vec![Individual::from_genotype([false; N]); 10])
+            .evaluation_function(&fitness)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(NoVariation)
+            .seed(0);
+
+        let slightly_better = |ga: &SimpleGA<'_, Gnt, bool, f64, _, _, _>| {
+            let mut genotype = *ga.best_individual().unwrap().genotype();
+            *genotype.iter_mut().find(|bit| !**bit).unwrap() = true;
+            Individual::from_genotype(genotype)
+        };
+
+        let mut plain = builder.clone().build();
+        assert_eq!(plain.improvement_epsilon(), f64::IMPROVEMENT_EPSILON);
+        plain.initialize();
+        plain.step();
+        assert_eq!(plain.stagnant_generations(), 1);
+        plain.inject(vec![slightly_better(&plain)]);
+        plain.step();
+        assert_eq!(plain.stagnant_generations(), 0);
+
+        let mut ga = builder.improvement_epsilon(0.5).build();
+        ga.initialize();
+        for generation in 1..=3 {
+            ga.inject(vec![slightly_better(&ga)]);
+            ga.step();
+            assert_eq!(ga.stagnant_generations(), generation);
+        }
+
+        ga.inject(vec![Individual::from_genotype([true; N])]);
+        ga.step();
+        assert_eq!(ga.stagnant_generations(), 0);
+    }
+
+    #[test]
+    fn callback_sees_selection_pressure() {
+        const POPULATION_SIZE: usize = 20;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .seed(0);
+
+        for streaming in [false, true] {
+            let mut ga = builder.clone().streaming(streaming).build();
+            ga.run_with_callback(200, |ga| {
+                let pressure = ga.selection_pressure().unwrap();
+                assert_eq!(pressure.pool_size(), 2 * POPULATION_SIZE);
+                assert_eq!(pressure.survivors(), POPULATION_SIZE);
+                assert_eq!(pressure.ratio(), 2.0);
+                assert_eq!(pressure.participations(), None);
+                ControlFlow::Continue(())
+            });
+        }
+
+        // Every individual of the pool competes in two tournaments of four for twenty slots
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TournamentSelection::new(4, true))
+            .variation(UniformCrossover::default())
+            .seed(0)
+            .build();
+        ga.initialize();
+        assert!(ga.selection_pressure().is_none());
+        ga.step();
+        assert_eq!(ga.selection_pressure().unwrap().participations(), Some(2.0));
+
+        // Without the parents, only the offspring compete
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TournamentSelection::new(2, false))
+            .variation(UniformCrossover::default())
+            .seed(0)
+            .build();
+        ga.initialize();
+        ga.step();
+        let pressure = ga.selection_pressure().unwrap();
+        assert_eq!(pressure.pool_size(), POPULATION_SIZE);
+        assert_eq!(pressure.ratio(), 1.0);
+    }
+
+    #[test]
+    fn warns_about_diversity_loss_without_mutation() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(Umda::with_genome(&genome));
+
+        // The check is opt-in
+        assert!(builder.clone().build().diversity_warning().is_none());
+
+        let builder = builder.warn_on_diversity_loss(true);
+        assert!(builder.clone().build().diversity_warning().is_some());
+        assert!(builder
+            .clone()
+            .selection_mode(SelectionMode::Comma)
+            .lambda(40)
+            .build()
+            .diversity_warning()
+            .is_none());
+    }
+
+    #[test]
+    fn comma_selection_keeps_mu_survivors() {
+        const MU: usize = 20;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        for lambda in [MU, 3 * MU] {
+            let mut copying = SimpleGABuilder::new()
+                .genome(&genome)
+                .random_population(MU)
+                .lambda(lambda)
+                .evaluation_function(&one_max)
+                .goal(OptimizationGoal::Maximize)
+                .selection(CopyOffspringSelection)
+                .variation(UniformCrossover::default())
+                .selection_mode(SelectionMode::Comma)
+                .seed(0)
+                .build();
+            copying.initialize();
+
+            let mut generational = SimpleGABuilder::new()
+                .genome(&genome)
+                .random_population(MU)
+                .lambda(lambda)
+                .evaluation_function(&one_max)
+                .goal(OptimizationGoal::Maximize)
+                .selection(TournamentSelection::new(2, false))
+                .variation(UniformCrossover::default())
+                .selection_mode(SelectionMode::Comma)
+                .seed(0)
+                .build();
+            generational.initialize();
+
+            for _ in 0..5 {
+                copying.step();
+                generational.step();
+                assert_eq!(copying.population().len(), MU);
+                assert_eq!(generational.population().len(), MU);
+            }
+        }
+    }
+
+    #[test]
+    fn injected_individuals_compete_under_generational_selection() {
+        const POPULATION_SIZE: usize = 20;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TournamentSelection::new(2, false))
+            .variation(UniformCrossover::default())
+            .seed(0)
+            .build();
+        ga.initialize();
+
+        ga.inject(vec![Individual::from_genotype([false; N]); 5]);
+
+        // The injected individuals are the worst possible, so they only win tournaments among
+        // themselves rather than replacing the population
+        assert_eq!(ga.population().len(), POPULATION_SIZE);
+        let injected = ga.iter().filter(|idv| idv.fitness() == 0).count();
+        assert!(injected < POPULATION_SIZE / 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "a generation gap other than 1 changes the population size")]
+    fn copying_selection_rejects_generation_gap() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(CopyOffspringSelection)
+            .variation(Umda::with_genome(&genome).generation_gap(2.0))
+            .build();
+    }
+
+    #[test]
+    fn capped_generation_keeps_population_size() {
+        const POPULATION_SIZE: usize = 20;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(CopyOffspringSelection)
+            .variation(Umda::with_genome(&genome))
+            .seed(0)
+            .build();
+
+        // The second generation is capped at five offspring
+        assert!(matches!(ga.run(45), Status::BudgetReached(45)));
+        assert_eq!(ga.population().len(), POPULATION_SIZE);
+    }
+
+    #[test]
+    fn derived_seeds_differ_per_run() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let builder = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(10)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .seed(0);
+
+        let first = builder.clone().derive_seed(0).build();
+        let second = builder.clone().derive_seed(1).build();
+        assert_ne!(first.seed(), second.seed());
+        assert_eq!(builder.derive_seed(1).build().seed(), second.seed());
+
+        let genotypes = |ga: &SimpleGA<'_, Gnt, bool, usize, _, _, _>| {
+            ga.iter().map(|idv| *idv.genotype()).collect::<Vec<_>>()
+        };
+        assert_ne!(genotypes(&first), genotypes(&second));
     }
 }
@@ -1,15 +1,288 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
 use ndarray::{Array, Ix1, Ix2};
 use ndarray_linalg::{Cholesky, UPLO};
 use rand::Rng;
 
-fn sample_multivariate_normal(
+use crate::{
+    fitness::{Fitness, FitnessFunc},
+    gene::{Allele, Real},
+    genotype::Genotype,
+    individual::Individual,
+};
+
+// Computes statistics over the population, once per generation
+pub trait StatsCollector<Gnt, A, F>: Clone
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn collect(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    );
+}
+
+#[derive(Clone, Default)]
+pub struct NoStats;
+
+impl<Gnt, A, F> StatsCollector<Gnt, A, F> for NoStats
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn collect(
+        &mut self,
+        _population: &[Individual<Gnt, A, F>],
+        _fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) {
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct BestFitnessStats<F>
+where
+    F: Fitness,
+{
+    history: Vec<F>,
+}
+
+impl<F> BestFitnessStats<F>
+where
+    F: Fitness,
+{
+    pub fn history(&self) -> &[F] {
+        &self.history
+    }
+}
+
+impl<Gnt, A, F> StatsCollector<Gnt, A, F> for BestFitnessStats<F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn collect(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) {
+        if let Some(best) = population
+            .iter()
+            .map(|idv| idv.fitness())
+            .min_by(|a, b| fitness_func.cmp(a, b))
+        {
+            self.history.push(best);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FitnessSummary<F>
+where
+    F: Fitness,
+{
+    pub best: F,
+    pub worst: F,
+    pub median: F,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+#[derive(Clone, Default)]
+pub struct FitnessSummaryStats<F>
+where
+    F: Fitness,
+{
+    history: Vec<FitnessSummary<F>>,
+}
+
+impl<F> FitnessSummaryStats<F>
+where
+    F: Fitness,
+{
+    pub fn history(&self) -> &[FitnessSummary<F>] {
+        &self.history
+    }
+}
+
+impl<Gnt, A, F> StatsCollector<Gnt, A, F> for FitnessSummaryStats<F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn collect(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) {
+        if population.is_empty() {
+            return;
+        }
+
+        let mut fitnesses: Vec<_> = population.iter().map(|idv| idv.fitness()).collect();
+        fitnesses.sort_by(|a, b| fitness_func.cmp(a, b));
+
+        let n = fitnesses.len() as f64;
+        let mean = fitnesses.iter().map(|f| f.as_f64()).sum::<f64>() / n;
+        let variance = fitnesses
+            .iter()
+            .map(|f| (f.as_f64() - mean).powi(2))
+            .sum::<f64>()
+            / n;
+
+        self.history.push(FitnessSummary {
+            best: fitnesses[0],
+            worst: fitnesses[fitnesses.len() - 1],
+            median: fitnesses[fitnesses.len() / 2],
+            mean,
+            std_dev: variance.sqrt(),
+        });
+    }
+}
+
+// Records the fitness at each of the given percentiles of the population, where percentile 0 is
+// the best fitness and 1 the worst. Each percentile takes the fitness of the nearest rank.
+#[derive(Clone)]
+pub struct PercentileStats<F>
+where
+    F: Fitness,
+{
+    percentiles: Vec<f64>,
+    history: Vec<Vec<F>>,
+}
+
+impl<F> PercentileStats<F>
+where
+    F: Fitness,
+{
+    pub fn new(percentiles: Vec<f64>) -> Self {
+        assert!(
+            percentiles.iter().all(|p| (0.0..=1.0).contains(p)),
+            "percentiles must be between 0 and 1"
+        );
+
+        Self {
+            percentiles,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn percentiles(&self) -> &[f64] {
+        &self.percentiles
+    }
+
+    // The fitness at each percentile, in the order of the percentiles, for every generation
+    pub fn history(&self) -> &[Vec<F>] {
+        &self.history
+    }
+}
+
+impl<Gnt, A, F> StatsCollector<Gnt, A, F> for PercentileStats<F>
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn collect(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    ) {
+        if population.is_empty() {
+            return;
+        }
+
+        let mut fitnesses: Vec<_> = population.iter().map(|idv| idv.fitness()).collect();
+        fitnesses.sort_by(|a, b| fitness_func.cmp(a, b));
+
+        let last = (fitnesses.len() - 1) as f64;
+        self.history.push(
+            self.percentiles
+                .iter()
+                .map(|p| fitnesses[(p * last).round() as usize])
+                .collect(),
+        );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatsError {
+    // The dimensions of the mean vector and the covariance matrix do not agree
+    DimensionMismatch {
+        mean: usize,
+        covariance: (usize, usize),
+    },
+    // The covariance matrix is not positive definite, even after regularization
+    NotPositiveDefinite,
+}
+
+impl Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsError::DimensionMismatch { mean, covariance } => write!(
+                f,
+                "mean of length {} does not match covariance of shape {}x{}",
+                mean, covariance.0, covariance.1
+            ),
+            StatsError::NotPositiveDefinite => {
+                write!(f, "covariance matrix is not positive definite")
+            }
+        }
+    }
+}
+
+impl Error for StatsError {}
+
+// The number of times the ridge is grown before a covariance matrix is rejected
+const RIDGE_ATTEMPTS: usize = 10;
+
+// Cholesky decomposition, adding a growing ridge to the diagonal when the covariance is
+// singular (e.g. estimated from fewer samples than dimensions)
+pub(crate) fn regularized_cholesky(
+    covariance: &Array<f64, Ix2>,
+) -> Result<Array<f64, Ix2>, StatsError> {
+    if let Ok(lower) = covariance.cholesky(UPLO::Lower) {
+        return Ok(lower);
+    }
+
+    let n = covariance.nrows();
+    let scale = covariance.diag().iter().map(|v| v.abs()).sum::<f64>() / n.max(1) as f64;
+    let mut ridge = 1e-10 * scale.max(1.0);
+
+    for _ in 0..RIDGE_ATTEMPTS {
+        let regularized = covariance + &(Array::<f64, Ix2>::eye(n) * ridge);
+        if let Ok(lower) = regularized.cholesky(UPLO::Lower) {
+            return Ok(lower);
+        }
+        ridge *= 10.0;
+    }
+
+    Err(StatsError::NotPositiveDefinite)
+}
+
+pub fn sample_multivariate_normal(
     mean: &Array<f64, Ix1>,
     covariance: &Array<f64, Ix2>,
-) -> Array<f64, Ix1> {
+) -> Result<Array<f64, Ix1>, StatsError> {
     let n = mean.len();
 
+    if covariance.dim() != (n, n) {
+        return Err(StatsError::DimensionMismatch {
+            mean: n,
+            covariance: covariance.dim(),
+        });
+    }
+
     // Cholesky decomposition
-    let lower = covariance.cholesky(UPLO::Lower).unwrap();
+    let lower = regularized_cholesky(covariance)?;
 
     // Sample 'n' standard normal variables
     let mut rng = rand::thread_rng();
@@ -18,16 +291,180 @@ fn sample_multivariate_normal(
         .collect();
 
     // Scale and translate the random sample (L*v + mean)
-    lower.dot(&random_vec) + mean
+    Ok(lower.dot(&random_vec) + mean)
+}
+
+// Relative ridge added to an estimated covariance when there are fewer samples than dimensions
+const COVARIANCE_RIDGE: f64 = 1e-6;
+
+fn as_vector<Gnt, A>(genotype: &Gnt) -> Array<f64, Ix1>
+where
+    A: Allele + Real,
+    Gnt: Genotype<A>,
+{
+    genotype.iter().map(|a| a.to_f64().unwrap()).collect()
+}
+
+pub fn estimate_mean<Gnt, A>(genotypes: &[Gnt]) -> Array<f64, Ix1>
+where
+    A: Allele + Real,
+    Gnt: Genotype<A>,
+{
+    assert!(
+        !genotypes.is_empty(),
+        "cannot estimate the mean of an empty sample"
+    );
+
+    let mut sum: Array<f64, Ix1> = Array::zeros(Gnt::LEN);
+    for genotype in genotypes {
+        sum += &as_vector(genotype);
+    }
+
+    sum / genotypes.len() as f64
+}
+
+// Unbiased sample covariance. A sample with no more individuals than dimensions yields a singular
+// matrix, so a small ridge proportional to the mean variance is added to its diagonal.
+pub fn estimate_covariance<Gnt, A>(genotypes: &[Gnt]) -> Array<f64, Ix2>
+where
+    A: Allele + Real,
+    Gnt: Genotype<A>,
+{
+    let dim = Gnt::LEN;
+    let n = genotypes.len();
+    let mean = estimate_mean(genotypes);
+
+    let mut covariance: Array<f64, Ix2> = Array::zeros((dim, dim));
+    for genotype in genotypes {
+        let centered = as_vector(genotype) - &mean;
+        for i in 0..dim {
+            for j in 0..dim {
+                covariance[[i, j]] += centered[i] * centered[j];
+            }
+        }
+    }
+    covariance /= n.saturating_sub(1).max(1) as f64;
+
+    if n <= dim {
+        let scale = covariance.diag().sum() / dim.max(1) as f64;
+        let ridge = COVARIANCE_RIDGE * if scale > 0.0 { scale } else { 1.0 };
+        covariance.diag_mut().mapv_inplace(|v| v + ridge);
+    }
+
+    covariance
 }
 
 #[cfg(test)]
 mod tests {
     use std::ops::Add;
 
-    use approx::assert_relative_eq;
+    use approx::{assert_abs_diff_eq, assert_relative_eq};
 
     use super::*;
+    use crate::{
+        fitness::OptimizationGoal, gene::BoolDomain, genome::Genome,
+        selection::TruncationSelection, simplega::SimpleGABuilder, variation::UniformCrossover,
+    };
+
+    fn evaluated_population(fitnesses: &[i32]) -> Vec<Individual<[bool; 1], bool, i32>> {
+        fitnesses
+            .iter()
+            .map(|fitness| {
+                let mut idv = Individual::from_genotype([false]);
+                idv.set_fitness(*fitness);
+                idv
+            })
+            .collect()
+    }
+
+    #[test]
+    fn best_fitness_stats_records_best() {
+        let evaluation_func = |_: &[bool; 1]| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+
+        let mut stats = BestFitnessStats::default();
+        stats.collect(&evaluated_population(&[3, -2, 7]), &fitness_func);
+        stats.collect(&evaluated_population(&[1, 4]), &fitness_func);
+
+        assert_eq!(stats.history(), &[-2, 1]);
+    }
+
+    #[test]
+    fn fitness_summary_stats_records_all_fields() {
+        let evaluation_func = |_: &[bool; 1]| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let mut stats = FitnessSummaryStats::default();
+        stats.collect(
+            &evaluated_population(&[2, 4, 4, 4, 5, 5, 7, 9]),
+            &fitness_func,
+        );
+
+        let summary = &stats.history()[0];
+        assert_eq!(summary.best, 9);
+        assert_eq!(summary.worst, 2);
+        assert_eq!(summary.median, 4);
+        assert_abs_diff_eq!(summary.mean, 5.0);
+        assert_abs_diff_eq!(summary.std_dev, 2.0);
+    }
+
+    #[test]
+    fn percentile_stats_records_requested_percentiles() {
+        let evaluation_func = |_: &[bool; 1]| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let mut stats = PercentileStats::new(vec![0.0, 0.5, 0.9, 1.0]);
+        stats.collect(
+            &evaluated_population(&[2, 4, 4, 4, 5, 5, 7, 9]),
+            &fitness_func,
+        );
+        stats.collect(&evaluated_population(&[3]), &fitness_func);
+
+        assert_eq!(stats.history(), &[vec![9, 4, 4, 2], vec![3; 4]]);
+    }
+
+    // Counts how often it collects
+    #[derive(Clone, Default)]
+    struct CountingStats {
+        collections: usize,
+    }
+
+    impl<Gnt, A, F> StatsCollector<Gnt, A, F> for CountingStats
+    where
+        A: Allele,
+        F: Fitness,
+        Gnt: Genotype<A>,
+    {
+        fn collect(
+            &mut self,
+            _population: &[Individual<Gnt, A, F>],
+            _fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        ) {
+            self.collections += 1;
+        }
+    }
+
+    #[test]
+    fn run_collects_once_per_generation() {
+        let one_max = |genotype: &[bool; 16]| genotype.iter().filter(|bit| *bit).count();
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(20)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(UniformCrossover::default())
+            .stats(CountingStats::default())
+            .seed(0)
+            .build();
+        ga.run(20 * 10);
+
+        // The initial population is collected as well
+        assert_eq!(ga.generation(), 9);
+        assert_eq!(ga.stats().collections, ga.generation() + 1);
+    }
 
     #[test]
     fn mean_of_samples_approx_equals_mean_of_dist() {
@@ -45,7 +482,7 @@ mod tests {
 
         // Draw samples from multivariate normal distribution
         let samples: Vec<_> = (0..NUM_SAMPLES)
-            .map(|_| sample_multivariate_normal(&mean, &covariance))
+            .map(|_| sample_multivariate_normal(&mean, &covariance).unwrap())
             .collect();
 
         // Calculate mean
@@ -60,4 +497,76 @@ mod tests {
             assert_relative_eq!(sum_vec[i], mean[i], epsilon = 0.01);
         }
     }
+
+    #[test]
+    fn singular_covariance_is_regularized() {
+        let mean: Array<f64, Ix1> = Array::zeros(2);
+        let covariance = ndarray::arr2(&[[1.0, 1.0], [1.0, 1.0]]);
+
+        let sample = sample_multivariate_normal(&mean, &covariance).unwrap();
+
+        assert!(sample.iter().all(|v| v.is_finite()));
+        // All variance lies along the diagonal
+        assert_abs_diff_eq!(sample[0], sample[1], epsilon = 1e-3);
+    }
+
+    #[test]
+    fn negative_definite_covariance_is_rejected() {
+        let mean: Array<f64, Ix1> = Array::zeros(2);
+        let covariance = ndarray::arr2(&[[-1.0, 0.0], [0.0, -1.0]]);
+
+        assert_eq!(
+            sample_multivariate_normal(&mean, &covariance),
+            Err(StatsError::NotPositiveDefinite)
+        );
+
+        let covariance: Array<f64, Ix2> = Array::eye(3);
+        assert_eq!(
+            sample_multivariate_normal(&mean, &covariance),
+            Err(StatsError::DimensionMismatch {
+                mean: 2,
+                covariance: (3, 3)
+            })
+        );
+    }
+
+    #[test]
+    fn estimates_recover_gaussian_parameters() {
+        const NUM_SAMPLES: usize = 50000;
+
+        let mean = ndarray::arr1(&[1.0, -2.0, 0.5]);
+        let covariance = ndarray::arr2(&[[2.0, 0.6, 0.0], [0.6, 1.0, -0.3], [0.0, -0.3, 0.5]]);
+
+        let samples: Vec<[f64; 3]> = (0..NUM_SAMPLES)
+            .map(|_| {
+                let sample = sample_multivariate_normal(&mean, &covariance).unwrap();
+                [sample[0], sample[1], sample[2]]
+            })
+            .collect();
+
+        let estimated_mean = estimate_mean(&samples);
+        let estimated_covariance = estimate_covariance(&samples);
+
+        for i in 0..3 {
+            assert_abs_diff_eq!(estimated_mean[i], mean[i], epsilon = 0.05);
+            for j in 0..3 {
+                assert_abs_diff_eq!(
+                    estimated_covariance[[i, j]],
+                    covariance[[i, j]],
+                    epsilon = 0.1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn small_sample_covariance_is_regularized() {
+        let samples = [[1.0, 2.0, 3.0], [2.0, 4.0, 6.0]];
+
+        let covariance = estimate_covariance(&samples);
+
+        assert!(covariance.diag().iter().all(|v| *v > 0.0));
+        assert!(sample_multivariate_normal(&estimate_mean(&samples), &covariance).is_ok());
+        assert!(covariance.cholesky(UPLO::Lower).is_ok());
+    }
 }
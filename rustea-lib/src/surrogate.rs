@@ -0,0 +1,282 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::{
+    fitness::{EvaluationFunc, Fitness, FitnessFunc},
+    gene::Allele,
+    genotype::Genotype,
+    individual::Individual,
+    population::Population,
+    rng::SeededRng,
+    variation::{offspring_count, VariationOperator},
+};
+
+// A cheap approximation of an expensive fitness function, which learns from the true fitnesses
+// of the genotypes that were evaluated
+pub trait Surrogate<Gnt, F>: Send + Sync
+where
+    F: Fitness,
+{
+    fn predict(&self, genotype: &Gnt) -> F;
+
+    fn update(&mut self, samples: &[(&Gnt, F)]);
+}
+
+// Pre-screens the offspring of a variation operator with a surrogate: the operator creates its
+// offspring as usual, but evaluated by the surrogate, after which only the `evaluations` most
+// promising ones are evaluated with the true fitness function and the others are discarded. The
+// surrogate is trained on the evaluated parents the first time, and on the true fitnesses of the
+// promising offspring after every generation. As the offspring are ranked, they are no longer
+// paired, and an operator that selects its own offspring (e.g. optimal mixing) cannot be
+// pre-screened.
+#[derive(Clone)]
+pub struct SurrogateAssisted<Gnt, A, F, V, Sg>
+where
+    A: Allele,
+    F: Fitness,
+    V: VariationOperator<Gnt, A, F>,
+    Sg: Surrogate<Gnt, F> + Clone,
+    Gnt: Genotype<A>,
+{
+    operator: V,
+    surrogate: Sg,
+    evaluations: usize,
+    trained: bool,
+    _allele: PhantomData<A>,
+    _genotype: PhantomData<Gnt>,
+    _fitness: PhantomData<F>,
+}
+
+impl<Gnt, A, F, V, Sg> SurrogateAssisted<Gnt, A, F, V, Sg>
+where
+    A: Allele,
+    F: Fitness,
+    V: VariationOperator<Gnt, A, F>,
+    Sg: Surrogate<Gnt, F> + Clone,
+    Gnt: Genotype<A>,
+{
+    // `evaluations` is the number of offspring per generation that get a true evaluation
+    pub fn new(operator: V, surrogate: Sg, evaluations: usize) -> Self {
+        assert!(
+            evaluations > 0,
+            "at least one offspring must be evaluated per generation"
+        );
+        assert!(
+            !operator.selects_offspring(),
+            "cannot pre-screen the offspring of an operator that selects them itself"
+        );
+
+        Self {
+            operator,
+            surrogate,
+            evaluations,
+            trained: false,
+            _allele: PhantomData,
+            _genotype: PhantomData,
+            _fitness: PhantomData,
+        }
+    }
+
+    pub fn surrogate(&self) -> &Sg {
+        &self.surrogate
+    }
+}
+
+impl<Gnt, A, F, V, Sg> VariationOperator<Gnt, A, F> for SurrogateAssisted<Gnt, A, F, V, Sg>
+where
+    A: Allele,
+    F: Fitness,
+    V: VariationOperator<Gnt, A, F>,
+    Sg: Surrogate<Gnt, F> + Clone,
+    Gnt: Genotype<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>> {
+        if !self.trained {
+            let samples: Vec<_> = population
+                .iter()
+                .filter(|idv| idv.is_evaluated())
+                .map(|idv| (idv.genotype(), idv.fitness()))
+                .collect();
+            self.surrogate.update(&samples);
+            self.trained = true;
+        }
+
+        // The predictions are not counted by (and do not use up the budget of) the true fitness
+        // function
+        let mut candidates: Population<_, _, _> = {
+            let surrogate = &self.surrogate;
+            let predict: Arc<EvaluationFunc<'_, Gnt, F>> =
+                Arc::new(move |genotype: &Gnt| surrogate.predict(genotype));
+            let predicted = FitnessFunc::from_shared(predict, fitness_func.goal().clone());
+
+            self.operator
+                .create_offspring(population, &predicted, None, rng)
+                .into()
+        };
+        candidates.sort_by_fitness(fitness_func);
+
+        let n = offspring_count(self.evaluations, max_new_evaluations);
+        let mut offspring: Vec<_> = candidates
+            .into_inner()
+            .into_iter()
+            .take(n)
+            .map(|idv| Individual::from_genotype(idv.genotype().clone()))
+            .collect();
+        fitness_func.evaluate_all(&mut offspring);
+
+        let samples: Vec<_> = offspring
+            .iter()
+            .map(|idv| (idv.genotype(), idv.fitness()))
+            .collect();
+        self.surrogate.update(&samples);
+
+        offspring
+    }
+
+    fn mutates(&self) -> bool {
+        self.operator.mutates()
+    }
+
+    fn fixed_offspring_count(&self) -> Option<usize> {
+        Some(self.evaluations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        fitness::OptimizationGoal,
+        gene::BoolDomain,
+        genome::Genome,
+        gom::Gom,
+        selection::{CopyOffspringSelection, TruncationSelection},
+        simplega::{SimpleGABuilder, Status},
+        variation::UniformCrossover,
+    };
+
+    const N: usize = 16;
+
+    type Gnt = [bool; N];
+
+    fn one_max(genotype: &Gnt) -> usize {
+        genotype.iter().filter(|bit| *bit).count()
+    }
+
+    // A linear model with unit weights, which records its predictions and the number of samples
+    // it was updated with
+    #[derive(Clone, Default)]
+    struct LinearSurrogate {
+        predictions: Arc<Mutex<Vec<usize>>>,
+        samples: usize,
+    }
+
+    impl Surrogate<Gnt, usize> for LinearSurrogate {
+        fn predict(&self, genotype: &Gnt) -> usize {
+            let prediction = genotype.iter().map(usize::from).sum();
+            self.predictions.lock().unwrap().push(prediction);
+            prediction
+        }
+
+        fn update(&mut self, samples: &[(&Gnt, usize)]) {
+            self.samples += samples.len();
+        }
+    }
+
+    #[test]
+    fn only_promising_offspring_are_evaluated() {
+        const POPULATION_SIZE: usize = 20;
+        const EVALUATIONS: usize = 5;
+
+        let mut rng = SeededRng::seed_from_u64(0);
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let fitness_func = FitnessFunc::new(&one_max, OptimizationGoal::Maximize);
+
+        let mut population: Vec<Individual<Gnt, bool, usize>> = genome
+            .random_genotype_batch(&mut rng, POPULATION_SIZE)
+            .into_iter()
+            .map(Individual::from_genotype)
+            .collect();
+        fitness_func.evaluate_all(&mut population);
+
+        let surrogate = LinearSurrogate::default();
+        let mut operator =
+            SurrogateAssisted::new(UniformCrossover::default(), surrogate.clone(), EVALUATIONS);
+        let offspring = operator.create_offspring(&population, &fitness_func, None, &mut rng);
+
+        assert_eq!(offspring.len(), EVALUATIONS);
+        assert_eq!(fitness_func.evaluations(), POPULATION_SIZE + EVALUATIONS);
+        assert_eq!(operator.surrogate().samples, POPULATION_SIZE + EVALUATIONS);
+
+        // The surrogate is exact, so the evaluated offspring are the best predicted ones
+        let mut predictions = surrogate.predictions.lock().unwrap().clone();
+        assert_eq!(predictions.len(), POPULATION_SIZE);
+        predictions.sort_by(|a, b| b.cmp(a));
+
+        let mut fitnesses: Vec<_> = offspring.iter().map(|idv| idv.fitness()).collect();
+        fitnesses.sort_by(|a, b| b.cmp(a));
+        assert_eq!(fitnesses, predictions[..EVALUATIONS]);
+    }
+
+    #[test]
+    fn surrogate_assisted_ga_reaches_target() {
+        const EVALUATIONS: usize = 10;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(40)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(SurrogateAssisted::new(
+                UniformCrossover::default(),
+                LinearSurrogate::default(),
+                EVALUATIONS,
+            ))
+            .target(N)
+            .seed(0)
+            .build();
+
+        assert!(matches!(ga.run(10_000), Status::TargetReached(_)));
+        assert_eq!(ga.evaluations(), 40 + ga.generation() * EVALUATIONS);
+    }
+
+    #[test]
+    #[should_panic(expected = "fixed number of offspring")]
+    fn copying_selection_rejects_pre_screening() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+
+        SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(40)
+            .evaluation_function(&one_max)
+            .goal(OptimizationGoal::Maximize)
+            .selection(CopyOffspringSelection)
+            .variation(SurrogateAssisted::new(
+                UniformCrossover::default(),
+                LinearSurrogate::default(),
+                10,
+            ))
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "selects them itself")]
+    fn optimal_mixing_cannot_be_pre_screened() {
+        SurrogateAssisted::<Gnt, bool, usize, _, _>::new(
+            Gom::default(),
+            LinearSurrogate::default(),
+            10,
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use rand::{Error, RngCore};
+
+// A generator that replays a fixed stream of values (cycling when exhausted), so that tests of
+// stochastic operators can dictate every random decision
+#[derive(Debug, Clone)]
+pub(crate) struct SequenceRng {
+    values: Vec<u64>,
+    index: usize,
+}
+
+impl SequenceRng {
+    pub(crate) fn new(values: Vec<u64>) -> Self {
+        assert!(!values.is_empty(), "the sequence must not be empty");
+        Self { values, index: 0 }
+    }
+
+    // A stream for which `gen::<f64>()` returns each of the given values in [0, 1), and
+    // `gen_bool(p)` returns whether the value is below `p`
+    pub(crate) fn from_unit_floats(values: &[f64]) -> Self {
+        Self::new(
+            values
+                .iter()
+                .map(|v| {
+                    assert!((0.0..1.0).contains(v), "values must lie in [0, 1)");
+                    ((v * (1u64 << 53) as f64) as u64) << 11
+                })
+                .collect(),
+        )
+    }
+}
+
+impl RngCore for SequenceRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.values[self.index];
+        self.index = (self.index + 1) % self.values.len();
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn replays_unit_floats() {
+        let mut rng = SequenceRng::from_unit_floats(&[0.25, 0.75]);
+
+        assert_eq!(rng.gen::<f64>(), 0.25);
+        assert_eq!(rng.gen::<f64>(), 0.75);
+
+        // The stream cycles
+        assert!(rng.gen_bool(0.5));
+        assert!(!rng.gen_bool(0.5));
+    }
+}
@@ -4,13 +4,30 @@ use crate::{
     genome::{Cartesian, Genome},
     genotype::Genotype,
     individual::Individual,
-    model::UnivariateModel,
+    model::{CumulativeDistribution, Factorization, MultivariateModel, UnivariateModel},
+    parallel::*,
+    rng::{item_rngs, SeededRng},
 };
 
 use derivative::Derivative;
 use rand::{seq::SliceRandom, Rng};
-use rayon::prelude::*;
-use std::marker::PhantomData;
+use std::{
+    collections::HashSet,
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// The number of offspring to create from a population, given an optional cap on the number of
+// new fitness evaluations
+pub(crate) fn offspring_count(population_size: usize, max_new_evaluations: Option<usize>) -> usize {
+    max_new_evaluations.map_or(population_size, |cap| cap.min(population_size))
+}
+
+// The number of offspring of an EDA for a population, where the generation gap is the ratio of
+// offspring to parents
+pub(crate) fn gap_count(population_size: usize, generation_gap: f64) -> usize {
+    (population_size as f64 * generation_gap).round() as usize
+}
 
 pub trait VariationOperator<Gnt, A, F>: Clone
 where
@@ -20,12 +37,152 @@ where
     Gnt: Genotype<A>,
 {
     fn create_offspring(
-        &self,
+        &mut self,
         population: &[Individual<Gnt, A, F>],
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
     ) -> Vec<Individual<Gnt, A, F>>;
 
+    // Streaming variant of `create_offspring`, yielding each offspring once it is evaluated, so
+    // that selection can consume them as they are produced. Operators that can create offspring
+    // one at a time override this; the default creates them in a batch.
+    fn create_offspring_iter<'b>(
+        &'b mut self,
+        population: &'b [Individual<Gnt, A, F>],
+        fitness_func: &'b FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Box<dyn Iterator<Item = Individual<Gnt, A, F>> + 'b> {
+        Box::new(
+            self.create_offspring(population, fitness_func, max_new_evaluations, rng)
+                .into_iter(),
+        )
+    }
+
     fn mutates(&self) -> bool;
+
+    // Whether the offspring are created from (and ordered by) pairs of parents
+    fn offspring_are_paired(&self) -> bool {
+        false
+    }
+
+    // The number of offspring per parent, e.g. the generation gap of an EDA
+    fn offspring_ratio(&self) -> f64 {
+        1.0
+    }
+
+    // The number of offspring per generation, if it does not depend on the number of parents
+    // (e.g. when only the most promising offspring are kept)
+    fn fixed_offspring_count(&self) -> Option<usize> {
+        None
+    }
+
+    // Whether the operator already accepts or rejects changes based on fitness (as optimal mixing
+    // does), so that its offspring should replace the population as is
+    fn selects_offspring(&self) -> bool {
+        false
+    }
+}
+
+// Counts the offspring created by an operator, and how many of those differ from their parents
+#[derive(Debug, Default)]
+pub struct OperatorStatistics {
+    produced: AtomicUsize,
+    effective: AtomicUsize,
+}
+
+impl OperatorStatistics {
+    pub fn record_crossover<Gnt, A, F>(
+        &self,
+        parent_a: &Individual<Gnt, A, F>,
+        parent_b: &Individual<Gnt, A, F>,
+        children: &[Individual<Gnt, A, F>],
+    ) where
+        A: Allele,
+        F: Fitness,
+        Gnt: Genotype<A>,
+    {
+        let effective = children
+            .iter()
+            .filter(|child| {
+                !child.genotype().iter().eq(parent_a.genotype().iter())
+                    && !child.genotype().iter().eq(parent_b.genotype().iter())
+            })
+            .count();
+
+        self.produced.fetch_add(children.len(), Ordering::Relaxed);
+        self.effective.fetch_add(effective, Ordering::Relaxed);
+    }
+
+    // Records a mutated child, which is effective if it differs from its parent
+    pub fn record_mutation<Gnt, A>(&self, parent: &Gnt, child: &Gnt)
+    where
+        A: Allele,
+        Gnt: Genotype<A>,
+    {
+        self.produced.fetch_add(1, Ordering::Relaxed);
+        if !child.iter().eq(parent.iter()) {
+            self.effective.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // Records children sampled from a model, which are effective if they differ from every
+    // individual of the population that the model was estimated from
+    pub fn record_samples<Gnt, A, F>(
+        &self,
+        population: &[Individual<Gnt, A, F>],
+        children: &[Individual<Gnt, A, F>],
+    ) where
+        A: Allele + Discrete,
+        F: Fitness,
+        Gnt: Genotype<A>,
+    {
+        let known = genotype_set(population);
+        for child in children {
+            self.record_sample(&known, child.genotype());
+        }
+    }
+
+    fn record_sample<Gnt, A>(&self, known: &HashSet<Vec<A>>, genotype: &Gnt)
+    where
+        A: Allele + Discrete,
+        Gnt: Genotype<A>,
+    {
+        self.produced.fetch_add(1, Ordering::Relaxed);
+        if !known.contains(&genotype.iter().collect::<Vec<_>>()) {
+            self.effective.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn offspring_produced(&self) -> usize {
+        self.produced.load(Ordering::Relaxed)
+    }
+
+    pub fn effective_offspring(&self) -> usize {
+        self.effective.load(Ordering::Relaxed)
+    }
+}
+
+fn genotype_set<Gnt, A, F>(population: &[Individual<Gnt, A, F>]) -> HashSet<Vec<A>>
+where
+    A: Allele + Discrete,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    population
+        .iter()
+        .map(|idv| idv.genotype().iter().collect())
+        .collect()
+}
+
+impl Clone for OperatorStatistics {
+    fn clone(&self) -> Self {
+        Self {
+            produced: AtomicUsize::new(self.offspring_produced()),
+            effective: AtomicUsize::new(self.effective_offspring()),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -38,21 +195,27 @@ where
     Gnt: Genotype<A>,
 {
     fn create_offspring(
-        &self,
+        &mut self,
         population: &[Individual<Gnt, A, F>],
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        _rng: &mut SeededRng,
     ) -> Vec<Individual<Gnt, A, F>> {
-        let offspring = population
-            .par_iter()
-            .map(|idv| {
-                let mut child = idv.clone();
+        let n = offspring_count(population.len(), max_new_evaluations);
 
-                fitness_func.evaluate(&mut child);
-
-                child
-            })
-            .collect();
+        // The children are copies of their parents and carry their fitness forward, so only
+        // parents that were never evaluated need an evaluation
+        let mut offspring = population.to_vec();
+        offspring.iter_mut().for_each(Individual::reset_age);
+        offspring
+            .iter_mut()
+            .filter(|idv| !idv.is_evaluated())
+            .take(n)
+            .for_each(|child| {
+                fitness_func.evaluate(child);
+            });
 
+        offspring.retain(|idv| idv.is_evaluated());
         offspring
     }
 
@@ -65,35 +228,41 @@ where
 #[derivative(Default)]
 pub struct UniformCrossover<Gnt, A>
 where
-    A: Allele + Discrete,
+    A: Allele,
     Gnt: Genotype<A> + Cartesian<A>,
 {
     #[derivative(Default(value = "0.5"))]
     probability: f64,
+    inherit_fitness: bool,
+    statistics: OperatorStatistics,
     _allele: PhantomData<A>,
     _genotype: PhantomData<Gnt>,
 }
 
 impl<Gnt, A> UniformCrossover<Gnt, A>
 where
-    A: Allele + Discrete,
+    A: Allele,
     Gnt: Genotype<A> + Cartesian<A>,
 {
     pub fn with_probability(probability: f64) -> Self {
         Self {
             probability,
+            inherit_fitness: false,
+            statistics: OperatorStatistics::default(),
             _allele: PhantomData,
             _genotype: PhantomData,
         }
     }
 
-    fn crossover<F>(
+    fn crossover<F, R>(
         &self,
         parent_a: &Individual<Gnt, A, F>,
         parent_b: &Individual<Gnt, A, F>,
+        rng: &mut R,
     ) -> Vec<Individual<Gnt, A, F>>
     where
         F: Fitness,
+        R: Rng + ?Sized,
     {
         assert_eq!(
             parent_a.genotype().len(),
@@ -101,22 +270,15 @@ where
             "length of genotypes must be equal"
         );
 
-        let mut rng = rand::thread_rng();
-
-        // Generate an array of booleans
-        // true indicates that the gene should be crossed over
-        let choices: Vec<_> = (0..parent_a.genotype().len())
-            .map(|_| rng.gen_bool(self.probability))
-            .collect();
-
         // Create copies of parent a and b
         let mut offspring_a = parent_a.genotype().clone();
         let mut offspring_b = parent_b.genotype().clone();
 
-        for (idx, b) in choices.iter().enumerate() {
-            if *b {
-                offspring_b.set(idx, parent_a.genotype().get(idx));
-                offspring_a.set(idx, parent_b.genotype().get(idx));
+        // Each gene is crossed over with the crossover probability
+        for idx in 0..parent_a.genotype().len() {
+            if rng.gen_bool(self.probability) {
+                offspring_b.inherit(idx, parent_a.genotype());
+                offspring_a.inherit(idx, parent_b.genotype());
             }
         }
 
@@ -130,25 +292,29 @@ where
 #[derive(Default, Clone)]
 pub struct OnePointCrossover<Gnt, A>
 where
-    A: Allele + Discrete,
+    A: Allele,
     Gnt: Genotype<A>,
 {
+    inherit_fitness: bool,
+    statistics: OperatorStatistics,
     _allele: PhantomData<A>,
     _genotype: PhantomData<Gnt>,
 }
 
 impl<Gnt, A> OnePointCrossover<Gnt, A>
 where
-    A: Allele + Discrete,
+    A: Allele,
     Gnt: Genotype<A> + Cartesian<A>,
 {
-    fn crossover<F>(
+    fn crossover<F, R>(
         &self,
         parent_a: &Individual<Gnt, A, F>,
         parent_b: &Individual<Gnt, A, F>,
+        rng: &mut R,
     ) -> Vec<Individual<Gnt, A, F>>
     where
         F: Fitness,
+        R: Rng + ?Sized,
     {
         assert_eq!(
             parent_a.genotype().len(),
@@ -156,8 +322,6 @@ where
             "length of genotypes must be equal"
         );
 
-        let mut rng = rand::thread_rng();
-
         // Pick a crossover point (both endpoints are included)
         let crossover_point: usize = rng.gen_range(0..parent_a.genotype().len() + 1);
 
@@ -167,8 +331,8 @@ where
 
         for idx in 0..parent_a.genotype().len() {
             if idx >= crossover_point {
-                offspring_b.set(idx, parent_a.genotype().get(idx));
-                offspring_a.set(idx, parent_b.genotype().get(idx));
+                offspring_b.inherit(idx, parent_a.genotype());
+                offspring_a.inherit(idx, parent_b.genotype());
             }
         }
 
@@ -182,25 +346,29 @@ where
 #[derive(Default, Clone)]
 pub struct TwoPointCrossover<Gnt, A>
 where
-    A: Allele + Discrete,
+    A: Allele,
     Gnt: Genotype<A>,
 {
+    inherit_fitness: bool,
+    statistics: OperatorStatistics,
     _gene: PhantomData<A>,
     _genotype: PhantomData<Gnt>,
 }
 
 impl<Gnt, A> TwoPointCrossover<Gnt, A>
 where
-    A: Allele + Discrete,
+    A: Allele,
     Gnt: Genotype<A> + Cartesian<A>,
 {
-    fn crossover<F>(
+    fn crossover<F, R>(
         &self,
         parent_a: &Individual<Gnt, A, F>,
         parent_b: &Individual<Gnt, A, F>,
+        rng: &mut R,
     ) -> Vec<Individual<Gnt, A, F>>
     where
         F: Fitness,
+        R: Rng + ?Sized,
     {
         assert_eq!(
             parent_a.genotype().len(),
@@ -208,8 +376,6 @@ where
             "length of genotypes must be equal"
         );
 
-        let mut rng = rand::thread_rng();
-
         // Pick a crossover point (both endpoints are included)
         let crossover_point_1: usize = rng.gen_range(0..parent_a.genotype().len() + 1);
         let crossover_point_2: usize = rng.gen_range(0..parent_a.genotype().len() + 1);
@@ -222,8 +388,92 @@ where
             if idx >= crossover_point_1.min(crossover_point_2)
                 && idx <= crossover_point_1.max(crossover_point_2)
             {
-                offspring_b.set(idx, parent_a.genotype().get(idx));
-                offspring_a.set(idx, parent_b.genotype().get(idx));
+                offspring_b.inherit(idx, parent_a.genotype());
+                offspring_a.inherit(idx, parent_b.genotype());
+            }
+        }
+
+        vec![
+            Individual::from_genotype(offspring_a),
+            Individual::from_genotype(offspring_b),
+        ]
+    }
+}
+
+// Uniform crossover over the factors of a factorization: each factor is swapped between the
+// parents as a whole, so that building blocks (such as solved traps) are never disrupted
+#[derive(Clone)]
+pub struct BlockUniformCrossover<Gnt, A>
+where
+    A: Allele,
+    Gnt: Genotype<A>,
+{
+    factorization: Factorization,
+    probability: f64,
+    inherit_fitness: bool,
+    statistics: OperatorStatistics,
+    _allele: PhantomData<A>,
+    _genotype: PhantomData<Gnt>,
+}
+
+impl<Gnt, A> BlockUniformCrossover<Gnt, A>
+where
+    A: Allele,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    pub fn with_factorization(factorization: Factorization) -> Self {
+        assert_eq!(
+            factorization.num_loci(),
+            Gnt::LEN,
+            "the factorization must cover every locus of the genotype"
+        );
+
+        Self {
+            factorization,
+            probability: 0.5,
+            inherit_fitness: false,
+            statistics: OperatorStatistics::default(),
+            _allele: PhantomData,
+            _genotype: PhantomData,
+        }
+    }
+
+    // The probability with which each factor is swapped
+    pub fn probability(mut self, probability: f64) -> Self {
+        self.probability = probability;
+        self
+    }
+
+    pub fn factorization(&self) -> &Factorization {
+        &self.factorization
+    }
+
+    fn crossover<F, R>(
+        &self,
+        parent_a: &Individual<Gnt, A, F>,
+        parent_b: &Individual<Gnt, A, F>,
+        rng: &mut R,
+    ) -> Vec<Individual<Gnt, A, F>>
+    where
+        F: Fitness,
+        R: Rng + ?Sized,
+    {
+        assert_eq!(
+            parent_a.genotype().len(),
+            parent_b.genotype().len(),
+            "length of genotypes must be equal"
+        );
+
+        // Create copies of parent a and b
+        let mut offspring_a = parent_a.genotype().clone();
+        let mut offspring_b = parent_b.genotype().clone();
+
+        for factor in self.factorization.iter() {
+            if rng.gen_bool(self.probability) {
+                for &idx in factor {
+                    offspring_b.inherit(idx, parent_a.genotype());
+                    offspring_a.inherit(idx, parent_b.genotype());
+                }
             }
         }
 
@@ -234,44 +484,79 @@ where
     }
 }
 
+// Copies the fitness of an evaluated parent with the same genotype as the child, if there is one.
+// Returns whether the child was given a fitness.
+fn inherit_fitness<Gnt, A, F>(
+    child: &mut Individual<Gnt, A, F>,
+    parents: [&Individual<Gnt, A, F>; 2],
+) -> bool
+where
+    A: Allele,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    let parent = parents.into_iter().find(|parent| {
+        parent.is_evaluated() && parent.genotype().iter().eq(child.genotype().iter())
+    });
+
+    if let Some(parent) = parent {
+        child.set_fitness(parent.fitness());
+    }
+
+    parent.is_some()
+}
+
 macro_rules! impl_two_parent_crossover {
     (for $($t:ty),+) => {
         $(
             impl<Gnt, A, F> VariationOperator<Gnt, A, F> for $t
             where
-                A: Allele + Discrete,
+                A: Allele,
                 F: Fitness,
                 Gnt: Genotype<A> + Cartesian<A>,
             {
                 fn create_offspring(
-                    &self,
+                    &mut self,
                     population: &[Individual<Gnt, A, F>],
                     fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+                    max_new_evaluations: Option<usize>,
+                    rng: &mut SeededRng,
                 ) -> Vec<Individual<Gnt, A, F>>
                 where
                     Self: Sized,
                     F: Fitness,
                 {
-                    let mut rng = rand::thread_rng();
                     // Shuffle the population
                     let mut population: Vec<_> = population.iter().collect();
-                    population.shuffle(&mut rng);
-
-                    let mut population_pairs = Vec::<(_, _)>::new();
+                    population.shuffle(rng);
 
                     // Organize the population into pairs for crossover
-                    for i in 0..population.len() / 2 {
+                    let n = offspring_count(population.len() / 2 * 2, max_new_evaluations);
+                    let mut population_pairs = Vec::<(_, _)>::with_capacity(n.div_ceil(2));
+                    for i in 0..n.div_ceil(2) {
                         population_pairs.push((population[2 * i], population[2 * i + 1]));
                     }
 
                     // Perform crossover and evaluation in parallel
-                    let offspring: Vec<_> = population_pairs
-                        .par_iter()
-                        .flat_map(|(parent1, parent2)| {
-                            let mut children = self.crossover(parent1, parent2);
+                    let offspring: Vec<_> = item_rngs(rng, population_pairs.len())
+                        .into_par_iter()
+                        .zip(population_pairs.par_iter())
+                        .enumerate()
+                        .flat_map(|(i, (mut rng, (parent1, parent2)))| {
+                            let mut children = self.crossover(parent1, parent2, &mut rng);
+
+                            // With an odd cap, the second child of the last pair is not evaluated
+                            if 2 * i + 1 == n {
+                                children.truncate(1);
+                            }
 
-                            fitness_func.evaluate(&mut children[0]);
-                            fitness_func.evaluate(&mut children[1]);
+                            self.statistics.record_crossover(parent1, parent2, &children);
+
+                            for child in children.iter_mut() {
+                                if !(self.inherit_fitness && inherit_fitness(child, [parent1, parent2])) {
+                                    fitness_func.evaluate(child);
+                                }
+                            }
 
                             return children
                         })
@@ -283,17 +568,74 @@ macro_rules! impl_two_parent_crossover {
                 fn mutates(&self) -> bool {
                     false
                 }
+
+                fn offspring_are_paired(&self) -> bool {
+                    true
+                }
         })*
     }
   }
 
+macro_rules! impl_crossover_options {
+    (for $($t:ty),+) => {
+        $(
+            impl<Gnt, A> $t
+            where
+                A: Allele,
+                Gnt: Genotype<A> + Cartesian<A>,
+            {
+                pub fn statistics(&self) -> &OperatorStatistics {
+                    &self.statistics
+                }
+
+                // Lets children that are identical to one of their parents inherit its fitness
+                // instead of being evaluated again
+                pub fn inherit_parent_fitness(mut self, inherit: bool) -> Self {
+                    self.inherit_fitness = inherit;
+                    self
+                }
+            }
+        )*
+    }
+}
+
+impl_crossover_options!(
+    for
+        UniformCrossover<Gnt, A>,
+        OnePointCrossover<Gnt, A>,
+        TwoPointCrossover<Gnt, A>,
+        BlockUniformCrossover<Gnt, A>
+);
+
 impl_two_parent_crossover!(
     for
         UniformCrossover<Gnt, A>,
         OnePointCrossover<Gnt, A>,
-        TwoPointCrossover<Gnt, A>
+        TwoPointCrossover<Gnt, A>,
+        BlockUniformCrossover<Gnt, A>
 );
 
+// Cut-and-splice crossover for variable-length sequences: each parent is cut at its own random
+// point (both ends included), after which the head of each parent is joined to the tail of the
+// other. Unlike the other crossovers, the parents may differ in length and so may the children,
+// although the total length is preserved.
+pub fn cut_and_splice<A, R>(parent_a: &[A], parent_b: &[A], rng: &mut R) -> (Vec<A>, Vec<A>)
+where
+    A: Clone,
+    R: Rng + ?Sized,
+{
+    let cut_a = rng.gen_range(0..=parent_a.len());
+    let cut_b = rng.gen_range(0..=parent_b.len());
+
+    let (head_a, tail_a) = parent_a.split_at(cut_a);
+    let (head_b, tail_b) = parent_b.split_at(cut_b);
+
+    (
+        head_a.iter().chain(tail_b).cloned().collect(),
+        head_b.iter().chain(tail_a).cloned().collect(),
+    )
+}
+
 #[derive(Debug, Clone)]
 pub struct Umda<'a, Gnt, A, D>
 where
@@ -302,6 +644,8 @@ where
     Gnt: Genotype<A>,
 {
     genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+    generation_gap: f64,
+    statistics: OperatorStatistics,
 }
 
 impl<'a, Gnt, A, D> Umda<'a, Gnt, A, D>
@@ -311,7 +655,26 @@ where
     Gnt: Genotype<A>,
 {
     pub fn with_genome(genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>) -> Self {
-        Self { genome }
+        genome.assert_matches_genotype();
+
+        Self {
+            genome,
+            generation_gap: 1.0,
+            statistics: OperatorStatistics::default(),
+        }
+    }
+
+    // The number of offspring sampled per generation, relative to the population size. A gap
+    // other than 1 needs a selection operator that selects survivors, since under
+    // `CopyOffspringSelection` the population would take the size of the offspring.
+    pub fn generation_gap(mut self, generation_gap: f64) -> Self {
+        assert!(generation_gap > 0.0, "the generation gap must be positive");
+        self.generation_gap = generation_gap;
+        self
+    }
+
+    pub fn statistics(&self) -> &OperatorStatistics {
+        &self.statistics
     }
 }
 
@@ -323,31 +686,1047 @@ where
     Gnt: Genotype<A>,
 {
     fn create_offspring(
-        &self,
+        &mut self,
         population: &[Individual<Gnt, A, F>],
         fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
     ) -> Vec<Individual<Gnt, A, F>>
     where
         Self: Sized,
     {
         let model = UnivariateModel::estimate_from_population(self.genome, population);
 
-        (0..population.len())
+        let n = gap_count(population.len(), self.generation_gap);
+
+        let offspring: Vec<_> = item_rngs(rng, offspring_count(n, max_new_evaluations))
             .into_par_iter()
-            .map_init(
-                || rand::thread_rng(), // each thread has its own rng
-                |rng, _| {
-                    let mut child = model.sample(rng);
+            .map(|mut rng| {
+                let mut child = model.sample(&mut rng);
+
+                fitness_func.evaluate(&mut child);
+
+                child
+            })
+            .collect();
+
+        self.statistics.record_samples(population, &offspring);
+        offspring
+    }
+
+    // Draws the same offspring as `create_offspring`, but samples and evaluates them on demand
+    fn create_offspring_iter<'b>(
+        &'b mut self,
+        population: &'b [Individual<Gnt, A, F>],
+        fitness_func: &'b FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Box<dyn Iterator<Item = Individual<Gnt, A, F>> + 'b> {
+        let model = UnivariateModel::estimate_from_population(self.genome, population);
+
+        let n = gap_count(population.len(), self.generation_gap);
+        let known = genotype_set(population);
+        let statistics = &self.statistics;
+
+        Box::new(
+            item_rngs(rng, offspring_count(n, max_new_evaluations))
+                .into_iter()
+                .map(move |mut rng| {
+                    let mut child = model.sample(&mut rng);
 
                     fitness_func.evaluate(&mut child);
+                    statistics.record_sample(&known, child.genotype());
 
                     child
-                },
-            )
-            .collect()
+                }),
+        )
     }
 
     fn mutates(&self) -> bool {
         false
     }
+
+    fn offspring_ratio(&self) -> f64 {
+        self.generation_gap
+    }
+}
+
+// An EDA that estimates a marginal product model over a fixed, user-supplied factorization each
+// generation, rather than learning one like ECGA does
+#[derive(Debug, Clone)]
+pub struct FixedLinkageEda<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A>,
+{
+    genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+    factorization: Factorization,
+    generation_gap: f64,
+    statistics: OperatorStatistics,
+}
+
+impl<'a, Gnt, A, D> FixedLinkageEda<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A>,
+{
+    pub fn with_factorization(
+        genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+        factorization: Factorization,
+    ) -> Self {
+        genome.assert_matches_genotype();
+        assert_eq!(
+            factorization.num_loci(),
+            genome.len(),
+            "the factorization must cover every locus of the genome"
+        );
+
+        Self {
+            genome,
+            factorization,
+            generation_gap: 1.0,
+            statistics: OperatorStatistics::default(),
+        }
+    }
+
+    // The number of offspring sampled per generation, relative to the population size (see
+    // `Umda::generation_gap`)
+    pub fn generation_gap(mut self, generation_gap: f64) -> Self {
+        assert!(generation_gap > 0.0, "the generation gap must be positive");
+        self.generation_gap = generation_gap;
+        self
+    }
+
+    pub fn statistics(&self) -> &OperatorStatistics {
+        &self.statistics
+    }
+
+    pub fn factorization(&self) -> &Factorization {
+        &self.factorization
+    }
+}
+
+impl<'a, Gnt, A, D, F> VariationOperator<Gnt, A, F> for FixedLinkageEda<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>>
+    where
+        Self: Sized,
+    {
+        let model = MultivariateModel::estimate_from_population(
+            self.genome,
+            &population.iter().collect::<Vec<_>>(),
+            self.factorization.clone(),
+        );
+
+        let n = gap_count(population.len(), self.generation_gap);
+
+        let offspring: Vec<_> = item_rngs(rng, offspring_count(n, max_new_evaluations))
+            .into_par_iter()
+            .map(|mut rng| {
+                let mut child = model.sample(&mut rng);
+
+                fitness_func.evaluate(&mut child);
+
+                child
+            })
+            .collect();
+
+        self.statistics.record_samples(population, &offspring);
+        offspring
+    }
+
+    fn mutates(&self) -> bool {
+        false
+    }
+
+    fn offspring_ratio(&self) -> f64 {
+        self.generation_gap
+    }
+}
+
+// An EDA that hedges over several candidate model structures. Each generation a model is estimated
+// for the univariate factorization and every candidate factorization, and each offspring is sampled
+// from one of them, chosen with a weight of 2^-MDL (the combined complexity, as used by ECGA, relative
+// to the best model), so that models that describe the population well are sampled most.
+#[derive(Debug, Clone)]
+pub struct MixtureEda<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A>,
+{
+    genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+    factorizations: Vec<Factorization>,
+    weights: Vec<f64>,
+    generation_gap: f64,
+    statistics: OperatorStatistics,
+}
+
+impl<'a, Gnt, A, D> MixtureEda<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A>,
+{
+    // The univariate factorization is always included as the first candidate
+    pub fn with_factorizations(
+        genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+        candidates: Vec<Factorization>,
+    ) -> Self {
+        genome.assert_matches_genotype();
+
+        let univariate = Factorization::univariate(genome.len());
+        let mut factorizations = vec![univariate.clone()];
+
+        for factorization in candidates {
+            assert_eq!(
+                factorization.num_loci(),
+                genome.len(),
+                "every factorization must cover every locus of the genome"
+            );
+
+            if factorization != univariate {
+                factorizations.push(factorization);
+            }
+        }
+
+        Self {
+            genome,
+            factorizations,
+            weights: Vec::new(),
+            generation_gap: 1.0,
+            statistics: OperatorStatistics::default(),
+        }
+    }
+
+    // The number of offspring sampled per generation, relative to the population size (see
+    // `Umda::generation_gap`)
+    pub fn generation_gap(mut self, generation_gap: f64) -> Self {
+        assert!(generation_gap > 0.0, "the generation gap must be positive");
+        self.generation_gap = generation_gap;
+        self
+    }
+
+    pub fn statistics(&self) -> &OperatorStatistics {
+        &self.statistics
+    }
+
+    pub fn factorizations(&self) -> &[Factorization] {
+        &self.factorizations
+    }
+
+    // The normalized mixture weight of each factorization in the most recent generation
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+}
+
+impl<'a, Gnt, A, D, F> VariationOperator<Gnt, A, F> for MixtureEda<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>>
+    where
+        Self: Sized,
+    {
+        let selection: Vec<_> = population.iter().collect();
+
+        let models: Vec<_> = self
+            .factorizations
+            .iter()
+            .map(|factorization| {
+                MultivariateModel::estimate_from_population(
+                    self.genome,
+                    &selection,
+                    factorization.clone(),
+                )
+            })
+            .collect();
+
+        let complexities: Vec<_> = models
+            .iter()
+            .map(|model| model.combined_complexity())
+            .collect();
+        let best = complexities.iter().copied().fold(f64::INFINITY, f64::min);
+
+        let weights: Vec<_> = complexities.iter().map(|c| (best - c).exp2()).collect();
+        let total: f64 = weights.iter().sum();
+        self.weights = weights.iter().map(|w| w / total).collect();
+
+        let mixture = CumulativeDistribution::from_weights(weights);
+
+        let n = gap_count(population.len(), self.generation_gap);
+
+        let offspring: Vec<_> = item_rngs(rng, offspring_count(n, max_new_evaluations))
+            .into_par_iter()
+            .map(|mut rng| {
+                let mut child = models[mixture.sample(&mut rng)].sample(&mut rng);
+
+                fitness_func.evaluate(&mut child);
+
+                child
+            })
+            .collect();
+
+        self.statistics.record_samples(population, &offspring);
+        offspring
+    }
+
+    fn mutates(&self) -> bool {
+        false
+    }
+
+    fn offspring_ratio(&self) -> f64 {
+        self.generation_gap
+    }
+}
+
+// Climbs from each individual to a local optimum, trying every alternative allele of each locus in
+// turn and taking every move that improves the fitness. Every trial counts as an evaluation.
+#[derive(Debug, Clone)]
+pub struct FirstImprovementHillClimb<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+}
+
+impl<'a, Gnt, A, D> FirstImprovementHillClimb<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    pub fn with_genome(genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>) -> Self {
+        genome.assert_matches_genotype();
+
+        Self { genome }
+    }
+}
+
+impl<'a, Gnt, A, D, F> VariationOperator<Gnt, A, F> for FirstImprovementHillClimb<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        _rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>> {
+        hill_climb(
+            self.genome,
+            population,
+            fitness_func,
+            max_new_evaluations,
+            false,
+        )
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+}
+
+// As `FirstImprovementHillClimb`, but tries all neighbours of the current genotype before taking
+// the move that improves the fitness the most
+#[derive(Debug, Clone)]
+pub struct BestImprovementHillClimb<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>,
+}
+
+impl<'a, Gnt, A, D> BestImprovementHillClimb<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    pub fn with_genome(genome: &'a Genome<Gnt, A, DiscreteGene<A, D>>) -> Self {
+        genome.assert_matches_genotype();
+
+        Self { genome }
+    }
+}
+
+impl<'a, Gnt, A, D, F> VariationOperator<Gnt, A, F> for BestImprovementHillClimb<'a, Gnt, A, D>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    fn create_offspring(
+        &mut self,
+        population: &[Individual<Gnt, A, F>],
+        fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+        max_new_evaluations: Option<usize>,
+        _rng: &mut SeededRng,
+    ) -> Vec<Individual<Gnt, A, F>> {
+        hill_climb(
+            self.genome,
+            population,
+            fitness_func,
+            max_new_evaluations,
+            true,
+        )
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+}
+
+// Climbs from each individual in turn until the evaluations run out. The individuals are climbed
+// one after another, so that a capped generation spends its evaluations on the first ones.
+fn hill_climb<Gnt, A, D, F>(
+    genome: &Genome<Gnt, A, DiscreteGene<A, D>>,
+    population: &[Individual<Gnt, A, F>],
+    fitness_func: &FitnessFunc<'_, Gnt, A, F>,
+    max_new_evaluations: Option<usize>,
+    best_improvement: bool,
+) -> Vec<Individual<Gnt, A, F>>
+where
+    A: Allele + Discrete,
+    D: DiscreteDomain<A>,
+    F: Fitness,
+    Gnt: Genotype<A> + Cartesian<A>,
+{
+    let mut remaining = max_new_evaluations.unwrap_or(usize::MAX);
+    let mut offspring = Vec::with_capacity(population.len());
+
+    for parent in population {
+        if remaining == 0 {
+            break;
+        }
+
+        let mut current = parent.clone();
+        if !current.is_evaluated() {
+            fitness_func.evaluate(&mut current);
+            remaining -= 1;
+        }
+
+        let mut genotype = current.genotype().clone();
+        let mut fitness = current.fitness();
+
+        // Passes over all loci until a pass finds no improving move (or the evaluations run out)
+        loop {
+            let mut improved = false;
+            let mut best_move: Option<(usize, A, F)> = None;
+
+            'pass: for (locus, gene) in genome.iter().enumerate() {
+                let allele = genotype.get(locus);
+
+                for alternative in gene.domain().iter().filter(|a| *a != allele) {
+                    if remaining == 0 {
+                        break 'pass;
+                    }
+
+                    genotype.set(locus, alternative);
+                    let trial = fitness_func.evaluate_genotype(&genotype);
+                    genotype.set(locus, allele);
+                    remaining -= 1;
+
+                    if best_improvement {
+                        let best = best_move.map_or(fitness, |(_, _, best)| best);
+                        if fitness_func.cmp(&trial, &best).is_lt() {
+                            best_move = Some((locus, alternative, trial));
+                        }
+                    } else if fitness_func.cmp(&trial, &fitness).is_lt() {
+                        // Take the move and continue with the next locus
+                        genotype.set(locus, alternative);
+                        fitness = trial;
+                        improved = true;
+                        break;
+                    }
+                }
+            }
+
+            if let Some((locus, allele, trial)) = best_move {
+                genotype.set(locus, allele);
+                fitness = trial;
+                improved = true;
+            }
+
+            if !improved || remaining == 0 {
+                break;
+            }
+        }
+
+        let mut child = Individual::from_genotype(genotype);
+        child.set_fitness(fitness);
+        offspring.push(child);
+    }
+
+    offspring
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use approx::assert_abs_diff_eq;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        ecga::Ecga, fitness::OptimizationGoal, gene::BoolDomain, genotype::SizedArray,
+        testing::SequenceRng,
+    };
+
+    const N: usize = 8;
+    type Gnt = [bool; N];
+    type Ftnss = usize;
+
+    fn is_paired<V: VariationOperator<Gnt, bool, Ftnss>>(operator: &V) -> bool {
+        operator.offspring_are_paired()
+    }
+
+    #[test]
+    fn crossovers_report_paired_offspring() {
+        assert!(is_paired(&UniformCrossover::default()));
+        assert!(is_paired(&OnePointCrossover::default()));
+        assert!(is_paired(&TwoPointCrossover::default()));
+    }
+
+    #[test]
+    fn crossover_of_identical_parents_is_not_effective() {
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let population =
+            vec![
+                Individual::from_genotype([true, false, true, true, false, false, true, false]);
+                10
+            ];
+
+        let mut crossover = UniformCrossover::default();
+        crossover.create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::from_entropy(),
+        );
+
+        assert_eq!(crossover.statistics().offspring_produced(), 10);
+        assert_eq!(crossover.statistics().effective_offspring(), 0);
+    }
+
+    #[test]
+    fn eda_statistics_count_new_samples() {
+        use crate::{selection::TruncationSelection, simplega::SimpleGABuilder};
+
+        const POPULATION_SIZE: usize = 20;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let evaluation_func = |genotype: &Gnt| genotype.iter().filter(|bit| *bit).count();
+
+        let mut ga = SimpleGABuilder::new()
+            .genome(&genome)
+            .random_population(POPULATION_SIZE)
+            .evaluation_function(&evaluation_func)
+            .goal(OptimizationGoal::Maximize)
+            .selection(TruncationSelection::default())
+            .variation(Umda::with_genome(&genome))
+            .seed(0)
+            .build();
+        ga.run(POPULATION_SIZE * 30);
+
+        // The population converges, after which the model only samples copies of it
+        let statistics = ga.variation_operator().statistics();
+        assert_eq!(
+            statistics.offspring_produced(),
+            ga.evaluations() - POPULATION_SIZE
+        );
+        assert!(statistics.effective_offspring() > 0);
+        assert!(statistics.effective_offspring() < statistics.offspring_produced());
+    }
+
+    #[test]
+    fn copies_of_parents_inherit_their_fitness() {
+        let evaluation_func = |genotype: &Gnt| genotype.iter().filter(|bit| *bit).count();
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let mut population =
+            vec![
+                Individual::from_genotype([true, false, true, true, false, false, true, false]);
+                10
+            ];
+        population.iter_mut().for_each(|idv| {
+            fitness_func.evaluate(idv);
+        });
+
+        let mut crossover = UniformCrossover::default().inherit_parent_fitness(true);
+        let offspring = crossover.create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::seed_from_u64(0),
+        );
+
+        assert_eq!(offspring.len(), 10);
+        assert!(offspring.iter().all(|idv| idv.fitness() == 4));
+        assert_eq!(fitness_func.evaluations(), 10);
+    }
+
+    #[test]
+    fn umda_respects_evaluation_cap() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let evaluation_func = |genotype: &Gnt| genotype.iter().filter(|bit| *bit).count();
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let mut rng = rand::thread_rng();
+        let population: Vec<_> = (0..100)
+            .map(|_| Individual::from_genotype(genome.sample_uniform(&mut rng)))
+            .collect();
+
+        let offspring = Umda::with_genome(&genome).create_offspring(
+            &population,
+            &fitness_func,
+            Some(50),
+            &mut SeededRng::from_entropy(),
+        );
+
+        assert_eq!(offspring.len(), 50);
+        assert_eq!(fitness_func.evaluations(), 50);
+    }
+
+    #[test]
+    fn umda_with_generation_gap() {
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let evaluation_func = |genotype: &Gnt| genotype.iter().filter(|bit| *bit).count();
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let mut rng = rand::thread_rng();
+        let population: Vec<_> = (0..100)
+            .map(|_| Individual::from_genotype(genome.sample_uniform(&mut rng)))
+            .collect();
+
+        let mut umda = Umda::with_genome(&genome).generation_gap(2.0);
+        let offspring = umda.create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::from_entropy(),
+        );
+
+        assert_eq!(offspring.len(), 200);
+        assert_eq!(fitness_func.evaluations(), 200);
+
+        // The evaluation cap still applies
+        let offspring = umda.create_offspring(
+            &population,
+            &fitness_func,
+            Some(150),
+            &mut SeededRng::from_entropy(),
+        );
+        assert_eq!(offspring.len(), 150);
+    }
+
+    #[test]
+    fn crossover_respects_odd_evaluation_cap() {
+        let evaluation_func = |_: &Gnt| 0;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let population = vec![Individual::from_genotype([false; N]); 10];
+
+        let offspring = UniformCrossover::default().create_offspring(
+            &population,
+            &fitness_func,
+            Some(5),
+            &mut SeededRng::from_entropy(),
+        );
+
+        assert_eq!(offspring.len(), 5);
+        assert_eq!(fitness_func.evaluations(), 5);
+    }
+
+    #[test]
+    fn fixed_linkage_solves_trap_faster_than_umda() {
+        use crate::simplega::{SimpleGABuilder, Status};
+
+        const K: usize = 4;
+        const M: usize = 5;
+        type TrapGnt = [bool; K * M];
+
+        fn trap(genotype: &TrapGnt) -> usize {
+            genotype
+                .chunks(K)
+                .map(|block| match block.iter().filter(|bit| **bit).count() {
+                    K => K,
+                    ones => K - 1 - ones,
+                })
+                .sum()
+        }
+
+        fn run<V>(
+            genome: &Genome<TrapGnt, bool, DiscreteGene<bool, BoolDomain>>,
+            variation: V,
+        ) -> Status
+        where
+            V: VariationOperator<TrapGnt, bool, usize>,
+        {
+            SimpleGABuilder::new()
+                .genome(genome)
+                .random_population(500)
+                .evaluation_function(&trap)
+                .goal(OptimizationGoal::Maximize)
+                .selection(crate::selection::TruncationSelection::default())
+                .variation(variation)
+                .target(K * M)
+                .build()
+                .run(100_000)
+        }
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let factorization =
+            Factorization::from_groups((0..M).map(|b| (b * K..(b + 1) * K).collect()).collect());
+
+        let Status::TargetReached(fixed_evaluations) = run(
+            &genome,
+            FixedLinkageEda::with_factorization(&genome, factorization),
+        ) else {
+            panic!("the fixed linkage EDA did not solve the trap");
+        };
+
+        match run(&genome, Umda::with_genome(&genome)) {
+            Status::TargetReached(umda_evaluations) => {
+                assert!(umda_evaluations > 2 * fixed_evaluations)
+            }
+            Status::BudgetReached(_)
+            | Status::Converged(_)
+            | Status::StoppedByCallback(_)
+            | Status::ConditionMet(_) => (),
+        }
+    }
+
+    #[test]
+    fn uniform_crossover_on_real_genotypes() {
+        type RealGnt = [f64; N];
+
+        let evaluation_func = |genotype: &RealGnt| genotype.iter().sum::<f64>();
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Minimize);
+
+        let parent_a: RealGnt = [0.5, 1.5, 2.5, 3.5, 4.5, 5.5, 6.5, 7.5];
+        let parent_b: RealGnt = [-0.5, -1.5, -2.5, -3.5, -4.5, -5.5, -6.5, -7.5];
+        let population = vec![
+            Individual::from_genotype(parent_a),
+            Individual::from_genotype(parent_b),
+        ];
+
+        let offspring = UniformCrossover::default().create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::from_entropy(),
+        );
+
+        assert_eq!(offspring.len(), 2);
+        for child in offspring {
+            for (idx, allele) in child.genotype().iter().enumerate() {
+                assert!(allele == parent_a[idx] || allele == parent_b[idx]);
+            }
+        }
+    }
+
+    #[test]
+    fn edas_report_unpaired_offspring() {
+        let genome: Genome<Gnt, bool, DiscreteGene<bool, BoolDomain>> = Genome::with_bool_domain();
+
+        assert!(!is_paired(&Umda::with_genome(&genome)));
+        assert!(!is_paired(&Ecga::with_genome(&genome, 0.5)));
+        assert!(!is_paired(&NoVariation));
+    }
+
+    #[test]
+    fn mixture_eda_recovers_pairwise_dependency() {
+        const POPULATION_SIZE: usize = 500;
+
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let evaluation_func = |genotype: &Gnt| genotype.iter().filter(|bit| *bit).count();
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        // Loci 0 and 1 are always equal, while the other loci are independently set 90% of the time
+        let mut rng = SeededRng::seed_from_u64(3);
+        let population: Vec<_> = (0..POPULATION_SIZE)
+            .map(|_| {
+                let linked = rng.gen_bool(0.5);
+                let mut genotype = [false; N];
+                genotype[0] = linked;
+                genotype[1] = linked;
+                for allele in genotype.iter_mut().skip(2) {
+                    *allele = rng.gen_bool(0.9);
+                }
+                Individual::from_genotype(genotype)
+            })
+            .collect();
+
+        let pairwise = Factorization::from_groups(
+            std::iter::once(vec![0, 1])
+                .chain((2..N).map(|i| vec![i]))
+                .collect(),
+        );
+        let mut mixture = MixtureEda::with_factorizations(&genome, vec![pairwise]);
+
+        let offspring = mixture.create_offspring(&population, &fitness_func, None, &mut rng);
+
+        // The model with the linked pair describes the population far better
+        assert!(mixture.weights()[1] > 0.99);
+
+        let linked = offspring
+            .iter()
+            .filter(|idv| idv.genotype()[0] == idv.genotype()[1])
+            .count();
+        assert!(linked as f64 / offspring.len() as f64 > 0.99);
+
+        for i in 2..N {
+            let set = offspring.iter().filter(|idv| idv.genotype()[i]).count();
+            assert_abs_diff_eq!(set as f64 / offspring.len() as f64, 0.9, epsilon = 0.05);
+        }
+    }
+
+    #[test]
+    fn uniform_crossover_on_ndarray_genotype() {
+        type ArrayGnt = SizedArray<f64, N>;
+
+        let evaluation_func = |genotype: &ArrayGnt| genotype.as_array().sum() as i64;
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+
+        let population: Vec<Individual<ArrayGnt, f64, i64>> = vec![
+            Individual::from_genotype(ndarray::Array::zeros(N).into()),
+            Individual::from_genotype(ndarray::Array::ones(N).into()),
+        ];
+
+        let mut crossover = UniformCrossover::default();
+        let offspring = crossover.create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut SeededRng::from_entropy(),
+        );
+
+        assert_eq!(offspring.len(), 2);
+        // Every locus is taken from one parent by one child, and from the other parent by the other
+        let sum = offspring[0].genotype().as_array() + offspring[1].genotype().as_array();
+        assert!(sum.iter().all(|v| *v == 1.0));
+        assert_eq!(offspring[0].fitness() + offspring[1].fitness(), N as i64);
+    }
+
+    #[test]
+    fn uniform_crossover_swaps_chosen_loci() {
+        let crossover = UniformCrossover::default();
+        let parent_a: Individual<Gnt, bool, Ftnss> = Individual::from_genotype([false; N]);
+        let parent_b = Individual::from_genotype([true; N]);
+
+        // Values below the crossover probability of 0.5 swap the locus
+        let mut rng = SequenceRng::from_unit_floats(&[0.9, 0.1, 0.9, 0.1, 0.9, 0.9, 0.9, 0.9]);
+        let offspring = crossover.crossover(&parent_a, &parent_b, &mut rng);
+
+        let mut expected = [false; N];
+        expected[1] = true;
+        expected[3] = true;
+        assert_eq!(offspring[0].genotype(), &expected);
+        assert_eq!(offspring[1].genotype(), &expected.map(|bit| !bit));
+    }
+
+    #[test]
+    fn crossover_carries_step_sizes_with_their_values() {
+        use crate::{
+            genotype::{SelfAdaptiveGenotype, DEFAULT_STEP_SIZE},
+            types::CollectUnsafe,
+        };
+
+        let parent = |value: f64, step_size: f64| {
+            let mut genotype: SelfAdaptiveGenotype<f64, N> =
+                [value; N].into_iter().collect_unsafe();
+            assert_eq!(genotype.step_sizes(), &[DEFAULT_STEP_SIZE; N]);
+            for idx in 0..N {
+                genotype.set_step_size(idx, step_size);
+            }
+            Individual::<_, f64, i64>::from_genotype(genotype)
+        };
+        let (parent_a, parent_b) = (parent(0.0, 0.1), parent(1.0, 2.0));
+
+        let crossover = UniformCrossover::default();
+        let mut rng = SequenceRng::from_unit_floats(&[0.9, 0.1, 0.9, 0.1, 0.9, 0.9, 0.9, 0.9]);
+        let offspring = crossover.crossover(&parent_a, &parent_b, &mut rng);
+
+        for child in &offspring {
+            for idx in 0..N {
+                let expected = if child.genotype().get(idx) == 0.0 {
+                    0.1
+                } else {
+                    2.0
+                };
+                assert_eq!(child.genotype().step_size(idx), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn cut_and_splice_recombines_parents_of_different_lengths() {
+        let parent_a = vec![0u8; 5];
+        let parent_b = vec![1u8; 9];
+        let mut rng = SeededRng::seed_from_u64(7);
+
+        let mut lengths = HashSet::new();
+        for _ in 0..100 {
+            let (child_a, child_b) = cut_and_splice(&parent_a, &parent_b, &mut rng);
+
+            assert_eq!(
+                child_a.len() + child_b.len(),
+                parent_a.len() + parent_b.len()
+            );
+            lengths.insert(child_a.len());
+
+            // Each child is a head of one parent followed by a tail of the other
+            let head_a = child_a.iter().take_while(|&&allele| allele == 0).count();
+            assert!(child_a[head_a..].iter().all(|&allele| allele == 1));
+            let head_b = child_b.iter().take_while(|&&allele| allele == 1).count();
+            assert!(child_b[head_b..].iter().all(|&allele| allele == 0));
+
+            assert_eq!(head_a + child_b.len() - head_b, parent_a.len());
+        }
+
+        assert!(lengths.len() > 2);
+    }
+
+    #[test]
+    fn block_crossover_preserves_solved_traps() {
+        const K: usize = 4;
+        const M: usize = 4;
+
+        type TrapGnt = [bool; K * M];
+
+        fn solved_blocks(genotype: &TrapGnt) -> usize {
+            genotype
+                .chunks(K)
+                .filter(|block| block.iter().all(|bit| *bit))
+                .count()
+        }
+
+        // Parent a has solved the even blocks and parent b the odd blocks
+        let parent_a: Individual<TrapGnt, bool, Ftnss> =
+            Individual::from_genotype(std::array::from_fn(|idx| (idx / K).is_multiple_of(2)));
+        let parent_b =
+            Individual::from_genotype(std::array::from_fn(|idx| !(idx / K).is_multiple_of(2)));
+
+        let factorization =
+            Factorization::from_groups((0..M).map(|b| (b * K..(b + 1) * K).collect()).collect());
+        let block_crossover = BlockUniformCrossover::with_factorization(factorization);
+        let uniform_crossover = UniformCrossover::default();
+
+        let mut rng = SeededRng::seed_from_u64(3);
+        let mut uniform_disruptions = 0;
+
+        for _ in 0..100 {
+            let offspring = block_crossover.crossover(&parent_a, &parent_b, &mut rng);
+            let solved: usize = offspring
+                .iter()
+                .map(|idv| solved_blocks(idv.genotype()))
+                .sum();
+            assert_eq!(solved, M);
+
+            let offspring = uniform_crossover.crossover(&parent_a, &parent_b, &mut rng);
+            let solved: usize = offspring
+                .iter()
+                .map(|idv| solved_blocks(idv.genotype()))
+                .sum();
+            if solved < M {
+                uniform_disruptions += 1;
+            }
+        }
+
+        assert!(uniform_disruptions > 90);
+    }
+
+    #[test]
+    fn hill_climbers_solve_one_max_from_any_start() {
+        let evaluation_func = |genotype: &Gnt| genotype.iter().filter(|bit| *bit).count();
+        let genome = Genome::with_discrete_domain(&BoolDomain);
+        let mut rng = SeededRng::seed_from_u64(0);
+
+        // Every genotype of the search space as a start
+        let population: Vec<Individual<Gnt, bool, Ftnss>> = (0..1usize << N)
+            .map(|bits| Individual::from_genotype(std::array::from_fn(|i| bits >> i & 1 == 1)))
+            .collect();
+
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let offspring = FirstImprovementHillClimb::with_genome(&genome).create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut rng,
+        );
+        assert_eq!(offspring.len(), population.len());
+        assert!(offspring
+            .iter()
+            .all(|idv| idv.genotype() == &[true; N] && idv.fitness() == N));
+        let first_evaluations = fitness_func.evaluations();
+
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let offspring = BestImprovementHillClimb::with_genome(&genome).create_offspring(
+            &population,
+            &fitness_func,
+            None,
+            &mut rng,
+        );
+        assert!(offspring
+            .iter()
+            .all(|idv| idv.genotype() == &[true; N] && idv.fitness() == N));
+        assert!(fitness_func.evaluations() > first_evaluations);
+
+        // From all zeros, a first-improvement pass flips every bit, and a second pass confirms
+        // the optimum
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        FirstImprovementHillClimb::with_genome(&genome).create_offspring(
+            &population[..1],
+            &fitness_func,
+            None,
+            &mut rng,
+        );
+        assert_eq!(fitness_func.evaluations(), 1 + 2 * N);
+
+        // Every trial counts against a capped generation
+        let fitness_func = FitnessFunc::new(&evaluation_func, OptimizationGoal::Maximize);
+        let offspring = FirstImprovementHillClimb::with_genome(&genome).create_offspring(
+            &population,
+            &fitness_func,
+            Some(20),
+            &mut rng,
+        );
+        assert_eq!(fitness_func.evaluations(), 20);
+        assert!(offspring.len() < population.len());
+    }
 }
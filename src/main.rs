@@ -2,23 +2,7 @@ pub extern crate rustea_lib;
 
 use std::time::Instant;
 
-use rustea_lib::bdom;
-use rustea_lib::fitness::Fitness;
-use rustea_lib::gene::Gene;
-use rustea_lib::genotype::{Genotype, SizedVec};
-use rustea_lib::selection::SelectionOperator;
-use rustea_lib::variation::VariationOperator;
-
-use rustea_lib::{
-    ecga::Ecga,
-    fitness::OptimizationGoal,
-    gene::BoolDomain,
-    genome::Genome,
-    selection::TruncationSelection,
-    simplega::SimpleGABuilder,
-    simplega::Status,
-    variation::{Umda, UniformCrossover},
-};
+use rustea_lib::prelude::*;
 
 const K: usize = 5;
 const M: usize = 12;
@@ -62,39 +46,6 @@ fn deceptive_trap(genotype: &Gnt) -> usize {
         .sum()
 }
 
-fn measure_success_rate<G, F, S, V>(
-    builder: SimpleGABuilder<Gnt, AlleleType, G, F, S, V>,
-    n: usize,
-) -> f64
-where
-    G: Gene<AlleleType>,
-    F: Fitness,
-    S: SelectionOperator,
-    V: VariationOperator<Gnt, AlleleType, F>,
-{
-    let mut success_count = 0usize;
-    for i in 0..n {
-        let mut ga = builder.clone().build();
-        let status = ga.run(EVAL_BUDGET);
-
-        match status {
-            Status::TargetReached(_) => success_count += 1,
-            Status::BudgetReached(_) => (),
-        }
-
-        println!(
-            "[{}/{}] Finished with status: {:?}. Best fitness: {:?}, Worst fitness: {:?}",
-            i + 1,
-            n,
-            status,
-            ga.best_individual().unwrap().fitness(),
-            ga.worst_individual().unwrap().fitness()
-        )
-    }
-
-    return (success_count as f64) / (n as f64);
-}
-
 fn main() {
     let genome = Genome::with_discrete_domain(&bdom!());
 
@@ -103,7 +54,7 @@ fn main() {
         .random_population(POPULATION_SIZE)
         .evaluation_function(&deceptive_trap)
         .goal(GOAL)
-        .selection(TruncationSelection)
+        .selection(TruncationSelection::default())
         // .variation(UniformCrossover::default())
         // .variation(Umda::with_genome(&genome))
         .variation(Ecga::with_genome(&genome, 0.02))
@@ -111,12 +62,24 @@ fn main() {
 
     // Run EA
     let now = Instant::now();
-    let success_rate = measure_success_rate(builder, RUNS);
+    let result = benchmark(&builder, RUNS, EVAL_BUDGET);
     let elapsed = now.elapsed();
 
+    for (i, status) in result.statuses().iter().enumerate() {
+        println!("[{}/{}] Finished with status: {:?}", i + 1, RUNS, status);
+    }
+
     println!(
         "Elapsed: {:.2?}, Success Rate: {:.2?}",
-        elapsed, success_rate
+        elapsed,
+        result.success_rate()
+    );
+    println!(
+        "Evaluations to target: mean {:?}, median {:?}, std dev {:?}, expected running time {:?}",
+        result.mean_evaluations_to_target(),
+        result.median_evaluations_to_target(),
+        result.std_dev_evaluations_to_target(),
+        result.expected_running_time()
     );
     // println!(
     //     "Best fitness: {}, Worst fitness: {}, Elapsed: {:.2?}, Status: {:?}",